@@ -9,7 +9,136 @@ function getUpdateTime() {
 rust.then(m => {
     let lastRenderTime = Date.now();
 
-    const client = new m.Client();
+    const client = new m.Client("canvas");
+
+    // Restore the key bindings saved by `saveBindings`, e.g. after rebinding for another layout
+    // with `client.bind_key("move_forward", "KeyZ")`.
+    const savedBindings = localStorage.getItem("key-bindings");
+    if (savedBindings !== null) {
+        try {
+            client.load_bindings(savedBindings);
+        } catch (error) {
+            console.warn("Ignoring the saved key bindings:", error.message);
+        }
+    }
+    window.saveBindings = () => localStorage.setItem("key-bindings", client.bindings_json());
+
+    // Record a walkthrough from the console with `startRecording()` and `stopRecording()`, which
+    // downloads it, and replay a downloaded one with `playRecording(file)`.
+    window.startRecording = () => client.start_recording();
+    window.stopRecording = () => {
+        const bytes = client.stop_recording();
+        if (bytes !== undefined) {
+            const link = document.createElement("a");
+            link.href = URL.createObjectURL(new Blob([bytes], { type: "application/octet-stream" }));
+            link.download = "walkthrough.rec";
+            link.click();
+            URL.revokeObjectURL(link.href);
+        }
+    };
+    window.playRecording = async file => client.play_recording(new Uint8Array(await file.arrayBuffer()));
+
+    const cameraMode = document.getElementById("camera-mode");
+    cameraMode.addEventListener("change", () => client.set_camera_mode(cameraMode.value));
+
+    // Plot the typed expression, showing where it fails to parse.
+    const expression = document.getElementById("expression");
+    const expressionError = document.getElementById("expression-error");
+    let surface = null;
+    function plotExpression() {
+        try {
+            if (surface === null) {
+                surface = client.add_surface(expression.value, [-2, 2], [-2, 2], 64);
+            } else {
+                client.set_expression(surface, expression.value);
+            }
+            expressionError.textContent = "";
+        } catch (error) {
+            const position = error.start !== undefined ? ` (column ${error.start + 1})` : "";
+            expressionError.textContent = error.message + position;
+        }
+    }
+    expression.addEventListener("change", plotExpression);
+    plotExpression();
+
+    const gradient = document.getElementById("gradient");
+    gradient.addEventListener("change", () => {
+        if (surface !== null) {
+            client.set_gradient_field(surface, gradient.value, 12);
+        }
+    });
+
+    const contours = document.getElementById("contours");
+    contours.addEventListener("change", () => {
+        if (surface !== null) {
+            client.set_contour_count(surface, 12, contours.value);
+        }
+    });
+
+    // Shift + click on the surface to put the tangent plane there.
+    const tangentPlane = document.getElementById("tangent-plane");
+    const tangentPlaneEquation = document.getElementById("tangent-plane-equation");
+    document.getElementById("canvas").addEventListener("click", event => {
+        if (surface === null || !tangentPlane.checked || !event.shiftKey) {
+            return;
+        }
+        try {
+            const plane = client.pick_tangent_plane(surface, event.offsetX, event.offsetY);
+            if (plane !== null) {
+                tangentPlaneEquation.textContent = plane.equation;
+            }
+        } catch (error) {
+            tangentPlaneEquation.textContent = error.message;
+        }
+    });
+    tangentPlane.addEventListener("change", () => {
+        if (surface !== null && !tangentPlane.checked) {
+            client.hide_tangent_plane(surface);
+            tangentPlaneEquation.textContent = "";
+        }
+    });
+
+    // Mark the critical points of the surface and list them.
+    const criticalPoints = document.getElementById("critical-points");
+    const criticalPointsList = document.getElementById("critical-points-list");
+    function showCriticalPoints() {
+        if (surface === null) {
+            return;
+        }
+        if (criticalPoints.checked) {
+            const points = client.find_critical_points(surface, 12);
+            criticalPointsList.textContent = points
+                .map(p => `${p.kind} (${p.x.toFixed(2)}, ${p.y.toFixed(2)})`)
+                .join(", ");
+        } else {
+            client.hide_critical_points(surface);
+            criticalPointsList.textContent = "";
+        }
+    }
+    criticalPoints.addEventListener("change", showCriticalPoints);
+    expression.addEventListener("change", showCriticalPoints);
+
+    // Extrema of the surface on a constraint curve, by Lagrange multipliers.
+    const constraint = document.getElementById("constraint");
+    const constraintSolutions = document.getElementById("constraint-solutions");
+    constraint.addEventListener("change", () => {
+        if (surface === null) {
+            return;
+        }
+        try {
+            if (constraint.value.trim() === "") {
+                client.hide_constraint(surface);
+                constraintSolutions.textContent = "";
+            } else {
+                const solutions = client.set_constraint(surface, constraint.value);
+                constraintSolutions.textContent = solutions
+                    .map(s => `${s.kind} ${s.z.toFixed(3)} at (${s.x.toFixed(2)}, ${s.y.toFixed(2)}), λ = ${s.lambda.toFixed(3)}`)
+                    .join(", ");
+            }
+        } catch (error) {
+            constraintSolutions.textContent = error.message;
+        }
+    });
 
     function render() {
         window.requestAnimationFrame(render)
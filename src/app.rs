@@ -1,72 +1,33 @@
-use std::sync::Arc;
-use std::sync::Mutex;
+use std::cell::RefCell;
+use std::rc::Rc;
 
 use wasm_bindgen::JsCast;
 use wasm_bindgen::JsValue;
 use wasm_bindgen::prelude::*;
-use web_sys::{EventListener, HtmlCanvasElement};
+use web_sys::{EventTarget, HtmlCanvasElement};
+use serde::{Deserialize, Serialize};
 use super::log;
+use crate::gamepad::GamepadSnapshot;
+use crate::gestures::{ContactId, GestureDelta, Gestures};
+use crate::input::{Action, ActionStates, Keyboard};
 
 
-const KEY_FORWARD   : &str = "KeyW";
-const KEY_BACKWARDS : &str = "KeyS";
-const KEY_LEFT      : &str = "KeyA";
-const KEY_RIGHT     : &str = "KeyD";
-const KEY_UP        : &str = "Space";
-const KEY_DOWN      : &str = "ControlLeft";
-const KEY_ROTATE_LEFT  : &str = "KeyQ";
-const KEY_ROTATE_RIGHT : &str = "KeyE";
-const KEY_TOGGLE_MOUSE_LOCK : &str = "Escape";
-
-pub const KEY_FORWARD_INDEX   : usize = 1;
-pub const KEY_LEFT_INDEX      : usize = 2;
-pub const KEY_BACKWARDS_INDEX : usize = 3;
-pub const KEY_RIGHT_INDEX     : usize = 4;
-pub const KEY_UP_INDEX        : usize = 5;
-pub const KEY_DOWN_INDEX      : usize = 6;
-pub const KEY_ROTATE_LEFT_INDEX  : usize = 7;
-pub const KEY_ROTATE_RIGHT_INDEX : usize = 8;
-
-
-lazy_static! {
-    static ref APP_STATE: Mutex<Arc<AppState>> = Mutex::new(Arc::new(AppState::new()));
-}
-
-pub fn get_current_state() -> AppState {
-    *APP_STATE.lock().unwrap().clone()
-}
-pub fn set_current_state(state: AppState) {
-    let mut data = APP_STATE.lock().unwrap();
-    *data = Arc::new(state);
-}
-pub fn get_state_of_frame_start(dt: f32, width: f32, height: f32) -> AppState {
-    let mut data  = APP_STATE.lock().unwrap();
-    let new_state = AppState {
-        canvas_height: height,
-        canvas_width: width,
-        time: data.time + dt,
-        ..*data.clone()
-    };
-
-    *data = Arc::new(new_state.clone());
-    new_state
-}
-
-
-
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AppState {
     pub canvas_height: f32,
     pub canvas_width: f32,
     pub mouse_locked: bool,
     pub mouse_down: bool,
+    pub mouse_button: i16,  // 0 for the left, 1 for the middle and 2 for the right button.
     pub mouse_x: f32,
     pub mouse_y: f32,
     pub delta_mouse_x: f32,
     pub delta_mouse_y: f32,
+    pub wheel_delta: f32,  // In pixels, positive when scrolling down.
     pub time: f32,
 
-    pub key_pressed: [bool; 9]
+    pub actions: ActionStates,
+    pub gesture: GestureDelta,  // The touch and pen gestures since the last frame.
 }
 
 impl AppState {
@@ -76,154 +37,339 @@ impl AppState {
             canvas_width: 0.,
             mouse_locked: true,
             mouse_down: false,
+            mouse_button: 0,
             mouse_x: -1.,
             mouse_y: -1.,
             delta_mouse_x: 0.,
             delta_mouse_y: 0.,
+            wheel_delta: 0.,
             time: 0.,
-            key_pressed: [false, false, false, false, false, false, false, false, false],
+            actions: ActionStates::default(),
+            gesture: GestureDelta::default(),
         }
     }
 }
 
-pub fn update_mouse_down(event: web_sys::MouseEvent) {
-    let mut data = APP_STATE.lock().unwrap();
-    *data = Arc::new(AppState {
-        mouse_down: true,
-        mouse_x: event.client_x() as f32,
-        mouse_y: data.canvas_height - event.client_y() as f32,
-        ..*data.clone()
-    });
-}
 
-pub fn update_mouse_up(event: web_sys::MouseEvent) {
-    let mut data = APP_STATE.lock().unwrap();
-    *data = Arc::new(AppState {
-        mouse_down: false,
-        mouse_x: event.client_x() as f32,
-        mouse_y: data.canvas_height - event.client_y() as f32,
-        ..*data.clone()
-    });
+/// What the listeners saw, in the form `Input::handle` takes it so it can be recorded. Positions
+/// are from the upper left corner of the canvas.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InputEvent {
+    MouseDown { button: i16, x: f32, y: f32 },
+    MouseUp { x: f32, y: f32 },
+    MouseMove { x: f32, y: f32 },
+    Wheel(f32),
+    TrackpadPinch(f32),
+    KeyDown(String),
+    KeyUp(String),
+    ContactStart { id: ContactId, x: f32, y: f32 },
+    ContactMove { id: ContactId, x: f32, y: f32 },
+    ContactEnd(ContactId),
+    Blur,
 }
 
-pub fn update_mouse_move(event: web_sys::MouseEvent) {
-    let mut data = APP_STATE.lock().unwrap();
-    let inverted_y = data.canvas_height - event.client_y() as f32;
-
-    *data = Arc::new(AppState {
-        mouse_x: event.client_x() as f32,
-        mouse_y: inverted_y,
-        delta_mouse_x: data.mouse_x - event.client_y() as f32,
-        delta_mouse_y: data.mouse_y - inverted_y,
-        ..*data.clone()
-    });
+
+/// The input of one `Client`, updated by the listeners on its canvas. Mouse positions are in
+/// pixels from the lower left corner of the canvas.
+#[derive(Debug, Clone)]
+pub struct Input {
+    state: AppState,
+    pub keyboard: Keyboard,
+    pub gestures: Gestures,
+    // The events handled since the last frame, while recording.
+    recorded: Option<Vec<InputEvent>>,
 }
 
-fn get_key_index(code: &str) -> usize {
-    if code == KEY_FORWARD {
-        KEY_FORWARD_INDEX
-    } else if code == KEY_LEFT {
-        KEY_LEFT_INDEX
-    } else if code == KEY_BACKWARDS {
-        KEY_BACKWARDS_INDEX
-    } else if code == KEY_RIGHT {
-        KEY_RIGHT_INDEX
-    } else if code == KEY_UP {
-        KEY_UP_INDEX
-    } else if code == KEY_DOWN {
-        KEY_DOWN_INDEX
-    } else if code == KEY_ROTATE_LEFT {
-        KEY_ROTATE_LEFT_INDEX
-    } else if code == KEY_ROTATE_RIGHT {
-        KEY_ROTATE_RIGHT_INDEX
-    } else {
-        0
+impl Input {
+    pub fn new() -> Self {
+        Self { state: AppState::new(), keyboard: Keyboard::default(), gestures: Gestures::default(), recorded: None }
     }
-}
 
-pub fn update_key_down(event: web_sys::KeyboardEvent) {
-    let index = get_key_index(&event.code());
-    let mut data = APP_STATE.lock().unwrap();
-    let mut key_pressed = data.key_pressed;
-    key_pressed[index] = true;
+    /// Returns whether the event toggled the mouse lock.
+    pub fn handle(&mut self, event: InputEvent) -> bool {
+        let mut toggled_mouse_lock = false;
+        // Browsers may follow pen events with mouse events for the same stroke, which would drag twice.
+        let from_pen = self.gestures.has_pen();
+        match &event {
+            InputEvent::MouseDown { .. } | InputEvent::MouseMove { .. } if from_pen => {},
+            InputEvent::MouseDown { button, x, y } => self.mouse_down(*button, *x, *y),
+            InputEvent::MouseUp { x, y }           => self.mouse_up(*x, *y),
+            InputEvent::MouseMove { x, y }         => self.mouse_move(*x, *y),
+            InputEvent::Wheel(delta)               => self.wheel(*delta),
+            InputEvent::TrackpadPinch(delta)       => self.gestures.trackpad_pinch(*delta),
+            InputEvent::KeyDown(code)              => self.key_down(code),
+            InputEvent::KeyUp(code)                => toggled_mouse_lock = self.key_up(code),
+            InputEvent::ContactStart { id, x, y }  => self.gestures.start(*id, *x, *y),
+            InputEvent::ContactMove { id, x, y }   => self.gestures.moved(*id, *x, *y),
+            InputEvent::ContactEnd(id)             => self.gestures.end(*id),
+            InputEvent::Blur                       => self.blur(),
+        }
+        if let Some(recorded) = &mut self.recorded {
+            recorded.push(event);
+        }
+        toggled_mouse_lock
+    }
 
-    *data = Arc::new(AppState {
-        key_pressed,
-        ..*data.clone()
-    });
-}
+    /// Keeps the handled events until `take_recorded_events`, or stops keeping them.
+    pub fn set_recording(&mut self, recording: bool) {
+        self.recorded = if recording { Some(Vec::new()) } else { None };
+    }
 
-pub fn update_key_up(event: web_sys::KeyboardEvent) {
-    let code = event.code();
-
-    if code == KEY_TOGGLE_MOUSE_LOCK {
-        let mut data = APP_STATE.lock().unwrap();
-        let mouse_locked = !data.mouse_locked;
-        *data = Arc::new(AppState {
-            mouse_locked,
-            ..*data.clone()
-        });
-        let window   = web_sys::window().expect("no global `window` exists");
-        let document = window.document().expect("should have a document on window");
-        let body     = document.body().expect("document should have a body");
-        if mouse_locked {
-            body.request_pointer_lock();
-        } else {
-            document.exit_pointer_lock();
+    /// The events handled since the last call, while recording.
+    pub fn take_recorded_events(&mut self) -> Vec<InputEvent> {
+        self.recorded.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// The state for the frame, with the mouse movement and wheel scrolling since the last frame,
+    /// and the actions of the keys pressed.
+    pub fn frame_start(&mut self, dt: f32, width: f32, height: f32) -> AppState {
+        let new_state = AppState {
+            canvas_height: height,
+            canvas_width: width,
+            time: self.state.time + dt,
+            actions: self.keyboard.frame_states(&self.state.actions),
+            gesture: self.gestures.take_delta(),
+            ..self.state
+        };
+
+        self.state = AppState {
+            delta_mouse_x: 0.,
+            delta_mouse_y: 0.,
+            wheel_delta: 0.,
+            ..new_state
+        };
+        new_state
+    }
+
+    /// `x` and `y` are from the upper left corner, like the offsets of mouse events.
+    pub fn mouse_down(&mut self, button: i16, x: f32, y: f32) {
+        self.state.mouse_down   = true;
+        self.state.mouse_button = button;
+        self.state.mouse_x = x;
+        self.state.mouse_y = self.state.canvas_height - y;
+    }
+
+    pub fn mouse_up(&mut self, x: f32, y: f32) {
+        self.state.mouse_down = false;
+        self.state.mouse_x = x;
+        self.state.mouse_y = self.state.canvas_height - y;
+    }
+
+    pub fn mouse_move(&mut self, x: f32, y: f32) {
+        let inverted_y = self.state.canvas_height - y;
+        self.state.delta_mouse_x += x - self.state.mouse_x;
+        self.state.delta_mouse_y += inverted_y - self.state.mouse_y;
+        self.state.mouse_x = x;
+        self.state.mouse_y = inverted_y;
+    }
+
+    /// In pixels, positive when scrolling down.
+    pub fn wheel(&mut self, delta: f32) {
+        self.state.wheel_delta += delta;
+    }
+
+    pub fn key_down(&mut self, code: &str) {
+        self.keyboard.key_down(code);
+    }
+
+    /// Returns whether the key toggled the mouse lock.
+    pub fn key_up(&mut self, code: &str) -> bool {
+        self.keyboard.key_up(code);
+        let toggles = self.keyboard.bindings.is_bound(code, Action::ToggleMouseLock);
+        if toggles {
+            self.state.mouse_locked = !self.state.mouse_locked;
         }
-        return;
+        toggles
     }
 
-    let index = get_key_index(&event.code());
-    let mut data = APP_STATE.lock().unwrap();
-    let mut key_pressed = data.key_pressed;
-    key_pressed[index] = false;
+    pub fn mouse_locked(&self) -> bool {
+        self.state.mouse_locked
+    }
 
-    *data = Arc::new(AppState {
-        key_pressed,
-        ..*data.clone()
-    });
+    /// The key up events go elsewhere when the canvas loses focus, so nothing stays held.
+    pub fn blur(&mut self) {
+        self.keyboard.release_all();
+        self.gestures.cancel_all();
+    }
 }
 
 
-// ---- ATTACHMENTS ----
-pub fn attach_mouse_down_callback(canvas: &HtmlCanvasElement, callback: fn(web_sys::MouseEvent)) -> Result<(), JsValue> {
-    let callback = Closure::wrap(Box::new(callback) as Box<dyn FnMut(_)>);
-    canvas.add_event_listener_with_callback("mousedown", callback.as_ref().unchecked_ref())?;
-    callback.forget();
-    Ok(())
-}
+type Listener = (EventTarget, &'static str, Closure<dyn FnMut(web_sys::Event)>);
 
-pub fn attach_mouse_up_callback(canvas: &HtmlCanvasElement, callback: fn(event: web_sys::MouseEvent)) -> Result<(), JsValue> {
-    let callback = Closure::wrap(Box::new(callback) as Box<dyn FnMut(_)>);
-    canvas.add_event_listener_with_callback("mouseup", callback.as_ref().unchecked_ref())?;
-    callback.forget();
-    Ok(())
+/// Event listeners that are removed when dropped, instead of being leaked with `forget`.
+pub struct EventListeners {
+    listeners: Vec<Listener>,
 }
 
-pub fn attach_mouse_move_callback(canvas: &HtmlCanvasElement, callback: fn(event: web_sys::MouseEvent)) -> Result<(), JsValue> {
-    let callback = Closure::wrap(Box::new(callback) as Box<dyn FnMut(_)>);
-    canvas.add_event_listener_with_callback("mousemove", callback.as_ref().unchecked_ref())?;
-    callback.forget();
-    Ok(())
+impl EventListeners {
+    pub fn new() -> Self {
+        Self { listeners: Vec::new() }
+    }
+
+    /// Calls `callback` with the events of type `E`, e.g. `web_sys::MouseEvent` for "mousedown".
+    pub fn add<E, F>(&mut self, target: &EventTarget, event_type: &'static str, mut callback: F) -> Result<(), JsValue>
+        where E: JsCast + 'static,
+              F: FnMut(E) + 'static
+    {
+        let closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            callback(event.unchecked_into::<E>())
+        }) as Box<dyn FnMut(_)>);
+        target.add_event_listener_with_callback(event_type, closure.as_ref().unchecked_ref())?;
+        self.listeners.push((target.clone(), event_type, closure));
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.listeners.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.listeners.is_empty()
+    }
+
+    pub fn remove_all(&mut self) {
+        for (target, event_type, closure) in self.listeners.drain(..) {
+            // Only fails if the target is gone, in which case there's nothing left to remove from.
+            let _ = target.remove_event_listener_with_callback(event_type, closure.as_ref().unchecked_ref());
+        }
+    }
 }
 
-// https://rustwasm.github.io/wasm-bindgen/api/web_sys/struct.KeyboardEvent.html
-pub fn attach_key_down_callback(canvas: &HtmlCanvasElement, callback: fn(event: web_sys::KeyboardEvent)) -> Result<(), JsValue> {
-    let callback = Closure::wrap(Box::new(callback) as Box<dyn FnMut(_)>);
-    canvas.add_event_listener_with_callback("keydown", callback.as_ref().unchecked_ref())?;
-    callback.forget();
-    Ok(())
+impl Drop for EventListeners {
+    fn drop(&mut self) {
+        self.remove_all();
+    }
 }
 
-pub fn attach_key_up_callback(canvas: &HtmlCanvasElement, callback: fn(event: web_sys::KeyboardEvent)) -> Result<(), JsValue> {
-    let callback = Closure::wrap(Box::new(callback) as Box<dyn FnMut(_)>);
-    canvas.add_event_listener_with_callback("keyup", callback.as_ref().unchecked_ref())?;
-    callback.forget();
-    Ok(())
+
+// ---- GAMEPADS ----
+/// The first connected gamepad. Gamepads don't send events when their sticks move, so they are
+/// polled once per frame instead.
+pub fn poll_gamepad() -> Option<GamepadSnapshot> {
+    let gamepads = web_sys::window()?.navigator().get_gamepads().ok()?;
+    let gamepad  = gamepads.iter()
+        .filter_map(|gamepad| gamepad.dyn_into::<web_sys::Gamepad>().ok())
+        .find(|gamepad| gamepad.connected())?;
+
+    let axes = gamepad.axes().iter()
+        .map(|axis| axis.as_f64().unwrap_or(0.0) as f32)
+        .collect();
+    let buttons = gamepad.buttons().iter()
+        .map(|button| button.dyn_into::<web_sys::GamepadButton>().map(|button| button.value() as f32).unwrap_or(0.0))
+        .collect();
+    Some(GamepadSnapshot { axes, buttons })
 }
 
 
+// ---- ATTACHMENTS ----
+/// Listens to the mouse, wheel, touches, pens and keys on the canvas, updating `input`.
+pub fn attach_input_listeners(canvas: &HtmlCanvasElement, input: &Rc<RefCell<Input>>) -> Result<EventListeners, JsValue> {
+    let mut listeners = EventListeners::new();
+
+    let state = input.clone();
+    listeners.add(canvas, "mousedown", move |event: web_sys::MouseEvent| {
+        state.borrow_mut().handle(InputEvent::MouseDown { button: event.button(), x: event.offset_x() as f32, y: event.offset_y() as f32 });
+    })?;
+    let state = input.clone();
+    listeners.add(canvas, "mouseup", move |event: web_sys::MouseEvent| {
+        state.borrow_mut().handle(InputEvent::MouseUp { x: event.offset_x() as f32, y: event.offset_y() as f32 });
+    })?;
+    let state = input.clone();
+    listeners.add(canvas, "mousemove", move |event: web_sys::MouseEvent| {
+        state.borrow_mut().handle(InputEvent::MouseMove { x: event.offset_x() as f32, y: event.offset_y() as f32 });
+    })?;
+
+    let state = input.clone();
+    listeners.add(canvas, "wheel", move |event: web_sys::WheelEvent| {
+        event.prevent_default();  // Don't scroll the page.
+
+        // Lines and pages are roughly converted to pixels.
+        let scale = match event.delta_mode() {
+            web_sys::WheelEvent::DOM_DELTA_LINE => 16.0,
+            web_sys::WheelEvent::DOM_DELTA_PAGE => 800.0,
+            _ => 1.0,
+        };
+        let delta = event.delta_y() as f32 * scale;
+        if event.ctrl_key() {
+            state.borrow_mut().handle(InputEvent::TrackpadPinch(delta));
+        } else {
+            state.borrow_mut().handle(InputEvent::Wheel(delta));
+        }
+    })?;
+
+    // Touches move the camera instead of scrolling or zooming the page.
+    for &event_type in &["touchstart", "touchmove", "touchend", "touchcancel"] {
+        let state = input.clone();
+        let touched_canvas = canvas.clone();
+        listeners.add(canvas, event_type, move |event: web_sys::TouchEvent| {
+            event.prevent_default();
+            let bounds  = touched_canvas.get_bounding_client_rect();
+            let touches = event.changed_touches();
+            let mut input = state.borrow_mut();
+            for touch in (0..touches.length()).filter_map(|i| touches.get(i)) {
+                let id = ContactId::Touch(touch.identifier());
+                let x  = touch.client_x() as f32 - bounds.left() as f32;
+                let y  = touch.client_y() as f32 - bounds.top() as f32;
+                input.handle(match event_type {
+                    "touchstart" => InputEvent::ContactStart { id, x, y },
+                    "touchmove"  => InputEvent::ContactMove { id, x, y },
+                    _            => InputEvent::ContactEnd(id),
+                });
+            }
+        })?;
+    }
+
+    // Pens come as pointer events. Mice and touches are handled by their own events above.
+    for &event_type in &["pointerdown", "pointermove", "pointerup", "pointercancel"] {
+        let state = input.clone();
+        listeners.add(canvas, event_type, move |event: web_sys::PointerEvent| {
+            if event.pointer_type() != "pen" {
+                return;
+            }
+            // Keeps the browser from sending mouse events for the same stroke.
+            if event_type == "pointerdown" {
+                event.prevent_default();
+            }
+            let id = ContactId::Pointer(event.pointer_id());
+            let (x, y) = (event.offset_x() as f32, event.offset_y() as f32);
+            let mut input = state.borrow_mut();
+            input.handle(match event_type {
+                "pointerdown" => InputEvent::ContactStart { id, x, y },
+                "pointermove" => InputEvent::ContactMove { id, x, y },
+                _             => InputEvent::ContactEnd(id),
+            });
+        })?;
+    }
+
+    // Keeps the context menu from opening, so the right button can be dragged with.
+    listeners.add(canvas, "contextmenu", |event: web_sys::MouseEvent| event.prevent_default())?;
+
+    // https://rustwasm.github.io/wasm-bindgen/api/web_sys/struct.KeyboardEvent.html
+    let state = input.clone();
+    listeners.add(canvas, "keydown", move |event: web_sys::KeyboardEvent| {
+        state.borrow_mut().handle(InputEvent::KeyDown(event.code()));
+    })?;
+    let state = input.clone();
+    let locked_canvas = canvas.clone();
+    listeners.add(canvas, "keyup", move |event: web_sys::KeyboardEvent| {
+        let mut input = state.borrow_mut();
+        // Pointer lock can only be requested while handling the event, so this can't wait for the frame.
+        if input.handle(InputEvent::KeyUp(event.code())) {
+            if input.mouse_locked() {
+                locked_canvas.request_pointer_lock();
+            } else {
+                let document = web_sys::window().and_then(|window| window.document());
+                if let Some(document) = document {
+                    document.exit_pointer_lock();
+                }
+            }
+        }
+    })?;
+    let state = input.clone();
+    listeners.add(canvas, "blur", move |_: web_sys::FocusEvent| { state.borrow_mut().handle(InputEvent::Blur); })?;
+
+    Ok(listeners)
+}
 
 
 //
@@ -280,3 +426,8 @@ pub fn attach_key_up_callback(canvas: &HtmlCanvasElement, callback: fn(event: we
 // }
 //
 //
+
+
+#[cfg(test)]
+#[path = "tests/app.rs"]
+mod tests;
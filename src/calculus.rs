@@ -0,0 +1,88 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+use crate::errors::ClientError;
+use crate::expression::{Expression, Variable};
+use crate::plots::parse_components;
+use crate::utils::to_js_value;
+
+
+/// Parses a comma separated list of variable names, e.g. `"x, y"`.
+pub fn parse_variables(source: &str) -> Result<Vec<Variable>, ClientError> {
+    let variables = source
+        .split(',')
+        .map(|name| {
+            let name = name.trim();
+            Variable::from_name(name).ok_or_else(|| ClientError::invalid_argument("variables", format!("Unknown variable '{}'", name)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if variables.is_empty() {
+        return Err(ClientError::invalid_argument("variables", String::from("Expected at least one variable")));
+    }
+    Ok(variables)
+}
+
+fn parse(expression: &str) -> Result<Expression, ClientError> {
+    Expression::parse(expression).map_err(|error| ClientError::parse(error, expression, "expression"))
+}
+
+fn to_strings(expressions: &[Expression]) -> Vec<String> {
+    expressions.iter().map(|expression| expression.to_string()).collect()
+}
+
+
+/// Returns the simplified partial derivative of `expression` with respect to `variable`, e.g.
+/// `differentiate("x^2 * y", "x")` gives `"2 * x * y"`.
+#[wasm_bindgen]
+pub fn differentiate(expression: &str, variable: &str) -> Result<String, JsValue> {
+    let variable = Variable::from_name(variable.trim())
+        .ok_or_else(|| ClientError::invalid_argument("variable", format!("Unknown variable '{}'", variable)))?;
+    Ok(parse(expression)?.derivative(variable).to_string())
+}
+
+/// Returns the partial derivatives with respect to `variables` (e.g. `"x, y"`) as an array of strings.
+#[wasm_bindgen]
+pub fn gradient(expression: &str, variables: &str) -> Result<JsValue, JsValue> {
+    let variables = parse_variables(variables)?;
+    Ok(to_js_value(&to_strings(&parse(expression)?.gradient(&variables))))
+}
+
+/// Returns the Hessian with respect to `variables` as an array of rows of strings.
+#[wasm_bindgen]
+pub fn hessian(expression: &str, variables: &str) -> Result<JsValue, JsValue> {
+    let variables = parse_variables(variables)?;
+    let rows: Vec<Vec<String>> = parse(expression)?.hessian(&variables).iter().map(|row| to_strings(row)).collect();
+    Ok(to_js_value(&rows))
+}
+
+/// Returns the Jacobian of the comma separated components in `expression` (e.g. `"u cos(v), u sin(v)"`)
+/// with respect to `variables`, as an array of rows of strings.
+#[wasm_bindgen]
+pub fn jacobian(expression: &str, variables: &str) -> Result<JsValue, JsValue> {
+    let variables  = parse_variables(variables)?;
+    let components = Expression::parse_tuple(expression, &Variable::ALL).map_err(|error| ClientError::parse(error, expression, "expression"))?;
+    let rows: Vec<Vec<String>> = Expression::jacobian(&components, &variables).iter().map(|row| to_strings(row)).collect();
+    Ok(to_js_value(&rows))
+}
+
+/// Returns the divergence of the vector field given as `P, Q` in x and y or `P, Q, R` in x, y
+/// and z, e.g. `divergence("x y, y z, z x")` gives `"y + z + x"`.
+#[wasm_bindgen]
+pub fn divergence(expression: &str) -> Result<String, JsValue> {
+    let components = Expression::parse_tuple(expression, &Variable::ALL).map_err(|error| ClientError::parse(error, expression, "expression"))?;
+    let variables: &[Variable] = match components.len() {
+        2 => &[Variable::X, Variable::Y],
+        3 => &[Variable::X, Variable::Y, Variable::Z],
+        n => return Err(ClientError::invalid_argument("expression", format!("Expected 2 or 3 comma separated components, got {}", n)).into()),
+    };
+    Ok(Expression::divergence(&components, variables).to_string())
+}
+
+/// Returns the curl of the vector field given as `P, Q, R` in x, y and z, as an array of strings.
+#[wasm_bindgen]
+pub fn curl(expression: &str) -> Result<JsValue, JsValue> {
+    let components = parse_components(expression, 3, &Variable::ALL)?;
+    let components = [components[0].clone(), components[1].clone(), components[2].clone()];
+    Ok(to_js_value(&to_strings(&Expression::curl(&components))))
+}
@@ -1,7 +1,9 @@
-use nalgebra::{Vector3, Matrix4};
+use crate::math::{Matrix4, Vector3};
 use crate::log;
 use wasm_bindgen::__rt::core::f32::consts::PI;
 use std::cmp::{max, min};
+use quaternions::Quaternion;
+use serde::{Deserialize, Serialize};
 
 type Millimeters = f32;
 type Radians = f32;
@@ -53,27 +55,32 @@ pub struct Axis {
     //   up      = normalize(up)
     //   forward = normalize(forward)
     //   right * forwards = right * up = up * forward = 0  (scalar product)
-    pub right:   Vector3<Millimeters>,
-    pub up:      Vector3<Millimeters>,
-    pub forward: Vector3<Millimeters>,
+    //   right = forward x up  (the camera looks down its negative z-axis, so right is -x initially)
+    pub right:   Vector3,
+    pub up:      Vector3,
+    pub forward: Vector3,
 
-    pub yaw: Radians,
-    pub pitch: Radians,
+    /// Rotates the world axes onto the camera's; the vectors above are derived from it.
+    pub orientation: Quaternion,
 }
 
 impl Axis {
-    const WORLD_AXIS_RIGHT:   [f32; 3] = [1.0, 0.0, 0.0];
+    const WORLD_AXIS_RIGHT:   [f32; 3] = [-1.0, 0.0, 0.0];
     const WORLD_AXIS_UP:      [f32; 3] = [0.0, 1.0, 0.0];
     const WORLD_AXIS_FORWARD: [f32; 3] = [0.0, 0.0, 1.0];
 
     pub fn new() -> Self {
-        Self {
-            right:   glm::make_vec3(&Axis::WORLD_AXIS_RIGHT),
-            up:      glm::make_vec3(&Axis::WORLD_AXIS_UP),
-            forward: glm::make_vec3(&Axis::WORLD_AXIS_FORWARD),
+        Self::from_orientation(Quaternion::new())
+    }
 
-            yaw: 0.0,
-            pitch: PI / 2.0,
+    pub fn from_orientation(orientation: Quaternion) -> Self {
+        let orientation = orientation.normalize();
+        let rotate = |axis: &[f32; 3]| orientation.rotate(Vector3::from_array(*axis)).normalize();
+        Self {
+            right:   rotate(&Axis::WORLD_AXIS_RIGHT),
+            up:      rotate(&Axis::WORLD_AXIS_UP),
+            forward: rotate(&Axis::WORLD_AXIS_FORWARD),
+            orientation,
         }
     }
 
@@ -87,35 +94,29 @@ impl Axis {
         }
     }
 
-    pub fn rotate(&mut self, yaw: f32, pitch: f32, roll: f32) {
-        let world_axis_up = glm::make_vec3(&Axis::WORLD_AXIS_UP);
-
-        self.yaw += yaw;  // TODO(ted): fmod 2PI
-        self.pitch = Self::clamp(self.pitch + pitch, 0.001, PI - 0.001);
-
-        // The OpenGL-coordinate system is not like the traditional mathematical system, it's rotated.
-        self.forward.z = self.yaw.cos() * self.pitch.sin();
-        self.forward.x = self.yaw.sin() * self.pitch.sin();
-        self.forward.y = self.pitch.cos();
-
-        // TODO(ted): THIS DOESN'T MAKE SENSE!! Why do we cross with the backwards vector in a
-        //   right handed coordinate system?
-        self.right = glm::cross(&world_axis_up, &-&self.forward);
-        self.up    = glm::cross(&-&self.forward, &self.right);
+    /// Positive yaw turns left (towards world +x initially) around the world's up axis, so yaw and
+    /// pitch together never tilt the horizon. Positive pitch looks down and positive roll tilts the
+    /// up vector to the right, both around the camera's own axes, so looking straight up or down
+    /// doesn't lock an axis.
+    pub fn rotate(&mut self, yaw: Radians, pitch: Radians, roll: Radians) {
+        let around = |angle: Radians, axis: &[f32; 3]| Quaternion::from_axis_rotation(angle, Vector3::from_array(*axis));
+        let world = around(yaw, &Axis::WORLD_AXIS_UP);
+        let local = around(-pitch, &Axis::WORLD_AXIS_RIGHT) * around(roll, &Axis::WORLD_AXIS_FORWARD);
+        *self = Self::from_orientation(world * self.orientation * local);
+    }
 
-        self.forward.normalize_mut();
-        self.right.normalize_mut();
-        self.up.normalize_mut();
+    /// The orientation a fraction `t` of the way to `other`, turning at a constant rate.
+    pub fn slerp(&self, other: &Axis, t: f32) -> Axis {
+        Self::from_orientation(self.orientation.slerp(&other.orientation, t))
     }
 }
 
 
 pub struct FPSCamera {
     pub direction: Axis,
-    pub position: Vector3<Millimeters>,
+    pub position: Vector3,
     pub is_orthographic: bool,
     pub pinhole_camera: PinholeCamera,
-    pub target: Option<Vector3<Millimeters>>,
 }
 
 impl FPSCamera {
@@ -125,36 +126,275 @@ impl FPSCamera {
             direction: Axis::new(),
             is_orthographic: false,
             pinhole_camera: PinholeCamera::new(),
-            target: None,
         }
     }
 
     /// Move the camera relative to it's direction ('move' is reserved, hence the name walk).
     pub fn move_right(&mut self, delta: f32) {
-        self.position += &self.direction.right * delta;
+        self.position += self.direction.right * delta;
     }
     pub fn move_up(&mut self, delta: f32) {
-        self.position += &self.direction.up * delta;
+        self.position += self.direction.up * delta;
     }
     pub fn move_forward(&mut self, delta: f32) {
-        self.position += &self.direction.forward * delta;
+        self.position += self.direction.forward * delta;
     }
 
     pub fn rotate(&mut self, yaw: f32, pitch: f32, roll: f32) {
         self.direction.rotate(yaw, pitch, roll);
     }
 
-    pub fn view_matrix(&self) -> Matrix4<Millimeters> {
-        match &self.target {
-            Some(target) => glm::look_at(&self.position, &target, &self.direction.up),
-            None => glm::look_at(&self.position,  &(&self.position + &self.direction.forward), &self.direction.up)
+    pub fn view_matrix(&self) -> Matrix4 {
+        Matrix4::look_at(self.position, self.position + self.direction.forward, self.direction.up)
+    }
+
+    pub fn projection_matrix(&self) -> Matrix4 {
+        Matrix4::perspective(self.pinhole_camera.aspect_ratio(), PI / 3.0, 0.1, 100.0)
+    }
+}
+
+
+/// A camera circling a focus point at a distance, for inspecting plots. Its direction is kept by
+/// an `Axis` like the `FPSCamera`'s, so both share the same yaw, pitch and roll conventions.
+pub struct OrbitCamera {
+    pub direction: Axis,
+    pub target: Vector3,
+    pub distance: Millimeters,
+    pub pinhole_camera: PinholeCamera,
+}
+
+impl OrbitCamera {
+    pub const MIN_DISTANCE: Millimeters = 0.1;
+    pub const MAX_DISTANCE: Millimeters = 50.0;
+
+    pub fn new(target: Vector3, distance: Millimeters) -> Self {
+        Self {
+            direction: Axis::new(),
+            target,
+            distance: Axis::clamp(distance, Self::MIN_DISTANCE, Self::MAX_DISTANCE),
+            pinhole_camera: PinholeCamera::new(),
+        }
+    }
+
+    pub fn position(&self) -> Vector3 {
+        self.target - self.direction.forward * self.distance
+    }
+
+    /// Circles the target, keeping the distance.
+    pub fn rotate(&mut self, yaw: Radians, pitch: Radians) {
+        self.direction.rotate(yaw, pitch, 0.0);
+    }
+
+    /// Scales the distance to the target by `factor`, within [MIN_DISTANCE, MAX_DISTANCE].
+    pub fn zoom(&mut self, factor: f32) {
+        self.distance = Axis::clamp(self.distance * factor, Self::MIN_DISTANCE, Self::MAX_DISTANCE);
+    }
+
+    /// Moves the target along the camera's right and up, by fractions of the distance so panning
+    /// feels the same at every zoom level.
+    pub fn pan(&mut self, right: f32, up: f32) {
+        self.target += self.direction.right * (right * self.distance) + self.direction.up * (up * self.distance);
+    }
+
+    pub fn view_matrix(&self) -> Matrix4 {
+        Matrix4::look_at(self.position(), self.target, self.direction.up)
+    }
+
+    pub fn projection_matrix(&self) -> Matrix4 {
+        Matrix4::perspective(self.pinhole_camera.aspect_ratio(), PI / 3.0, 0.1, 100.0)
+    }
+}
+
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CameraMode {
+    Fps,
+    Orbit,
+}
+
+impl CameraMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "fps"   => Some(CameraMode::Fps),
+            "orbit" => Some(CameraMode::Orbit),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CameraMode::Fps   => "fps",
+            CameraMode::Orbit => "orbit",
+        }
+    }
+}
+
+
+/// Where a camera is and which way it's turned, which is what's blended during transitions.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Pose {
+    pub eye: Vector3,
+    pub orientation: Quaternion,
+}
+
+impl Pose {
+    /// Moves from `self` to `other` as `t` goes from 0 to 1, easing in and out. The orientation is
+    /// slerped, so the camera turns at an even rate about a single axis.
+    pub fn blend(&self, other: &Pose, t: f32) -> Pose {
+        let t = Axis::clamp(t, 0.0, 1.0);
+        let t = t * t * (3.0 - 2.0 * t);
+        Pose {
+            eye: self.eye.lerp(other.eye, t),
+            orientation: self.orientation.slerp(&other.orientation, t),
+        }
+    }
+
+    pub fn view_matrix(&self) -> Matrix4 {
+        let direction = Axis::from_orientation(self.orientation);
+        Matrix4::look_at(self.eye, self.eye + direction.forward, direction.up)
+    }
+}
+
+
+struct Transition {
+    from: Pose,
+    elapsed: f32,
+}
+
+
+/// The camera the scene is drawn with: an `FPSCamera` to fly around with and an `OrbitCamera` to
+/// inspect plots with. Switching between them blends smoothly from one view to the other; both
+/// keep their own state while the other one is in use.
+pub struct Camera {
+    pub fps: FPSCamera,
+    pub orbit: OrbitCamera,
+    mode: CameraMode,
+    transition: Option<Transition>,
+}
+
+impl Camera {
+    /// In milliseconds, like the `dt` passed to `update`.
+    pub const TRANSITION_DURATION: f32 = 600.0;
+
+    pub fn new() -> Self {
+        Self {
+            fps: FPSCamera::new(),
+            orbit: OrbitCamera::new(Vector3::ZERO, 3.0),
+            mode: CameraMode::Fps,
+            transition: None,
+        }
+    }
+
+    pub fn mode(&self) -> CameraMode {
+        self.mode
+    }
+
+    /// Switches to the other camera, blending from the current view (even mid-transition).
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        if mode == self.mode {
+            return;
+        }
+        self.transition = Some(Transition { from: self.pose(), elapsed: 0.0 });
+        self.mode = mode;
+    }
+
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    /// Advances the transition by `dt` milliseconds.
+    pub fn update(&mut self, dt: f32) {
+        if let Some(transition) = &mut self.transition {
+            transition.elapsed += dt;
+            if transition.elapsed >= Self::TRANSITION_DURATION {
+                self.transition = None;
+            }
+        }
+    }
+
+    /// The pose of the active camera, ignoring any transition.
+    fn target_pose(&self) -> Pose {
+        match self.mode {
+            CameraMode::Fps   => Pose { eye: self.fps.position, orientation: self.fps.direction.orientation },
+            CameraMode::Orbit => Pose { eye: self.orbit.position(), orientation: self.orbit.direction.orientation },
+        }
+    }
+
+    /// What the camera currently looks like, including any transition.
+    pub fn pose(&self) -> Pose {
+        let pose = self.target_pose();
+        match &self.transition {
+            Some(transition) => transition.from.blend(&pose, transition.elapsed / Self::TRANSITION_DURATION),
+            None => pose,
+        }
+    }
+
+    pub fn view_matrix(&self) -> Matrix4 {
+        match (&self.transition, self.mode) {
+            (Some(_), _) => self.pose().view_matrix(),
+            (None, CameraMode::Fps)   => self.fps.view_matrix(),
+            (None, CameraMode::Orbit) => self.orbit.view_matrix(),
+        }
+    }
+
+    pub fn projection_matrix(&self) -> Matrix4 {
+        match self.mode {
+            CameraMode::Fps   => self.fps.projection_matrix(),
+            CameraMode::Orbit => self.orbit.projection_matrix(),
+        }
+    }
+}
+
+
+/// Where both cameras are and which one is in use, e.g. to start a replay from the same view.
+/// Transitions aren't kept, and the orientations are stored as (scalar, x, y, z).
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraSnapshot {
+    pub mode: CameraMode,
+    pub fps_position: [f32; 3],
+    pub fps_orientation: [f32; 4],
+    pub orbit_target: [f32; 3],
+    pub orbit_distance: f32,
+    pub orbit_orientation: [f32; 4],
+}
+
+impl CameraSnapshot {
+    fn from_quaternion(q: &Quaternion) -> [f32; 4] {
+        [q.scalar, q.vector.x, q.vector.y, q.vector.z]
+    }
+
+    fn to_quaternion([scalar, x, y, z]: [f32; 4]) -> Quaternion {
+        Quaternion::from_raw(scalar, Vector3::new(x, y, z))
+    }
+}
+
+impl Camera {
+    pub fn snapshot(&self) -> CameraSnapshot {
+        let (fps, orbit) = (&self.fps.position, &self.orbit.target);
+        CameraSnapshot {
+            mode: self.mode,
+            fps_position: [fps.x, fps.y, fps.z],
+            fps_orientation: CameraSnapshot::from_quaternion(&self.fps.direction.orientation),
+            orbit_target: [orbit.x, orbit.y, orbit.z],
+            orbit_distance: self.orbit.distance,
+            orbit_orientation: CameraSnapshot::from_quaternion(&self.orbit.direction.orientation),
         }
     }
 
-    pub fn projection_matrix(&self) -> Matrix4<Millimeters> {
-        glm::perspective(self.pinhole_camera.aspect_ratio(), PI / 3.0, 0.1, 100.0)
+    /// Moves both cameras to the snapshot, ending any transition.
+    pub fn restore(&mut self, snapshot: &CameraSnapshot) {
+        self.mode = snapshot.mode;
+        self.transition = None;
+        self.fps.position = Vector3::from(snapshot.fps_position);
+        self.fps.direction = Axis::from_orientation(CameraSnapshot::to_quaternion(snapshot.fps_orientation));
+        self.orbit.target = Vector3::from(snapshot.orbit_target);
+        self.orbit.distance = Axis::clamp(snapshot.orbit_distance, OrbitCamera::MIN_DISTANCE, OrbitCamera::MAX_DISTANCE);
+        self.orbit.direction = Axis::from_orientation(CameraSnapshot::to_quaternion(snapshot.orbit_orientation));
     }
 }
 
 
-pub type Camera = FPSCamera;
\ No newline at end of file
+#[cfg(test)]
+#[path = "tests/camera.rs"]
+mod tests;
\ No newline at end of file
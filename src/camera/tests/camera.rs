@@ -0,0 +1,185 @@
+use super::*;
+use crate::math::Vector4;
+use crate::test_support::assert_close;
+
+fn assert_orthonormal(axis: &Axis) {
+    for vector in [&axis.right, &axis.up, &axis.forward] {
+        assert!((vector.norm() - 1.0).abs() < 1e-5, "{:?} isn't normalized", vector);
+    }
+    assert!(axis.right.dot(&axis.forward).abs() < 1e-5);
+    assert!(axis.right.dot(&axis.up).abs() < 1e-5);
+    assert!(axis.up.dot(&axis.forward).abs() < 1e-5);
+    assert_close(&axis.right, &axis.forward.cross(&axis.up));
+}
+
+
+#[test]
+pub fn axis_starts_looking_down_the_z_axis() {
+    let axis = Axis::new();
+    assert_orthonormal(&axis);
+    assert_close(&axis.forward, &Vector3::new(0.0, 0.0, 1.0));
+    assert_close(&axis.up, &Vector3::new(0.0, 1.0, 0.0));
+}
+
+#[test]
+pub fn axis_stays_orthonormal() {
+    let mut axis = Axis::new();
+    for i in 0..1000 {
+        let i = i as f32;
+        axis.rotate((i * 0.37).sin(), (i * 0.11).cos() * 0.8, (i * 0.53).sin() * 0.3);
+        assert_orthonormal(&axis);
+        assert!((axis.orientation.norm() - 1.0).abs() < 1e-5);
+    }
+}
+
+#[test]
+pub fn axis_yaw_after_pitch_keeps_the_horizon_level() {
+    let mut axis = Axis::new();
+    for i in 0..100 {
+        let i = i as f32;
+        axis.rotate((i * 0.37).sin() * 0.2, (i * 0.11).cos() * 0.2, 0.0);
+        assert!(axis.right.y.abs() < 1e-4, "Right {:?} has tilted out of the horizon", axis.right);
+    }
+}
+
+#[test]
+pub fn axis_rotations_follow_the_conventions() {
+    let quarter = std::f32::consts::FRAC_PI_2;
+
+    let mut yawed = Axis::new();
+    yawed.rotate(quarter, 0.0, 0.0);
+    assert_close(&yawed.forward, &Vector3::new(1.0, 0.0, 0.0));
+    assert_close(&yawed.up, &Vector3::new(0.0, 1.0, 0.0));
+
+    let mut pitched = Axis::new();
+    pitched.rotate(0.0, quarter, 0.0);
+    assert_close(&pitched.forward, &Vector3::new(0.0, -1.0, 0.0));
+
+    // Roll keeps looking the same way.
+    let mut rolled = Axis::new();
+    rolled.rotate(0.0, 0.0, quarter);
+    assert_close(&rolled.forward, &Vector3::new(0.0, 0.0, 1.0));
+    assert_close(&rolled.up, &Axis::new().right);
+}
+
+#[test]
+pub fn axis_pitches_over_the_top_without_locking() {
+    // Pitching straight up and beyond must not lose the yaw axis.
+    let mut axis = Axis::new();
+    for _ in 0..3 {
+        axis.rotate(0.0, -std::f32::consts::FRAC_PI_4, 0.0);
+        assert_orthonormal(&axis);
+    }
+    assert_close(&axis.forward, &Vector3::new(0.0, 1.0, -1.0).normalize());
+
+    let forward = axis.forward;
+    axis.rotate(0.1, 0.0, 0.0);
+    assert!((axis.forward - forward).norm() > 0.05);
+}
+
+#[test]
+pub fn axis_slerp_turns_evenly() {
+    let start = Axis::new();
+    let mut end = Axis::new();
+    end.rotate(1.2, 0.0, 0.0);
+
+    let halfway = start.slerp(&end, 0.5);
+    assert_orthonormal(&halfway);
+    let mut expected = Axis::new();
+    expected.rotate(0.6, 0.0, 0.0);
+    assert_close(&halfway.forward, &expected.forward);
+}
+
+#[test]
+pub fn orbit_keeps_the_distance_to_the_target() {
+    let target = Vector3::new(1.0, 2.0, 3.0);
+    let mut camera = OrbitCamera::new(target, 4.0);
+    for (yaw, pitch) in [(0.3, 0.2), (-1.5, 0.9), (2.0, -2.0)] {
+        camera.rotate(yaw, pitch);
+        assert!(((camera.position() - target).norm() - 4.0).abs() < 1e-5);
+        assert_close(&(target - camera.position()).normalize(), &camera.direction.forward);
+    }
+
+    // The target is in the middle of the view, in front of the camera.
+    let center = camera.view_matrix() * Vector4::new(target.x, target.y, target.z, 1.0);
+    assert!(center.x.abs() < 1e-5 && center.y.abs() < 1e-5);
+    assert!((center.z + 4.0).abs() < 1e-5);
+}
+
+#[test]
+pub fn orbit_zoom_is_clamped() {
+    let mut camera = OrbitCamera::new(Vector3::ZERO, 2.0);
+    camera.zoom(0.5);
+    assert_eq!(camera.distance, 1.0);
+    camera.zoom(1e-6);
+    assert_eq!(camera.distance, OrbitCamera::MIN_DISTANCE);
+    camera.zoom(1e6);
+    assert_eq!(camera.distance, OrbitCamera::MAX_DISTANCE);
+}
+
+#[test]
+pub fn orbit_pan_moves_target_and_camera_together() {
+    let mut camera = OrbitCamera::new(Vector3::ZERO, 2.0);
+    let (position, right, up) = (camera.position(), camera.direction.right, camera.direction.up);
+    camera.pan(0.5, -0.25);
+
+    let offset = right * 1.0 - up * 0.5;
+    assert_close(&camera.target, &offset);
+    assert_close(&camera.position(), &(position + offset));
+}
+
+#[test]
+pub fn switching_modes_blends_between_poses() {
+    let mut camera = Camera::new();
+    let fps = camera.pose();
+    camera.set_mode(CameraMode::Orbit);
+    assert!(camera.is_transitioning());
+    assert_eq!(camera.pose(), fps);
+
+    camera.update(Camera::TRANSITION_DURATION / 2.0);
+    let orbit = Pose { eye: camera.orbit.position(), orientation: camera.orbit.direction.orientation };
+    assert_close(&camera.pose().eye, &((fps.eye + orbit.eye) / 2.0));
+    let halfway = fps.orientation.slerp(&orbit.orientation, 0.5);
+    assert!((camera.pose().orientation.dot(&halfway).abs() - 1.0).abs() < 1e-5);
+
+    camera.update(Camera::TRANSITION_DURATION);
+    assert!(!camera.is_transitioning());
+    assert_eq!(camera.pose(), orbit);
+    assert_eq!(camera.view_matrix(), camera.orbit.view_matrix());
+
+    // Switching to the same mode does nothing.
+    camera.set_mode(CameraMode::Orbit);
+    assert!(!camera.is_transitioning());
+}
+
+#[test]
+pub fn camera_modes_have_names() {
+    for mode in [CameraMode::Fps, CameraMode::Orbit] {
+        assert_eq!(CameraMode::from_name(mode.name()), Some(mode));
+    }
+    assert_eq!(CameraMode::from_name("arcball"), None);
+}
+
+#[test]
+pub fn snapshots_restore_both_cameras() {
+    let mut camera = Camera::new();
+    camera.fps.move_forward(2.0);
+    camera.fps.rotate(0.3, -0.2, 0.1);
+    camera.orbit.rotate(1.0, 0.5);
+    camera.orbit.zoom(2.0);
+    camera.set_mode(CameraMode::Orbit);
+    camera.update(Camera::TRANSITION_DURATION);
+    let snapshot = camera.snapshot();
+
+    let mut restored = Camera::new();
+    restored.restore(&snapshot);
+    assert_eq!(restored.mode(), CameraMode::Orbit);
+    assert_eq!(restored.snapshot(), snapshot);
+    assert_eq!(restored.view_matrix(), camera.view_matrix());
+
+    // Restoring mid-transition jumps straight to the snapshot.
+    restored.set_mode(CameraMode::Fps);
+    restored.restore(&snapshot);
+    assert!(!restored.is_transitioning());
+    assert_eq!(restored.mode(), CameraMode::Orbit);
+}
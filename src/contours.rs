@@ -0,0 +1,139 @@
+use crate::math::Vector3;
+
+use crate::fields::colormap;
+use crate::utils::{Domain, math_to_world};
+
+
+/// A scalar field sampled on the corners of a `resolution_x` by `resolution_y` grid of cells,
+/// laid out like the vertices of `create_surface` (x varies fastest).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalarGrid {
+    pub domain: Domain,
+    pub resolution_x: usize,
+    pub resolution_y: usize,
+    pub values: Vec<f32>,
+}
+
+impl ScalarGrid {
+    pub fn sample<F>(f: F, domain: Domain, resolution_x: usize, resolution_y: usize) -> Self
+        where F: Fn(f32, f32) -> f32
+    {
+        assert!(resolution_x >= 1 && resolution_y >= 1, "A grid needs at least one cell.");
+
+        let mut values = Vec::with_capacity((resolution_x + 1) * (resolution_y + 1));
+        for j in 0..=resolution_y {
+            for i in 0..=resolution_x {
+                let (x, y) = domain.lerp(i as f32 / resolution_x as f32, j as f32 / resolution_y as f32);
+                values.push(f(x, y));
+            }
+        }
+        Self { domain, resolution_x, resolution_y, values }
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> f32 {
+        self.values[j * (self.resolution_x + 1) + i]
+    }
+
+    /// The math (x, y) of a grid corner.
+    pub fn position(&self, i: usize, j: usize) -> (f32, f32) {
+        self.domain.lerp(i as f32 / self.resolution_x as f32, j as f32 / self.resolution_y as f32)
+    }
+
+    /// The smallest and largest finite values, or `None` if there are none.
+    pub fn range(&self) -> Option<(f32, f32)> {
+        self.values.iter()
+            .filter(|value| value.is_finite())
+            .fold(None, |range, &value| match range {
+                None => Some((value, value)),
+                Some((min, max)) => Some((min.min(value), max.max(value))),
+            })
+    }
+}
+
+
+/// `count` levels evenly spaced strictly inside [min, max], so that no level only touches the
+/// extremes of the field.
+pub fn evenly_spaced_levels(min: f32, max: f32, count: usize) -> Vec<f32> {
+    (1..=count).map(|k| min + (max - min) * k as f32 / (count + 1) as f32).collect()
+}
+
+
+/// The line segments where the field crosses `level`, as pairs of math (x, y) points. Cells with
+/// an undefined corner are skipped. Saddle cells, where diagonally opposite corners are on the
+/// same side, are resolved by the average of the four corners.
+pub fn marching_squares(grid: &ScalarGrid, level: f32) -> Vec<[(f32, f32); 2]> {
+    let mut segments = Vec::new();
+
+    for j in 0..grid.resolution_y {
+        for i in 0..grid.resolution_x {
+            // Corners counter-clockwise from the bottom left, and edge k goes from corner k to k + 1.
+            let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+            let values  = corners.map(|(i, j)| grid.get(i, j));
+            if values.iter().any(|value| !value.is_finite()) {
+                continue;
+            }
+
+            let case = values.iter().enumerate().fold(0, |case, (k, &value)| case | (((value >= level) as usize) << k));
+
+            // Where the level crosses edge k, by linear interpolation.
+            let crossing = |k: usize| {
+                let (a, b) = (values[k], values[(k + 1) % 4]);
+                let t = if a == b { 0.5 } else { (level - a) / (b - a) };
+                let (x0, y0) = grid.position(corners[k].0, corners[k].1);
+                let (x1, y1) = grid.position(corners[(k + 1) % 4].0, corners[(k + 1) % 4].1);
+                (x0 + t * (x1 - x0), y0 + t * (y1 - y0))
+            };
+            let center_above = values.iter().sum::<f32>() / 4.0 >= level;
+
+            let edges: &[(usize, usize)] = match case {
+                0 | 15 => &[],
+                1 | 14 => &[(3, 0)],
+                2 | 13 => &[(0, 1)],
+                3 | 12 => &[(3, 1)],
+                4 | 11 => &[(1, 2)],
+                6 | 9  => &[(0, 2)],
+                7 | 8  => &[(3, 2)],
+                // Corners 0 and 2 are above. If the center is too they're connected, which cuts off corners 1 and 3.
+                5  => if center_above { &[(0, 1), (2, 3)] } else { &[(3, 0), (1, 2)] },
+                10 => if center_above { &[(3, 0), (1, 2)] } else { &[(0, 1), (2, 3)] },
+                _  => unreachable!(),
+            };
+            segments.extend(edges.iter().map(|&(a, b)| [crossing(a), crossing(b)]));
+        }
+    }
+
+    segments
+}
+
+
+/// World positions and colors (6 floats per vertex) of the contours at each level, to be drawn
+/// with `GL::LINES`. Levels are colored from the lowest to the highest. With `lifted`, contours
+/// are drawn at their height on the surface z = f(x, y) instead of on the xy-plane.
+pub fn create_contour_lines(grid: &ScalarGrid, levels: &[f32], lifted: bool) -> Vec<f32> {
+    // Lift the lines a little off the surface so they aren't buried in it.
+    const SURFACE_OFFSET: f32 = 0.01;
+
+    let lowest  = levels.iter().cloned().fold(f32::INFINITY, f32::min);
+    let highest = levels.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+
+    let mut vertices = Vec::new();
+    for &level in levels {
+        let t = if highest > lowest { (level - lowest) / (highest - lowest) } else { 0.5 };
+        let color: Vector3 = colormap(t);
+        // On the level curve f(x, y) is the level itself, so there's no need to evaluate f.
+        let z = if lifted { level + SURFACE_OFFSET } else { 0.0 };
+
+        for [(x0, y0), (x1, y1)] in marching_squares(grid, level) {
+            vertices.extend_from_slice(&math_to_world(x0, y0, z));
+            vertices.extend_from_slice(&[color.x, color.y, color.z]);
+            vertices.extend_from_slice(&math_to_world(x1, y1, z));
+            vertices.extend_from_slice(&[color.x, color.y, color.z]);
+        }
+    }
+    vertices
+}
+
+
+#[cfg(test)]
+#[path = "tests/contours.rs"]
+mod tests;
@@ -0,0 +1,102 @@
+use std::f32::consts::PI;
+
+use crate::app::AppState;
+use crate::camera::{Camera, CameraMode, FPSCamera};
+use crate::gamepad::{CameraMotion, GamepadSettings};
+use crate::input::Action;
+use crate::recording::Frame;
+
+
+/// Moves the camera by a frame of input. It only depends on the frame (and the camera), so
+/// replaying recorded frames moves the camera exactly the same way.
+pub fn update_camera(camera: &mut Camera, frame: &Frame, gamepad: &GamepadSettings) {
+    if camera.mode() != frame.camera_mode {
+        camera.set_mode(frame.camera_mode);
+    }
+    camera.update(frame.dt);
+
+    match frame.camera_mode {
+        CameraMode::Orbit => update_orbit_camera(camera, &frame.state),
+        CameraMode::Fps   => {
+            let fps = &mut camera.fps;
+            update_fps_camera(fps, &frame.state, frame.dt);
+            if let Some(snapshot) = &frame.gamepad {
+                CameraMotion::from_snapshot(snapshot, gamepad, frame.dt).apply(fps);
+            }
+        },
+    }
+}
+
+
+/// Left drag circles the target, right drag pans and the wheel zooms. Touches and pens do the
+/// same with one finger, two fingers and pinching.
+fn update_orbit_camera(camera: &mut Camera, state: &AppState) {
+    const LEFT_BUTTON: i16 = 0;
+    const RIGHT_BUTTON: i16 = 2;
+
+    if state.mouse_down && state.canvas_height > 0.0 {
+        let delta_x = state.delta_mouse_x / state.canvas_height;
+        let delta_y = state.delta_mouse_y / state.canvas_height;
+        match state.mouse_button {
+            LEFT_BUTTON  => camera.orbit.rotate(-delta_x * PI, delta_y * PI),
+            RIGHT_BUTTON => camera.orbit.pan(-delta_x, -delta_y),
+            _ => {},
+        }
+    }
+    if state.wheel_delta != 0.0 {
+        camera.orbit.zoom((state.wheel_delta / 1000.0).exp());
+    }
+
+    // One finger circles like the left button, two pan like the right one and pinching zooms.
+    let gesture = &state.gesture;
+    if !gesture.is_none() && state.canvas_height > 0.0 {
+        let rotate = (gesture.rotate_x / state.canvas_height, gesture.rotate_y / state.canvas_height);
+        camera.orbit.rotate(-rotate.0 * PI, rotate.1 * PI);
+        camera.orbit.pan(-gesture.pan_x / state.canvas_height, -gesture.pan_y / state.canvas_height);
+        camera.orbit.zoom(1.0 / gesture.zoom);
+    }
+}
+
+
+/// Flies with the bound keys, looks towards the mouse while it's locked, and follows gestures.
+fn update_fps_camera(camera: &mut FPSCamera, state: &AppState, dt: f32) {
+    if !state.mouse_down && state.mouse_locked {
+        let offset_from_center_x = (state.mouse_x - state.canvas_width  / 2.0) / state.canvas_width;
+        let offset_from_center_y = (state.mouse_y - state.canvas_height / 2.0) / state.canvas_height;
+
+        if offset_from_center_x.abs() >= 0.1 {
+            camera.rotate(-offset_from_center_x * (dt/1000.0), 0.0, 0.0);
+        }
+        if offset_from_center_y.abs() >= 0.1 {
+            camera.rotate(0.0, -offset_from_center_y * (dt/1000.0), 0.0);
+        }
+    }
+
+    update_fps_camera_from_gestures(camera, state);
+
+    // Should take camera direction into account, i.e. movement should be local to camera.
+    let actions = &state.actions;
+    let delta_x = actions.axis(Action::MoveLeft,      Action::MoveRight)   * dt / 1000.0;
+    let delta_y = actions.axis(Action::MoveDown,      Action::MoveUp)      * dt / 1000.0;
+    let delta_z = actions.axis(Action::MoveBackwards, Action::MoveForward) * dt / 1000.0;
+
+    camera.move_right(delta_x);
+    camera.move_up(delta_y);
+    camera.move_forward(delta_z);
+
+    let roll = actions.axis(Action::RollLeft, Action::RollRight) * dt / 1000.0;
+    camera.rotate(0.0, 0.0, roll);
+}
+
+/// One finger looks around, two move sideways and up, and pinching moves forward.
+fn update_fps_camera_from_gestures(camera: &mut FPSCamera, state: &AppState) {
+    let gesture = &state.gesture;
+    if gesture.is_none() || state.canvas_height <= 0.0 {
+        return;
+    }
+    let scale = 1.0 / state.canvas_height;
+    camera.rotate(-gesture.rotate_x * scale * PI, gesture.rotate_y * scale * PI, 0.0);
+    camera.move_right(-gesture.pan_x * scale);
+    camera.move_up(-gesture.pan_y * scale);
+    camera.move_forward(gesture.zoom.ln());
+}
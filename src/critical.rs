@@ -0,0 +1,153 @@
+use crate::math::Vector3;
+use serde::Serialize;
+
+use crate::utils::{math_to_world, Domain};
+
+
+/// The second derivative test at a critical point, by D = fxx fyy - fxy^2.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Classification {
+    Minimum,
+    Maximum,
+    Saddle,
+    /// D = 0, where the test is inconclusive.
+    Degenerate,
+}
+
+impl Classification {
+    /// |D| below this times the squared size of the Hessian counts as zero. Relative, so that
+    /// scaling f doesn't change the classification.
+    pub const DEGENERATE_TOLERANCE: f32 = 1e-6;
+
+    pub fn from_hessian(hessian: [[f32; 2]; 2]) -> Self {
+        let [[fxx, fxy], [_, fyy]] = hessian;
+        let discriminant = fxx * fyy - fxy * fxy;
+        if !discriminant.is_finite() || discriminant.abs() <= Self::DEGENERATE_TOLERANCE * hessian_norm(hessian).powi(2) {
+            Classification::Degenerate
+        } else if discriminant < 0.0 {
+            Classification::Saddle
+        } else if fxx > 0.0 {
+            Classification::Minimum
+        } else {
+            Classification::Maximum
+        }
+    }
+
+    pub fn color(&self) -> Vector3 {
+        match self {
+            Classification::Minimum    => Vector3::new(0.20, 0.45, 0.95),
+            Classification::Maximum    => Vector3::new(0.95, 0.25, 0.20),
+            Classification::Saddle     => Vector3::new(0.30, 0.85, 0.35),
+            Classification::Degenerate => Vector3::new(0.70, 0.70, 0.70),
+        }
+    }
+}
+
+
+/// The Frobenius norm, i.e. how fast the gradient changes.
+pub fn hessian_norm(hessian: [[f32; 2]; 2]) -> f32 {
+    hessian.iter().flatten().map(|value| value * value).sum::<f32>().sqrt()
+}
+
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+pub struct CriticalPoint {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub kind: Classification,
+}
+
+
+const MAX_ITERATIONS: usize = 50;
+/// Newton has converged when a step is shorter than this, relative to the size of the domain.
+const STEP_TOLERANCE: f32 = 1e-6;
+/// |∇f| must be below this at a converged point, so that slow progress isn't taken for a root. It's
+/// relative to how much the gradient changes across the domain (the size of the Hessian times the
+/// size of the domain), so steep and flat functions are treated alike.
+const GRADIENT_TOLERANCE: f32 = 1e-4;
+
+/// Newton's method on ∇f = 0 from (x, y). Returns `None` if it diverges, leaves the domain or
+/// hits a singular Hessian before converging.
+pub fn newton<G, H>(gradient: &G, hessian: &H, domain: Domain, x: f32, y: f32) -> Option<(f32, f32)>
+    where G: Fn(f32, f32) -> (f32, f32),
+          H: Fn(f32, f32) -> [[f32; 2]; 2]
+{
+    let scale = domain.width().max(domain.depth());
+    let (mut x, mut y) = (x, y);
+
+    for _ in 0..MAX_ITERATIONS {
+        let (fx, fy) = gradient(x, y);
+        let [[fxx, fxy], [fyx, fyy]] = hessian(x, y);
+        let determinant = fxx * fyy - fxy * fyx;
+        if determinant == 0.0 || !determinant.is_finite() || !fx.is_finite() || !fy.is_finite() {
+            return None;
+        }
+
+        // Solve H (dx, dy) = ∇f by Cramer's rule.
+        let dx = (fx * fyy - fy * fxy) / determinant;
+        let dy = (fy * fxx - fx * fyx) / determinant;
+        x -= dx;
+        y -= dy;
+        if !domain.contains(x, y) {
+            return None;
+        }
+
+        if (dx * dx + dy * dy).sqrt() < STEP_TOLERANCE * scale {
+            let (fx, fy) = gradient(x, y);
+            let tolerance = GRADIENT_TOLERANCE * hessian_norm(hessian(x, y)) * scale;
+            return if (fx * fx + fy * fy).sqrt() < tolerance { Some((x, y)) } else { None };
+        }
+    }
+    None
+}
+
+/// The critical points of f in the domain, found by Newton's method from the centers of a
+/// `seeds` by `seeds` grid. Points closer than a thousandth of the domain are merged. Critical
+/// points are only found where the Hessian is invertible nearby, so e.g. a whole line of minima
+/// isn't.
+pub fn find_critical_points<F, G, H>(f: F, gradient: G, hessian: H, domain: Domain, seeds: usize) -> Vec<CriticalPoint>
+    where F: Fn(f32, f32) -> f32,
+          G: Fn(f32, f32) -> (f32, f32),
+          H: Fn(f32, f32) -> [[f32; 2]; 2]
+{
+    let merge_distance = 1e-3 * domain.width().max(domain.depth());
+
+    let mut points: Vec<CriticalPoint> = Vec::new();
+    for i in 0..seeds {
+        for j in 0..seeds {
+            let (x, y) = domain.lerp((i as f32 + 0.5) / seeds as f32, (j as f32 + 0.5) / seeds as f32);
+            let (x, y) = match newton(&gradient, &hessian, domain, x, y) {
+                Some(point) => point,
+                None => continue,
+            };
+
+            let z = f(x, y);
+            let is_new = points.iter().all(|point| (point.x - x).hypot(point.y - y) > merge_distance);
+            if z.is_finite() && is_new {
+                points.push(CriticalPoint { x, y, z, kind: Classification::from_hessian(hessian(x, y)) });
+            }
+        }
+    }
+    points
+}
+
+
+/// The instance buffer for a cube of side `size` at each point, colored by its classification,
+/// in the layout of `integration::create_prism_instances`.
+pub fn create_glyph_instances(points: &[CriticalPoint], size: f32) -> Vec<f32> {
+    let mut data = Vec::with_capacity(points.len() * 9);
+    for point in points {
+        let color = point.kind.color();
+        data.extend_from_slice(&math_to_world(point.x, point.y, point.z));
+        data.extend_from_slice(&[size, size, size]);
+        data.extend_from_slice(&[color.x, color.y, color.z]);
+    }
+    data
+}
+
+
+#[cfg(test)]
+#[path = "tests/critical.rs"]
+mod tests;
@@ -0,0 +1,176 @@
+use crate::math::Vector3;
+
+use crate::utils::{SurfaceData, math_to_world};
+
+
+/// How the cross section of a tube is oriented along the curve.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FrameKind {
+    /// The textbook tangent, normal and binormal. Follows the curvature, so it flips at
+    /// inflection points and is undefined where the curve is straight.
+    Frenet,
+    /// Twists as little as possible around the tangent (double reflection method by Wang et al.).
+    /// Well behaved everywhere, which makes it the better choice for drawing.
+    RotationMinimizing,
+}
+
+
+/// An orthonormal frame at a point on the curve, in math coordinates.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Frame {
+    pub tangent: Vector3,
+    pub normal: Vector3,
+    pub binormal: Vector3,
+}
+
+
+/// Samples r(t) at `samples` evenly spaced parameters in [t_min, t_max] (in math coordinates).
+/// Points where the curve is undefined are dropped.
+pub fn sample_curve<F>(f: F, t_min: f32, t_max: f32, samples: usize) -> Vec<Vector3>
+    where F: Fn(f32) -> [f32; 3]
+{
+    assert!(samples >= 2, "A curve needs at least two samples.");
+
+    (0..samples)
+        .map(|i| {
+            let t = t_min + (t_max - t_min) * (i as f32 / (samples - 1) as f32);
+            let [x, y, z] = f(t);
+            Vector3::new(x, y, z)
+        })
+        .filter(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite())
+        .collect()
+}
+
+
+/// World positions of the points, to be drawn with `GL::LINE_STRIP`.
+pub fn create_polyline(points: &[Vector3]) -> Vec<f32> {
+    points.iter().flat_map(|p| math_to_world(p.x, p.y, p.z).to_vec()).collect()
+}
+
+
+fn tangents(points: &[Vector3]) -> Vec<Vector3> {
+    let n = points.len();
+    (0..n).map(|i| {
+        let previous = points[i.saturating_sub(1)];
+        let next     = points[(i + 1).min(n - 1)];
+        let tangent  = next - previous;
+        if tangent.norm() > 0.0 { tangent.normalize() } else { Vector3::new(1.0, 0.0, 0.0) }
+    }).collect()
+}
+
+/// Any unit vector perpendicular to `v`.
+fn perpendicular(v: &Vector3) -> Vector3 {
+    let axis = if v.x.abs() < 0.9 { Vector3::new(1.0, 0.0, 0.0) } else { Vector3::new(0.0, 1.0, 0.0) };
+    v.cross(&axis).normalize()
+}
+
+pub fn compute_frames(points: &[Vector3], kind: FrameKind) -> Vec<Frame> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+
+    let tangents = tangents(points);
+    match kind {
+        FrameKind::Frenet             => frenet_frames(points, &tangents),
+        FrameKind::RotationMinimizing => rotation_minimizing_frames(points, &tangents),
+    }
+}
+
+fn frenet_frames(points: &[Vector3], tangents: &[Vector3]) -> Vec<Frame> {
+    let n = points.len();
+    let mut frames: Vec<Frame> = Vec::with_capacity(n);
+
+    for i in 0..n {
+        let tangent = tangents[i];
+        let change  = tangents[(i + 1).min(n - 1)] - tangents[i.saturating_sub(1)];
+        let curving = change - tangent * change.dot(&tangent);
+
+        // Where the curve is (locally) straight the normal is undefined, so keep the previous one.
+        let normal = if curving.norm() > 1e-6 {
+            curving.normalize()
+        } else if let Some(previous) = frames.last() {
+            (previous.normal - tangent * previous.normal.dot(&tangent)).normalize()
+        } else {
+            perpendicular(&tangent)
+        };
+
+        frames.push(Frame { tangent, normal, binormal: tangent.cross(&normal) });
+    }
+
+    frames
+}
+
+fn rotation_minimizing_frames(points: &[Vector3], tangents: &[Vector3]) -> Vec<Frame> {
+    let n = points.len();
+    let mut frames = Vec::with_capacity(n);
+
+    let normal = perpendicular(&tangents[0]);
+    frames.push(Frame { tangent: tangents[0], normal, binormal: tangents[0].cross(&normal) });
+
+    for i in 0..n - 1 {
+        let frame = frames[i];
+
+        // Reflect the frame over the bisecting plane of the two points...
+        let v1 = points[i + 1] - points[i];
+        let c1 = v1.dot(&v1);
+        if c1 < 1e-12 {
+            frames.push(Frame { tangent: tangents[i + 1], ..frame });
+            continue;
+        }
+        let normal_l  = frame.normal  - v1 * (2.0 / c1 * v1.dot(&frame.normal));
+        let tangent_l = frame.tangent - v1 * (2.0 / c1 * v1.dot(&frame.tangent));
+
+        // ... and then over the plane that maps the reflected tangent onto the next tangent.
+        let v2 = tangents[i + 1] - tangent_l;
+        let c2 = v2.dot(&v2);
+        let normal = if c2 < 1e-12 { normal_l } else { normal_l - v2 * (2.0 / c2 * v2.dot(&normal_l)) };
+        let normal = normal.normalize();
+
+        frames.push(Frame { tangent: tangents[i + 1], normal, binormal: (tangents[i + 1]).cross(&normal) });
+    }
+
+    frames
+}
+
+
+/// A tube of the given radius around the curve, in the same layout as `create_surface`.
+pub fn create_tube(points: &[Vector3], radius: f32, sides: usize, kind: FrameKind) -> SurfaceData {
+    assert!(sides >= 3, "A tube needs at least three sides.");
+
+    let frames = compute_frames(points, kind);
+    let rings  = points.len();
+
+    let mut vertices = Vec::with_capacity(rings * (sides + 1) * SurfaceData::COMPONENTS_PER_VERTEX);
+    for (i, (point, frame)) in points.iter().zip(frames.iter()).enumerate() {
+        // One extra vertex per ring, so the texture coordinates can wrap around.
+        for j in 0..=sides {
+            let angle  = 2.0 * std::f32::consts::PI * (j % sides) as f32 / sides as f32;
+            let normal = frame.normal * angle.cos() + frame.binormal * angle.sin();
+            let position = *point + normal * radius;
+
+            vertices.extend_from_slice(&math_to_world(position.x, position.y, position.z));
+            vertices.push(i as f32 / (rings - 1).max(1) as f32);
+            vertices.push(j as f32 / sides as f32);
+            vertices.extend_from_slice(&math_to_world(normal.x, normal.y, normal.z));
+        }
+    }
+
+    let mut indices = Vec::with_capacity(rings.saturating_sub(1) * sides * 6);
+    let stride = (sides + 1) as u32;
+    for i in 0..rings.saturating_sub(1) as u32 {
+        for j in 0..sides as u32 {
+            let a = i * stride + j;
+            let b = a + stride;
+            let c = a + 1;
+            let d = b + 1;
+            indices.extend_from_slice(&[a, c, d, a, d, b]);  // Counter-clockwise seen from the outside.
+        }
+    }
+
+    SurfaceData { vertices, indices }
+}
+
+
+#[cfg(test)]
+#[path = "tests/curves.rs"]
+mod tests;
@@ -0,0 +1,107 @@
+use std::fmt;
+
+use wasm_bindgen::JsValue;
+use js_sys::Reflect;
+
+use crate::expression::ParseError;
+
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    Parse,            // The expression couldn't be parsed. Has a span into the offending input.
+    InvalidArgument,  // E.g. an empty range or a zero resolution.
+    UnknownHandle,    // The handle was never returned by the client, or has been removed.
+    WebGl,            // Creating buffers or programs failed.
+}
+
+impl ErrorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::Parse           => "parse",
+            ErrorKind::InvalidArgument => "invalid_argument",
+            ErrorKind::UnknownHandle   => "unknown_handle",
+            ErrorKind::WebGl           => "webgl",
+        }
+    }
+}
+
+
+/// Errors returned to JS from the `Client` API. They are thrown as regular JS `Error` objects
+/// with the extra properties `kind`, `field` and, for parse errors, `start` and `end`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientError {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub field: Option<&'static str>,  // Which argument the error refers to, e.g. "expression".
+    pub span: Option<(usize, usize)>,  // In UTF-16 code units, which is how JS indexes strings.
+}
+
+impl ClientError {
+    pub fn new(kind: ErrorKind, message: String) -> Self {
+        Self { kind, message, field: None, span: None }
+    }
+
+    /// The lexer's spans are byte offsets into `source`, so they're converted for JS here.
+    pub fn parse(error: ParseError, source: &str, field: &'static str) -> Self {
+        let utf16 = |offset: usize| source[..offset].encode_utf16().count();
+        Self {
+            kind: ErrorKind::Parse,
+            message: error.message,
+            field: Some(field),
+            span: Some((utf16(error.span.start), utf16(error.span.end))),
+        }
+    }
+
+    pub fn invalid_argument(field: &'static str, message: String) -> Self {
+        Self { kind: ErrorKind::InvalidArgument, message, field: Some(field), span: None }
+    }
+
+    pub fn unknown_handle(handle: u32) -> Self {
+        Self::new(ErrorKind::UnknownHandle, format!("No plot with handle {}", handle))
+    }
+
+    pub fn webgl(message: String) -> Self {
+        Self::new(ErrorKind::WebGl, message)
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.field {
+            Some(field) => write!(f, "[{}] {}: {}", self.kind.name(), field, self.message),
+            None        => write!(f, "[{}] {}", self.kind.name(), self.message),
+        }
+    }
+}
+
+impl From<String> for ClientError {
+    fn from(message: String) -> Self {
+        // The WebGL wrappers report their errors as strings.
+        Self::webgl(message)
+    }
+}
+
+impl From<ClientError> for JsValue {
+    fn from(error: ClientError) -> Self {
+        let object = js_sys::Error::new(&error.message);
+        let set = |key: &str, value: JsValue| {
+            Reflect::set(&object, &JsValue::from_str(key), &value).expect("Couldn't set property on error!");
+        };
+
+        set("kind", JsValue::from_str(error.kind.name()));
+        if let Some(field) = error.field {
+            set("field", JsValue::from_str(field));
+        }
+        if let Some((start, end)) = error.span {
+            set("start", JsValue::from_f64(start as f64));
+            set("end",   JsValue::from_f64(end as f64));
+        }
+
+        object.into()
+    }
+}
+
+
+#[cfg(test)]
+#[path = "tests/errors.rs"]
+mod tests;
@@ -0,0 +1,315 @@
+use std::fmt;
+
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Variable {
+    X,
+    Y,
+    Z,
+    T,
+    U,
+    V,
+}
+
+impl Variable {
+    pub const ALL: [Variable; 6] = [Variable::X, Variable::Y, Variable::Z, Variable::T, Variable::U, Variable::V];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "x" => Some(Variable::X),
+            "y" => Some(Variable::Y),
+            "z" => Some(Variable::Z),
+            "t" => Some(Variable::T),
+            "u" => Some(Variable::U),
+            "v" => Some(Variable::V),
+            _   => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variable::X => "x",
+            Variable::Y => "y",
+            Variable::Z => "z",
+            Variable::T => "t",
+            Variable::U => "u",
+            Variable::V => "v",
+        }
+    }
+}
+
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Constant {
+    Pi,
+    E,
+    Tau,
+}
+
+impl Constant {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pi"  => Some(Constant::Pi),
+            "e"   => Some(Constant::E),
+            "tau" => Some(Constant::Tau),
+            _     => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Constant::Pi  => "pi",
+            Constant::E   => "e",
+            Constant::Tau => "tau",
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        match self {
+            Constant::Pi  => std::f32::consts::PI,
+            Constant::E   => std::f32::consts::E,
+            Constant::Tau => 2.0 * std::f32::consts::PI,
+        }
+    }
+}
+
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Exp,
+    Ln,
+    Sqrt,
+    Abs,
+    Sign,
+    Floor,
+    Ceil,
+}
+
+impl Function {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sin"   => Some(Function::Sin),
+            "cos"   => Some(Function::Cos),
+            "tan"   => Some(Function::Tan),
+            "asin"  => Some(Function::Asin),
+            "acos"  => Some(Function::Acos),
+            "atan"  => Some(Function::Atan),
+            "sinh"  => Some(Function::Sinh),
+            "cosh"  => Some(Function::Cosh),
+            "tanh"  => Some(Function::Tanh),
+            "exp"   => Some(Function::Exp),
+            "ln"    => Some(Function::Ln),
+            "log"   => Some(Function::Ln),  // 'log' is the natural logarithm, as in most calculus texts.
+            "sqrt"  => Some(Function::Sqrt),
+            "abs"   => Some(Function::Abs),
+            "sign"  => Some(Function::Sign),
+            "floor" => Some(Function::Floor),
+            "ceil"  => Some(Function::Ceil),
+            _       => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Function::Sin   => "sin",
+            Function::Cos   => "cos",
+            Function::Tan   => "tan",
+            Function::Asin  => "asin",
+            Function::Acos  => "acos",
+            Function::Atan  => "atan",
+            Function::Sinh  => "sinh",
+            Function::Cosh  => "cosh",
+            Function::Tanh  => "tanh",
+            Function::Exp   => "exp",
+            Function::Ln    => "ln",
+            Function::Sqrt  => "sqrt",
+            Function::Abs   => "abs",
+            Function::Sign  => "sign",
+            Function::Floor => "floor",
+            Function::Ceil  => "ceil",
+        }
+    }
+
+    pub fn apply(&self, x: f32) -> f32 {
+        match self {
+            Function::Sin   => x.sin(),
+            Function::Cos   => x.cos(),
+            Function::Tan   => x.tan(),
+            Function::Asin  => x.asin(),
+            Function::Acos  => x.acos(),
+            Function::Atan  => x.atan(),
+            Function::Sinh  => x.sinh(),
+            Function::Cosh  => x.cosh(),
+            Function::Tanh  => x.tanh(),
+            Function::Exp   => x.exp(),
+            Function::Ln    => x.ln(),
+            Function::Sqrt  => x.sqrt(),
+            Function::Abs   => x.abs(),
+            Function::Sign  => if x > 0.0 { 1.0 } else if x < 0.0 { -1.0 } else { 0.0 },
+            Function::Floor => x.floor(),
+            Function::Ceil  => x.ceil(),
+        }
+    }
+}
+
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+impl BinaryOperator {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOperator::Add => "+",
+            BinaryOperator::Sub => "-",
+            BinaryOperator::Mul => "*",
+            BinaryOperator::Div => "/",
+            BinaryOperator::Pow => "^",
+        }
+    }
+
+    pub fn apply(&self, a: f32, b: f32) -> f32 {
+        match self {
+            BinaryOperator::Add => a + b,
+            BinaryOperator::Sub => a - b,
+            BinaryOperator::Mul => a * b,
+            BinaryOperator::Div => a / b,
+            BinaryOperator::Pow => a.powf(b),
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            BinaryOperator::Add | BinaryOperator::Sub => Node::PRECEDENCE_SUM,
+            BinaryOperator::Mul | BinaryOperator::Div => Node::PRECEDENCE_PRODUCT,
+            BinaryOperator::Pow => Node::PRECEDENCE_POWER,
+        }
+    }
+}
+
+
+/// The abstract syntax tree of a parsed expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Number(f32),
+    Constant(Constant),
+    Variable(Variable),
+    Negate(Box<Node>),
+    Binary(BinaryOperator, Box<Node>, Box<Node>),
+    Call(Function, Box<Node>),
+}
+
+impl Node {
+    const PRECEDENCE_SUM:     u8 = 1;
+    const PRECEDENCE_PRODUCT: u8 = 2;
+    const PRECEDENCE_NEGATE:  u8 = 3;
+    const PRECEDENCE_POWER:   u8 = 4;
+    const PRECEDENCE_ATOM:    u8 = 5;
+
+    pub fn binary(operator: BinaryOperator, left: Node, right: Node) -> Self {
+        Node::Binary(operator, Box::new(left), Box::new(right))
+    }
+
+    pub fn negate(node: Node) -> Self {
+        Node::Negate(Box::new(node))
+    }
+
+    pub fn call(function: Function, argument: Node) -> Self {
+        Node::Call(function, Box::new(argument))
+    }
+
+    /// Whether the variable occurs anywhere in the tree.
+    pub fn contains(&self, variable: Variable) -> bool {
+        match self {
+            Node::Number(_) | Node::Constant(_) => false,
+            Node::Variable(v)        => *v == variable,
+            Node::Negate(a)          => a.contains(variable),
+            Node::Binary(_, a, b)    => a.contains(variable) || b.contains(variable),
+            Node::Call(_, a)         => a.contains(variable),
+        }
+    }
+
+    /// Returns the value of the node if it doesn't depend on any variables.
+    pub fn constant_value(&self) -> Option<f32> {
+        match self {
+            Node::Number(value)      => Some(*value),
+            Node::Constant(constant) => Some(constant.value()),
+            Node::Variable(_)        => None,
+            Node::Negate(a)          => a.constant_value().map(|a| -a),
+            Node::Binary(op, a, b)   => Some(op.apply(a.constant_value()?, b.constant_value()?)),
+            Node::Call(function, a)  => a.constant_value().map(|a| function.apply(a)),
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Node::Number(value) if *value < 0.0 => Self::PRECEDENCE_NEGATE,
+            Node::Number(_) | Node::Constant(_) | Node::Variable(_) | Node::Call(_, _) => Self::PRECEDENCE_ATOM,
+            Node::Negate(_)         => Self::PRECEDENCE_NEGATE,
+            Node::Binary(op, _, _)  => op.precedence(),
+        }
+    }
+
+    fn fmt_with_precedence(&self, f: &mut fmt::Formatter, minimum: u8) -> fmt::Result {
+        let parenthesize = self.precedence() < minimum;
+        if parenthesize {
+            write!(f, "(")?;
+        }
+
+        match self {
+            Node::Number(value)      => write!(f, "{}", value)?,
+            Node::Constant(constant) => write!(f, "{}", constant.name())?,
+            Node::Variable(variable) => write!(f, "{}", variable.name())?,
+            Node::Negate(a) => {
+                write!(f, "-")?;
+                a.fmt_with_precedence(f, Self::PRECEDENCE_NEGATE)?;
+            },
+            Node::Binary(op, a, b) => {
+                let precedence = op.precedence();
+                match op {
+                    // Right associative, and a negative exponent doesn't need parentheses.
+                    BinaryOperator::Pow => {
+                        a.fmt_with_precedence(f, precedence + 1)?;
+                        write!(f, "^")?;
+                        b.fmt_with_precedence(f, Self::PRECEDENCE_NEGATE)?;
+                    },
+                    _ => {
+                        a.fmt_with_precedence(f, precedence)?;
+                        write!(f, " {} ", op.symbol())?;
+                        b.fmt_with_precedence(f, precedence + 1)?;
+                    },
+                }
+            },
+            Node::Call(function, a) => {
+                write!(f, "{}(", function.name())?;
+                a.fmt_with_precedence(f, 0)?;
+                write!(f, ")")?;
+            },
+        }
+
+        if parenthesize {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_with_precedence(f, 0)
+    }
+}
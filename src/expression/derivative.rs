@@ -0,0 +1,77 @@
+use super::ast::{Node, Variable, Function, BinaryOperator};
+
+use BinaryOperator::{Add, Sub, Mul, Div, Pow};
+
+
+fn number(value: f32) -> Node {
+    Node::Number(value)
+}
+
+fn add(a: Node, b: Node) -> Node { Node::binary(Add, a, b) }
+fn sub(a: Node, b: Node) -> Node { Node::binary(Sub, a, b) }
+fn mul(a: Node, b: Node) -> Node { Node::binary(Mul, a, b) }
+fn div(a: Node, b: Node) -> Node { Node::binary(Div, a, b) }
+fn pow(a: Node, b: Node) -> Node { Node::binary(Pow, a, b) }
+
+
+/// The derivative of the node with respect to the variable. The result is not simplified, e.g.
+/// d/dx (2x) gives `0 * x + 2 * 1`; pass it through `simplify` before showing it to anyone.
+pub fn differentiate(node: &Node, variable: Variable) -> Node {
+    if !node.contains(variable) {
+        return number(0.0);
+    }
+
+    match node {
+        Node::Number(_) | Node::Constant(_) => number(0.0),
+        Node::Variable(v) => number(if *v == variable { 1.0 } else { 0.0 }),
+        Node::Negate(a) => Node::negate(differentiate(a, variable)),
+        Node::Binary(op, a, b) => {
+            let (a, b) = (a.as_ref(), b.as_ref());
+            let da = differentiate(a, variable);
+            let db = differentiate(b, variable);
+            match op {
+                Add => add(da, db),
+                Sub => sub(da, db),
+                Mul => add(mul(da, b.clone()), mul(a.clone(), db)),
+                Div => div(sub(mul(da, b.clone()), mul(a.clone(), db)), pow(b.clone(), number(2.0))),
+                Pow => {
+                    if !b.contains(variable) {
+                        // Power rule: (a^n)' = n a^(n-1) a'
+                        mul(mul(b.clone(), pow(a.clone(), sub(b.clone(), number(1.0)))), da)
+                    } else if !a.contains(variable) {
+                        // Exponential: (c^b)' = c^b ln(c) b'
+                        mul(mul(node.clone(), Node::call(Function::Ln, a.clone())), db)
+                    } else {
+                        // General case: (a^b)' = a^b (b' ln(a) + b a' / a)
+                        mul(node.clone(), add(
+                            mul(db, Node::call(Function::Ln, a.clone())),
+                            div(mul(b.clone(), da), a.clone()),
+                        ))
+                    }
+                },
+            }
+        },
+        Node::Call(function, a) => {
+            let a  = a.as_ref().clone();
+            let da = differentiate(&a, variable);
+            let outer = match function {
+                Function::Sin   => Node::call(Function::Cos, a),
+                Function::Cos   => Node::negate(Node::call(Function::Sin, a)),
+                Function::Tan   => div(number(1.0), pow(Node::call(Function::Cos, a), number(2.0))),
+                Function::Asin  => div(number(1.0), Node::call(Function::Sqrt, sub(number(1.0), pow(a, number(2.0))))),
+                Function::Acos  => Node::negate(div(number(1.0), Node::call(Function::Sqrt, sub(number(1.0), pow(a, number(2.0)))))),
+                Function::Atan  => div(number(1.0), add(number(1.0), pow(a, number(2.0)))),
+                Function::Sinh  => Node::call(Function::Cosh, a),
+                Function::Cosh  => Node::call(Function::Sinh, a),
+                Function::Tanh  => div(number(1.0), pow(Node::call(Function::Cosh, a), number(2.0))),
+                Function::Exp   => Node::call(Function::Exp, a),
+                Function::Ln    => div(number(1.0), a),
+                Function::Sqrt  => div(number(1.0), mul(number(2.0), Node::call(Function::Sqrt, a))),
+                Function::Abs   => Node::call(Function::Sign, a),
+                // Piecewise constant, so the derivative is zero wherever it exists.
+                Function::Sign | Function::Floor | Function::Ceil => number(0.0),
+            };
+            mul(outer, da)
+        },
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+
+
+/// Byte range into the source string, used to point at the offending part of the input.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn merge(&self, other: Span) -> Self {
+        Self::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl ParseError {
+    pub fn new(message: String, span: Span) -> Self {
+        Self { message, span }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (at {}..{})", self.message, self.span.start, self.span.end)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Number(f32),
+    Identifier(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Caret,
+    LeftParenthesis,
+    RightParenthesis,
+    End,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenKind::Number(value)        => write!(f, "number '{}'", value),
+            TokenKind::Identifier(name)     => write!(f, "'{}'", name),
+            TokenKind::Plus                 => write!(f, "'+'"),
+            TokenKind::Minus                => write!(f, "'-'"),
+            TokenKind::Star                 => write!(f, "'*'"),
+            TokenKind::Slash                => write!(f, "'/'"),
+            TokenKind::Caret                => write!(f, "'^'"),
+            TokenKind::LeftParenthesis      => write!(f, "'('"),
+            TokenKind::RightParenthesis     => write!(f, "')'"),
+            TokenKind::End                  => write!(f, "end of input"),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+
+/// Splits the source into tokens. The returned list always ends with a `TokenKind::End` token.
+pub fn tokenize(source: &str) -> Result<Vec<Token>, ParseError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let kind = if c.is_ascii_digit() || c == b'.' {
+            i = scan_number(bytes, i);
+            let text = &source[start..i];
+            let value = text.parse::<f32>().map_err(|_| ParseError::new(
+                format!("Invalid number '{}'", text), Span::new(start, i)
+            ))?;
+            TokenKind::Number(value)
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            TokenKind::Identifier(source[start..i].to_string())
+        } else {
+            i += 1;
+            match c {
+                b'+' => TokenKind::Plus,
+                b'-' => TokenKind::Minus,
+                b'*' => TokenKind::Star,
+                b'/' => TokenKind::Slash,
+                b'^' => TokenKind::Caret,
+                b'(' => TokenKind::LeftParenthesis,
+                b')' => TokenKind::RightParenthesis,
+                _ => {
+                    // Report the whole (possibly multi-byte) character.
+                    let character = source[start..].chars().next().unwrap();
+                    let end = start + character.len_utf8();
+                    return Err(ParseError::new(format!("Unexpected character '{}'", character), Span::new(start, end)));
+                }
+            }
+        };
+
+        tokens.push(Token { kind, span: Span::new(start, i) });
+    }
+
+    tokens.push(Token { kind: TokenKind::End, span: Span::new(bytes.len(), bytes.len()) });
+    Ok(tokens)
+}
+
+
+/// Returns the index one past the end of the number starting at `start`. An exponent is only
+/// consumed if digits follow it, so that `2e` still lexes as `2` followed by the constant `e`.
+fn scan_number(bytes: &[u8], start: usize) -> usize {
+    let mut i = start;
+    while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+        i += 1;
+    }
+
+    if i < bytes.len() && (bytes[i] == b'e' || bytes[i] == b'E') {
+        let mut j = i + 1;
+        if j < bytes.len() && (bytes[j] == b'+' || bytes[j] == b'-') {
+            j += 1;
+        }
+        if j < bytes.len() && bytes[j].is_ascii_digit() {
+            while j < bytes.len() && bytes[j].is_ascii_digit() {
+                j += 1;
+            }
+            i = j;
+        }
+    }
+
+    i
+}
@@ -0,0 +1,218 @@
+//! Parsing and evaluation of user typed math expressions, e.g. `z = x^2 - y^2` or `sin(x*y + t)`.
+//!
+//! The module is pure Rust and doesn't touch WebGL, so everything here can be tested natively.
+
+mod ast;
+mod lexer;
+mod parser;
+mod program;
+mod derivative;
+mod simplify;
+
+pub use ast::{Node, Variable, Constant, Function, BinaryOperator};
+pub use lexer::{Span, ParseError};
+pub use derivative::differentiate;
+pub use simplify::simplify;
+use parser::Parser;
+use program::Program;
+
+
+/// The values of all variables an expression can refer to.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Variables {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub t: f32,
+    pub u: f32,
+    pub v: f32,
+}
+
+impl Variables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn xy(x: f32, y: f32) -> Self {
+        Self { x, y, ..Self::default() }
+    }
+
+    pub fn xyz(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z, ..Self::default() }
+    }
+
+    pub fn get(&self, variable: Variable) -> f32 {
+        match variable {
+            Variable::X => self.x,
+            Variable::Y => self.y,
+            Variable::Z => self.z,
+            Variable::T => self.t,
+            Variable::U => self.u,
+            Variable::V => self.v,
+        }
+    }
+
+    pub fn set(&mut self, variable: Variable, value: f32) {
+        match variable {
+            Variable::X => self.x = value,
+            Variable::Y => self.y = value,
+            Variable::Z => self.z = value,
+            Variable::T => self.t = value,
+            Variable::U => self.u = value,
+            Variable::V => self.v = value,
+        }
+    }
+}
+
+
+/// A parsed expression together with its compiled form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    root: Node,
+    program: Program,
+}
+
+impl Expression {
+    /// Parses an expression that may refer to any variable.
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        Self::parse_with_variables(source, &Variable::ALL)
+    }
+
+    /// Parses an expression, reporting any variable not in `variables` as an error. E.g. a
+    /// height field z = f(x, y) only allows `[Variable::X, Variable::Y]`.
+    pub fn parse_with_variables(source: &str, variables: &[Variable]) -> Result<Self, ParseError> {
+        Ok(Self::from_node(Parser::parse(source, variables)?))
+    }
+
+    /// Parses a comma separated list of expressions, e.g. `cos(u), sin(u), v` for a parametric
+    /// surface. Spans in errors refer to the whole source.
+    pub fn parse_tuple(source: &str, variables: &[Variable]) -> Result<Vec<Self>, ParseError> {
+        let mut expressions = Vec::new();
+        let mut depth = 0;
+        let mut start = 0;
+
+        for (i, c) in source.char_indices().chain(std::iter::once((source.len(), ','))) {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ',' if depth <= 0 => {
+                    let expression = Self::parse_with_variables(&source[start..i], variables).map_err(|error| {
+                        ParseError::new(error.message, Span::new(error.span.start + start, error.span.end + start))
+                    })?;
+                    expressions.push(expression);
+                    start = i + 1;
+                },
+                _ => {},
+            }
+        }
+
+        Ok(expressions)
+    }
+
+    /// Parses an equation `lhs = rhs` as the expression `lhs - rhs`, whose zeros are the solutions,
+    /// e.g. `x^2 + y^2 = 1` for a circle. Without `=` the source is taken as `lhs = 0`.
+    pub fn parse_equation(source: &str, variables: &[Variable]) -> Result<Self, ParseError> {
+        let split = match source.find('=') {
+            Some(i) => i,
+            None => return Self::parse_with_variables(source, variables),
+        };
+        if let Some(j) = source[split + 1..].find('=') {
+            let j = split + 1 + j;
+            return Err(ParseError::new(String::from("An equation can only have one '='"), Span::new(j, j + 1)));
+        }
+
+        let side = |start: usize, end: usize| {
+            Parser::parse(&source[start..end], variables).map_err(|error| {
+                ParseError::new(error.message, Span::new(error.span.start + start, error.span.end + start))
+            })
+        };
+        let lhs = side(0, split)?;
+        let rhs = side(split + 1, source.len())?;
+        Ok(Self::from_node(Node::binary(BinaryOperator::Sub, lhs, rhs)))
+    }
+
+    pub fn from_node(root: Node) -> Self {
+        let program = Program::compile(&root);
+        Self { root, program }
+    }
+
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    pub fn contains(&self, variable: Variable) -> bool {
+        self.root.contains(variable)
+    }
+
+    pub fn evaluate(&self, variables: &Variables) -> f32 {
+        self.program.evaluate(variables)
+    }
+
+    pub fn simplified(&self) -> Self {
+        Self::from_node(simplify(&self.root))
+    }
+
+    /// The exact partial derivative with respect to the variable, simplified.
+    pub fn derivative(&self, variable: Variable) -> Self {
+        Self::from_node(simplify(&differentiate(&self.root, variable)))
+    }
+
+    /// The partial derivatives with respect to each of the variables, in order.
+    pub fn gradient(&self, variables: &[Variable]) -> Vec<Self> {
+        variables.iter().map(|&variable| self.derivative(variable)).collect()
+    }
+
+    /// The matrix of second order partial derivatives, as rows. Each entry is differentiated from
+    /// the simplified first derivative, so the matrix is symmetric only up to simplification.
+    pub fn hessian(&self, variables: &[Variable]) -> Vec<Vec<Self>> {
+        self.gradient(variables).iter().map(|partial| partial.gradient(variables)).collect()
+    }
+
+    /// The matrix of partial derivatives of a vector valued function, one row per component.
+    pub fn jacobian(components: &[Self], variables: &[Variable]) -> Vec<Vec<Self>> {
+        components.iter().map(|component| component.gradient(variables)).collect()
+    }
+
+    /// The divergence ∂F_1/∂v_1 + ... + ∂F_n/∂v_n of a vector field with a component per variable.
+    pub fn divergence(components: &[Self], variables: &[Variable]) -> Self {
+        assert_eq!(components.len(), variables.len(), "The divergence needs a component per variable.");
+
+        let sum = components.iter().zip(variables.iter())
+            .map(|(component, &variable)| differentiate(&component.root, variable))
+            .reduce(|a, b| Node::binary(BinaryOperator::Add, a, b))
+            .unwrap_or(Node::Number(0.0));
+        Self::from_node(simplify(&sum))
+    }
+
+    /// The curl ∇ × F of a vector field F = (P, Q, R) in x, y and z.
+    pub fn curl(components: &[Self; 3]) -> [Self; 3] {
+        let [p, q, r] = components;
+        let partial = |component: &Self, variable: Variable| differentiate(&component.root, variable);
+        let difference = |a: Node, b: Node| Self::from_node(simplify(&Node::binary(BinaryOperator::Sub, a, b)));
+        [
+            difference(partial(r, Variable::Y), partial(q, Variable::Z)),
+            difference(partial(p, Variable::Z), partial(r, Variable::X)),
+            difference(partial(q, Variable::X), partial(p, Variable::Y)),
+        ]
+    }
+
+    /// Evaluates the expression as a function of x and y, with the time `t` held fixed.
+    pub fn evaluate_xy(&self, x: f32, y: f32, t: f32) -> f32 {
+        self.program.evaluate(&Variables { x, y, t, ..Variables::default() })
+    }
+}
+
+impl std::fmt::Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.root)
+    }
+}
+
+
+#[cfg(test)]
+#[path = "tests/expression.rs"]
+mod tests;
+
+#[cfg(test)]
+#[path = "tests/derivative.rs"]
+mod derivative_tests;
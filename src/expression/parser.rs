@@ -0,0 +1,165 @@
+use super::ast::{Node, Variable, Constant, Function, BinaryOperator};
+use super::lexer::{tokenize, Token, TokenKind, Span, ParseError};
+
+
+/// Recursive descent parser with the grammar
+///
+///     sum     = product (('+' | '-') product)*
+///     product = unary (('*' | '/') unary | unary)*      -- juxtaposition is multiplication, '2x'
+///     unary   = ('-' | '+') unary | power
+///     power   = primary ('^' unary)?                   -- right associative, '-x^2' is '-(x^2)'
+///     primary = number | constant | variable | function '(' sum ')' | '(' sum ')'
+pub struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    variables: &'a [Variable],
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// How deeply parentheses, signs and exponents may nest. Deeper input is an error instead of
+    /// overflowing the stack, which traps in wasm.
+    pub const MAX_DEPTH: usize = 100;
+
+    pub fn parse(source: &str, variables: &'a [Variable]) -> Result<Node, ParseError> {
+        let mut parser = Self {
+            tokens: tokenize(source)?,
+            position: 0,
+            variables,
+            depth: 0,
+        };
+
+        if parser.peek().kind == TokenKind::End {
+            return Err(ParseError::new(String::from("Expression is empty"), parser.peek().span));
+        }
+
+        let node = parser.sum()?;
+
+        let token = parser.peek();
+        match token.kind {
+            TokenKind::End => Ok(node),
+            TokenKind::RightParenthesis => Err(ParseError::new(String::from("Unmatched ')'"), token.span)),
+            _ => Err(ParseError::new(format!("Unexpected {}", token.kind), token.span)),
+        }
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+        if token.kind != TokenKind::End {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn sum(&mut self) -> Result<Node, ParseError> {
+        let mut node = self.product()?;
+        loop {
+            let operator = match self.peek().kind {
+                TokenKind::Plus  => BinaryOperator::Add,
+                TokenKind::Minus => BinaryOperator::Sub,
+                _ => return Ok(node),
+            };
+            self.advance();
+            node = Node::binary(operator, node, self.product()?);
+        }
+    }
+
+    fn product(&mut self) -> Result<Node, ParseError> {
+        let mut node = self.unary()?;
+        loop {
+            let operator = match self.peek().kind {
+                TokenKind::Star  => { self.advance(); BinaryOperator::Mul },
+                TokenKind::Slash => { self.advance(); BinaryOperator::Div },
+                TokenKind::Number(_) | TokenKind::Identifier(_) | TokenKind::LeftParenthesis => BinaryOperator::Mul,
+                _ => return Ok(node),
+            };
+            node = Node::binary(operator, node, self.unary()?);
+        }
+    }
+
+    // Every nested sub-expression passes through here, so this is where the depth is tracked.
+    fn unary(&mut self) -> Result<Node, ParseError> {
+        if self.depth == Self::MAX_DEPTH {
+            return Err(ParseError::new(format!("Expression is nested more than {} levels deep", Self::MAX_DEPTH), self.peek().span));
+        }
+        self.depth += 1;
+        let node = match self.peek().kind {
+            TokenKind::Minus => {
+                self.advance();
+                self.unary().map(Node::negate)
+            },
+            TokenKind::Plus => {
+                self.advance();
+                self.unary()
+            },
+            _ => self.power(),
+        };
+        self.depth -= 1;
+        node
+    }
+
+    fn power(&mut self) -> Result<Node, ParseError> {
+        let base = self.primary()?;
+        if self.peek().kind == TokenKind::Caret {
+            self.advance();
+            Ok(Node::binary(BinaryOperator::Pow, base, self.unary()?))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn primary(&mut self) -> Result<Node, ParseError> {
+        let token = self.advance();
+        match token.kind {
+            TokenKind::Number(value) => Ok(Node::Number(value)),
+            TokenKind::Identifier(name) => self.identifier(&name, token.span),
+            TokenKind::LeftParenthesis => {
+                let node = self.sum()?;
+                self.expect_closing(token.span)?;
+                Ok(node)
+            },
+            TokenKind::End => Err(ParseError::new(String::from("Unexpected end of input"), token.span)),
+            kind => Err(ParseError::new(format!("Expected a value but found {}", kind), token.span)),
+        }
+    }
+
+    fn identifier(&mut self, name: &str, span: Span) -> Result<Node, ParseError> {
+        if let Some(function) = Function::from_name(name) {
+            let open = self.advance();
+            if open.kind != TokenKind::LeftParenthesis {
+                return Err(ParseError::new(format!("Expected '(' after function '{}'", name), span.merge(open.span)));
+            }
+            let argument = self.sum()?;
+            self.expect_closing(open.span)?;
+            Ok(Node::call(function, argument))
+        } else if let Some(constant) = Constant::from_name(name) {
+            Ok(Node::Constant(constant))
+        } else if let Some(variable) = Variable::from_name(name) {
+            if self.variables.contains(&variable) {
+                Ok(Node::Variable(variable))
+            } else {
+                let allowed: Vec<&str> = self.variables.iter().map(|v| v.name()).collect();
+                Err(ParseError::new(
+                    format!("Variable '{}' is not allowed here (expected one of: {})", name, allowed.join(", ")), span
+                ))
+            }
+        } else {
+            Err(ParseError::new(format!("Unknown identifier '{}'", name), span))
+        }
+    }
+
+    fn expect_closing(&mut self, open: Span) -> Result<(), ParseError> {
+        let token = self.advance();
+        if token.kind == TokenKind::RightParenthesis {
+            Ok(())
+        } else if token.kind == TokenKind::End {
+            Err(ParseError::new(String::from("Unclosed '('"), open))
+        } else {
+            Err(ParseError::new(format!("Expected ')' but found {}", token.kind), token.span))
+        }
+    }
+}
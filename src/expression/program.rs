@@ -0,0 +1,108 @@
+use super::ast::{Node, Variable, Function, BinaryOperator};
+use super::Variables;
+
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Instruction {
+    Push(f32),
+    Load(Variable),
+    Negate,
+    Binary(BinaryOperator),
+    Call(Function),
+}
+
+
+/// A flattened, constant folded version of the syntax tree that is evaluated on a small stack.
+/// Sampling a surface evaluates the same expression tens of thousands of times, so this avoids
+/// both the pointer chasing of the tree and any allocations per evaluation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    instructions: Vec<Instruction>,
+    stack_size: usize,
+}
+
+impl Program {
+    const INLINE_STACK_SIZE: usize = 32;
+
+    pub fn compile(node: &Node) -> Self {
+        let mut instructions = Vec::new();
+        let stack_size = Self::emit(node, &mut instructions);
+        Self { instructions, stack_size }
+    }
+
+    /// Emits the instructions for the node and returns the stack depth needed to evaluate it.
+    fn emit(node: &Node, instructions: &mut Vec<Instruction>) -> usize {
+        if let Some(value) = node.constant_value() {
+            instructions.push(Instruction::Push(value));
+            return 1;
+        }
+
+        match node {
+            Node::Number(value) => {
+                instructions.push(Instruction::Push(*value));
+                1
+            },
+            Node::Constant(constant) => {
+                instructions.push(Instruction::Push(constant.value()));
+                1
+            },
+            Node::Variable(variable) => {
+                instructions.push(Instruction::Load(*variable));
+                1
+            },
+            Node::Negate(a) => {
+                let depth = Self::emit(a, instructions);
+                instructions.push(Instruction::Negate);
+                depth
+            },
+            Node::Binary(op, a, b) => {
+                let left  = Self::emit(a, instructions);
+                let right = Self::emit(b, instructions);
+                instructions.push(Instruction::Binary(*op));
+                left.max(right + 1)
+            },
+            Node::Call(function, a) => {
+                let depth = Self::emit(a, instructions);
+                instructions.push(Instruction::Call(*function));
+                depth
+            },
+        }
+    }
+
+    pub fn evaluate(&self, variables: &Variables) -> f32 {
+        if self.stack_size <= Self::INLINE_STACK_SIZE {
+            let mut stack = [0.0; Self::INLINE_STACK_SIZE];
+            self.run(variables, &mut stack)
+        } else {
+            let mut stack = vec![0.0; self.stack_size];
+            self.run(variables, &mut stack)
+        }
+    }
+
+    fn run(&self, variables: &Variables, stack: &mut [f32]) -> f32 {
+        let mut top = 0;
+        for instruction in &self.instructions {
+            match *instruction {
+                Instruction::Push(value) => {
+                    stack[top] = value;
+                    top += 1;
+                },
+                Instruction::Load(variable) => {
+                    stack[top] = variables.get(variable);
+                    top += 1;
+                },
+                Instruction::Negate => {
+                    stack[top - 1] = -stack[top - 1];
+                },
+                Instruction::Binary(op) => {
+                    top -= 1;
+                    stack[top - 1] = op.apply(stack[top - 1], stack[top]);
+                },
+                Instruction::Call(function) => {
+                    stack[top - 1] = function.apply(stack[top - 1]);
+                },
+            }
+        }
+        stack[0]
+    }
+}
@@ -0,0 +1,297 @@
+use super::ast::{Node, BinaryOperator};
+
+use BinaryOperator::{Add, Sub, Mul, Div, Pow};
+
+
+/// Algebraic simplification, mainly to clean up the output of `differentiate`. It applies local
+/// rewrite rules (identities such as x + 0 = x, x * 1 = x, folding of numbers, collecting like
+/// terms and powers) bottom up until nothing changes. It's not a canonical form; two equal
+/// expressions may still simplify to different trees.
+pub fn simplify(node: &Node) -> Node {
+    const MAX_PASSES: usize = 16;
+
+    let mut current = node.clone();
+    for _ in 0..MAX_PASSES {
+        let next = simplify_once(&current);
+        if next == current {
+            break;
+        }
+        current = next;
+    }
+    current
+}
+
+fn simplify_once(node: &Node) -> Node {
+    match node {
+        Node::Number(_) | Node::Constant(_) | Node::Variable(_) => node.clone(),
+        Node::Negate(a)        => negate(simplify_once(a)),
+        Node::Binary(op, a, b) => binary(*op, simplify_once(a), simplify_once(b)),
+        Node::Call(function, a) => {
+            let a = simplify_once(a);
+            match a {
+                // Only fold when the result is exact, so cos(0) becomes 1 but sqrt(2) stays.
+                Node::Number(value) if is_exact(function.apply(value)) => Node::Number(function.apply(value)),
+                _ => Node::call(*function, a),
+            }
+        },
+    }
+}
+
+
+fn is_exact(value: f32) -> bool {
+    value.is_finite() && value.fract() == 0.0
+}
+
+fn is_number(node: &Node, value: f32) -> bool {
+    matches!(node, Node::Number(n) if *n == value)
+}
+
+/// Sort key that puts factors in a readable order, e.g. `2 * pi * x^2 * y * sin(x)`.
+fn factor_order(node: &Node) -> (u8, String) {
+    match node {
+        Node::Number(_)   => (0, String::new()),
+        Node::Constant(_) => (1, node.to_string()),
+        Node::Variable(variable) => (2, String::from(variable.name())),
+        Node::Binary(Pow, a, b) if matches!(**b, Node::Number(_)) => match **a {
+            Node::Variable(variable) => (2, String::from(variable.name())),
+            _ => (3, node.to_string()),
+        },
+        _ => (3, node.to_string()),
+    }
+}
+
+/// Splits `n * x` into (n, x), so like terms can be collected.
+fn split_coefficient(node: &Node) -> (f32, Node) {
+    match node {
+        Node::Binary(Mul, a, b) => match **a {
+            Node::Number(n) => (n, (**b).clone()),
+            _ => (1.0, node.clone()),
+        },
+        Node::Negate(a) => {
+            let (n, rest) = split_coefficient(a);
+            (-n, rest)
+        },
+        _ => (1.0, node.clone()),
+    }
+}
+
+/// Splits `x^n` into (x, n), so powers of the same base can be merged.
+fn split_power(node: &Node) -> (Node, f32) {
+    match node {
+        Node::Binary(Pow, a, b) => match **b {
+            Node::Number(n) => ((**a).clone(), n),
+            _ => (node.clone(), 1.0),
+        },
+        _ => (node.clone(), 1.0),
+    }
+}
+
+
+fn binary(op: BinaryOperator, a: Node, b: Node) -> Node {
+    match op {
+        Add => add(a, b),
+        Sub => sub(a, b),
+        Mul => mul(a, b),
+        Div => div(a, b),
+        Pow => pow(a, b),
+    }
+}
+
+fn negate(a: Node) -> Node {
+    match a {
+        Node::Number(n) => Node::Number(-n),
+        Node::Negate(b) => *b,
+        Node::Binary(Sub, b, c) => sub(*c, *b),
+        Node::Binary(Mul, _, _) => mul(Node::Number(-1.0), a),
+        _ => Node::negate(a),
+    }
+}
+
+fn add(a: Node, b: Node) -> Node {
+    if let (Node::Number(x), Node::Number(y)) = (&a, &b) {
+        return Node::Number(x + y);
+    }
+    if is_number(&a, 0.0) {
+        return b;
+    }
+    if is_number(&b, 0.0) {
+        return a;
+    }
+    match (a, b) {
+        (a, Node::Negate(b)) => sub(a, *b),
+        (a, Node::Number(n)) if n < 0.0 => sub(a, Node::Number(-n)),
+        (Node::Negate(a), b) => sub(b, *a),
+        (a, b) => {
+            let (n, x) = split_coefficient(&a);
+            let (m, y) = split_coefficient(&b);
+            if x == y {
+                mul(Node::Number(n + m), x)
+            } else {
+                Node::binary(Add, a, b)
+            }
+        },
+    }
+}
+
+fn sub(a: Node, b: Node) -> Node {
+    if let (Node::Number(x), Node::Number(y)) = (&a, &b) {
+        return Node::Number(x - y);
+    }
+    if is_number(&b, 0.0) {
+        return a;
+    }
+    if is_number(&a, 0.0) {
+        return negate(b);
+    }
+    match (a, b) {
+        (a, Node::Negate(b)) => add(a, *b),
+        (a, Node::Number(n)) if n < 0.0 => add(a, Node::Number(-n)),
+        (a, b) => {
+            let (n, x) = split_coefficient(&a);
+            let (m, y) = split_coefficient(&b);
+            if x == y {
+                mul(Node::Number(n - m), x)
+            } else {
+                Node::binary(Sub, a, b)
+            }
+        },
+    }
+}
+
+fn mul(a: Node, b: Node) -> Node {
+    if let (Node::Number(x), Node::Number(y)) = (&a, &b) {
+        return Node::Number(x * y);
+    }
+    if is_number(&a, 0.0) || is_number(&b, 0.0) {
+        return Node::Number(0.0);
+    }
+
+    // Flatten the product into a coefficient, factors and divisors, so that numbers can be
+    // combined, powers of the same base merged and the factors sorted.
+    let mut coefficient = 1.0;
+    let mut factors  = Vec::new();
+    let mut divisors = Vec::new();
+    collect_factors(a, &mut coefficient, &mut factors, &mut divisors);
+    collect_factors(b, &mut coefficient, &mut factors, &mut divisors);
+    if coefficient == 0.0 {
+        return Node::Number(0.0);
+    }
+
+    let mut merged: Vec<(Node, f32)> = Vec::new();
+    let mut others: Vec<Node> = Vec::new();
+    for factor in factors {
+        let (base, exponent) = split_power(&factor);
+        if exponent == 1.0 && matches!(factor, Node::Binary(Pow, _, _)) {
+            others.push(factor);  // Symbolic exponent, e.g. 2^x, which can't be merged.
+        } else if let Some(entry) = merged.iter_mut().find(|(b, _)| *b == base) {
+            entry.1 += exponent;
+        } else {
+            merged.push((base, exponent));
+        }
+    }
+
+    let mut factors: Vec<Node> = merged.into_iter()
+        .filter(|(_, exponent)| *exponent != 0.0)
+        .map(|(base, exponent)| if exponent == 1.0 { base } else { Node::binary(Pow, base, Node::Number(exponent)) })
+        .chain(others)
+        .collect();
+    factors.sort_by_key(factor_order);
+
+    let mut factors = factors.into_iter();
+    let product = match factors.next() {
+        None => Node::Number(coefficient),
+        Some(first) => {
+            let first = if coefficient == 1.0 {
+                first
+            } else if coefficient == -1.0 {
+                Node::negate(first)
+            } else {
+                Node::binary(Mul, Node::Number(coefficient), first)
+            };
+            factors.fold(first, |product, factor| Node::binary(Mul, product, factor))
+        },
+    };
+
+    match divisors.len() {
+        0 => product,
+        _ => {
+            let divisor = divisors.into_iter().reduce(|a, b| Node::binary(Mul, a, b)).unwrap();
+            div(product, divisor)
+        },
+    }
+}
+
+fn collect_factors(node: Node, coefficient: &mut f32, factors: &mut Vec<Node>, divisors: &mut Vec<Node>) {
+    match node {
+        Node::Number(n) => *coefficient *= n,
+        Node::Negate(a) => {
+            *coefficient = -*coefficient;
+            collect_factors(*a, coefficient, factors, divisors);
+        },
+        Node::Binary(Mul, a, b) => {
+            collect_factors(*a, coefficient, factors, divisors);
+            collect_factors(*b, coefficient, factors, divisors);
+        },
+        // n / x contributes n to the coefficient and x to the divisors.
+        Node::Binary(Div, a, b) if matches!(*a, Node::Number(_)) => {
+            collect_factors(*a, coefficient, factors, divisors);
+            divisors.push(*b);
+        },
+        other => factors.push(other),
+    }
+}
+
+fn div(a: Node, b: Node) -> Node {
+    if let (Node::Number(x), Node::Number(y)) = (&a, &b) {
+        if is_exact(x / y) {
+            return Node::Number(x / y);
+        }
+    }
+    if is_number(&a, 0.0) && !is_number(&b, 0.0) {
+        return Node::Number(0.0);
+    }
+    if is_number(&b, 1.0) {
+        return a;
+    }
+    if a == b {
+        return Node::Number(1.0);
+    }
+
+    match (a, b) {
+        (Node::Negate(a), b) => negate(div(*a, b)),
+        (a, Node::Negate(b)) => negate(div(a, *b)),
+        (a, b) => {
+            // x^n / x^m = x^(n - m)
+            let (x, n) = split_power(&a);
+            let (y, m) = split_power(&b);
+            if x == y && !matches!(x, Node::Number(_)) {
+                pow(x, Node::Number(n - m))
+            } else {
+                Node::binary(Div, a, b)
+            }
+        },
+    }
+}
+
+fn pow(a: Node, b: Node) -> Node {
+    if let (Node::Number(x), Node::Number(y)) = (&a, &b) {
+        if is_exact(x.powf(*y)) {
+            return Node::Number(x.powf(*y));
+        }
+    }
+    if is_number(&b, 0.0) || is_number(&a, 1.0) {
+        return Node::Number(1.0);
+    }
+    if is_number(&b, 1.0) {
+        return a;
+    }
+
+    match (a, b) {
+        // (x^n)^m = x^(n m), which only holds in general for integer m.
+        (Node::Binary(Pow, x, n), Node::Number(m)) if matches!(*n, Node::Number(_)) && m.fract() == 0.0 => {
+            let n = if let Node::Number(n) = *n { n } else { unreachable!() };
+            pow(*x, Node::Number(n * m))
+        },
+        (a, b) => Node::binary(Pow, a, b),
+    }
+}
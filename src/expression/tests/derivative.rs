@@ -0,0 +1,122 @@
+use super::*;
+
+fn derivative(source: &str, variable: Variable) -> String {
+    Expression::parse(source).unwrap().derivative(variable).to_string()
+}
+
+/// Compares the symbolic derivative against central differences at a few points.
+fn assert_matches_numerical(source: &str, variable: Variable) {
+    let expression = Expression::parse(source).unwrap();
+    let derivative = expression.derivative(variable);
+    let h = 1e-2;
+
+    for &(x, y) in &[(0.3, 0.7), (1.1, -0.4), (-0.6, 0.2), (0.8, 1.3)] {
+        let mut variables = Variables::xy(x, y);
+        let exact = derivative.evaluate(&variables);
+
+        let value = variables.get(variable);
+        variables.set(variable, value + h);
+        let ahead = expression.evaluate(&variables);
+        variables.set(variable, value - h);
+        let behind = expression.evaluate(&variables);
+        let numerical = (ahead - behind) / (2.0 * h);
+
+        assert!((exact - numerical).abs() < 1e-2 * (1.0 + exact.abs()),
+            "d/d{} {} = {} gives {} at ({}, {}) but differences give {}", variable.name(), source, derivative, exact, x, y, numerical);
+    }
+}
+
+
+#[test]
+pub fn simple_derivatives_are_simplified() {
+    assert_eq!(derivative("x^2 - y^2", Variable::X), "2 * x");
+    assert_eq!(derivative("x^2 - y^2", Variable::Y), "-2 * y");
+    assert_eq!(derivative("3x + 2", Variable::X), "3");
+    assert_eq!(derivative("x * y", Variable::X), "y");
+    assert_eq!(derivative("sin(x * y)", Variable::X), "y * cos(x * y)");
+    assert_eq!(derivative("exp(x)", Variable::X), "exp(x)");
+    assert_eq!(derivative("x^3", Variable::X), "3 * x^2");
+    assert_eq!(derivative("y", Variable::X), "0");
+}
+
+#[test]
+pub fn derivatives_match_finite_differences() {
+    let sources = [
+        "x^2 * y + sin(x) * cos(y)",
+        "exp(-(x^2 + y^2))",
+        "x / (1 + y^2)",
+        "ln(x^2 + 1) + sqrt(y^2 + 2)",
+        "tan(x / 2) + atan(y) + asin(x / 3) + acos(y / 3)",
+        "sinh(x) * cosh(y) + tanh(x * y)",
+        "2^x + (x^2 + 1)^y + (x + 2)^pi",
+        "abs(x) * y",
+        "-(x - y)^3 / (x + 3)",
+    ];
+    for source in &sources {
+        assert_matches_numerical(source, Variable::X);
+        assert_matches_numerical(source, Variable::Y);
+    }
+}
+
+#[test]
+pub fn gradient_and_hessian() {
+    let expression = Expression::parse("x^2 * y + y^3").unwrap();
+    let variables  = [Variable::X, Variable::Y];
+
+    let gradient: Vec<String> = expression.gradient(&variables).iter().map(|e| e.to_string()).collect();
+    assert_eq!(gradient, vec!["2 * x * y", "x^2 + 3 * y^2"]);
+
+    let hessian: Vec<Vec<String>> = expression.hessian(&variables).iter()
+        .map(|row| row.iter().map(|e| e.to_string()).collect())
+        .collect();
+    assert_eq!(hessian, vec![vec!["2 * y", "2 * x"], vec!["2 * x", "6 * y"]]);
+}
+
+#[test]
+pub fn jacobian() {
+    let components = Expression::parse_tuple("u * cos(v), u * sin(v)", &Variable::ALL).unwrap();
+    let jacobian   = Expression::jacobian(&components, &[Variable::U, Variable::V]);
+
+    let variables = Variables { u: 2.0, v: 0.5, ..Variables::default() };
+    let values: Vec<Vec<f32>> = jacobian.iter().map(|row| row.iter().map(|e| e.evaluate(&variables)).collect()).collect();
+    let expected = [[0.5f32.cos(), -2.0 * 0.5f32.sin()], [0.5f32.sin(), 2.0 * 0.5f32.cos()]];
+    for i in 0..2 {
+        for j in 0..2 {
+            assert!((values[i][j] - expected[i][j]).abs() < 1e-5);
+        }
+    }
+}
+
+#[test]
+pub fn simplify_identities() {
+    let simplified = |source: &str| Expression::parse(source).unwrap().simplified().to_string();
+    assert_eq!(simplified("0 + x * 1"), "x");
+    assert_eq!(simplified("x - x"), "0");
+    assert_eq!(simplified("x + x"), "2 * x");
+    assert_eq!(simplified("x * x * x"), "x^3");
+    assert_eq!(simplified("x^5 / x^2"), "x^3");
+    assert_eq!(simplified("--x"), "x");
+    assert_eq!(simplified("(x^2)^3"), "x^6");
+    assert_eq!(simplified("1 / 3"), "1 / 3");
+    assert_eq!(simplified("cos(0) + sqrt(2)"), "1 + sqrt(2)");
+    assert_eq!(simplified("-(-x * y)"), "x * y");
+    assert_eq!(simplified("y * 3 * x * 2"), "6 * x * y");
+}
+
+#[test]
+pub fn divergence_and_curl() {
+    let field = Expression::parse_tuple("x y, y z, z x", &Variable::ALL).unwrap();
+    assert_eq!(Expression::divergence(&field, &[Variable::X, Variable::Y, Variable::Z]).to_string(), "y + z + x");
+
+    // The rotation (-y, x, 0) has curl (0, 0, 2) and no divergence.
+    let rotation = Expression::parse_tuple("-y, x, 0", &Variable::ALL).unwrap();
+    let rotation = [rotation[0].clone(), rotation[1].clone(), rotation[2].clone()];
+    let curl: Vec<String> = Expression::curl(&rotation).iter().map(|c| c.to_string()).collect();
+    assert_eq!(curl, vec!["0", "0", "2"]);
+    assert_eq!(Expression::divergence(&rotation, &[Variable::X, Variable::Y, Variable::Z]).to_string(), "0");
+
+    // A gradient field has no curl.
+    let gradient = Expression::parse("x^2 y + sin(z)").unwrap().gradient(&[Variable::X, Variable::Y, Variable::Z]);
+    let gradient = [gradient[0].clone(), gradient[1].clone(), gradient[2].clone()];
+    assert!(Expression::curl(&gradient).iter().all(|c| c.to_string() == "0"));
+}
@@ -0,0 +1,163 @@
+use super::*;
+use crate::test_support::assert_close;
+
+fn evaluate(source: &str, x: f32, y: f32) -> f32 {
+    Expression::parse(source).unwrap().evaluate(&Variables::xy(x, y))
+}
+
+
+#[test]
+pub fn precedence() {
+    assert_close(evaluate("1 + 2 * 3", 0.0, 0.0), 7.0);
+    assert_close(evaluate("(1 + 2) * 3", 0.0, 0.0), 9.0);
+    assert_close(evaluate("8 / 4 / 2", 0.0, 0.0), 1.0);
+    assert_close(evaluate("10 - 4 - 3", 0.0, 0.0), 3.0);
+}
+
+#[test]
+pub fn power_is_right_associative_and_binds_tighter_than_negation() {
+    assert_close(evaluate("2^3^2", 0.0, 0.0), 512.0);
+    assert_close(evaluate("-x^2", 3.0, 0.0), -9.0);
+    assert_close(evaluate("2^-1", 0.0, 0.0), 0.5);
+}
+
+#[test]
+pub fn variables_and_constants() {
+    assert_close(evaluate("x^2 - y^2", 3.0, 2.0), 5.0);
+    assert_close(evaluate("2 * pi", 0.0, 0.0), 2.0 * std::f32::consts::PI);
+    assert_close(evaluate("tau - e", 0.0, 0.0), 2.0 * std::f32::consts::PI - std::f32::consts::E);
+
+    let expression = Expression::parse("x + y + z + t + u + v").unwrap();
+    let variables = Variables { x: 1.0, y: 2.0, z: 3.0, t: 4.0, u: 5.0, v: 6.0 };
+    assert_close(expression.evaluate(&variables), 21.0);
+}
+
+#[test]
+pub fn functions() {
+    assert_close(evaluate("sin(x) + cos(y)", 0.0, 0.0), 1.0);
+    assert_close(evaluate("exp(ln(x))", 2.5, 0.0), 2.5);
+    assert_close(evaluate("log(e)", 0.0, 0.0), 1.0);
+    assert_close(evaluate("sqrt(abs(x))", -16.0, 0.0), 4.0);
+    assert_close(evaluate("sign(x) * floor(y) + ceil(y)", -1.0, 1.5), 1.0);
+}
+
+#[test]
+pub fn implicit_multiplication() {
+    assert_close(evaluate("2x", 3.0, 0.0), 6.0);
+    assert_close(evaluate("2x^2", 3.0, 0.0), 18.0);
+    assert_close(evaluate("x y", 3.0, 4.0), 12.0);
+    assert_close(evaluate("(x + 1)(x - 1)", 3.0, 0.0), 8.0);
+    assert_close(evaluate("2 sin(pi / 2)", 0.0, 0.0), 2.0);
+}
+
+#[test]
+pub fn scientific_notation() {
+    assert_close(evaluate("1.5e2", 0.0, 0.0), 150.0);
+    assert_close(evaluate("2e", 0.0, 0.0), 2.0 * std::f32::consts::E);
+}
+
+#[test]
+pub fn display_round_trips() {
+    for source in &["x^2 - y^2", "-(x + y) * 2", "sin(x * y + t)", "(x - y) - (x - y)", "x / (y * 2)", "(-x)^2", "2^3^2"] {
+        let expression = Expression::parse(source).unwrap();
+        let printed    = expression.to_string();
+        let reparsed   = Expression::parse(&printed).unwrap();
+        assert_eq!(expression.root(), reparsed.root(), "'{}' printed as '{}'", source, printed);
+    }
+}
+
+#[test]
+pub fn display_uses_minimal_parentheses() {
+    assert_eq!(Expression::parse("((x)) + ((y * 2))").unwrap().to_string(), "x + y * 2");
+    assert_eq!(Expression::parse("x - (y + 1)").unwrap().to_string(), "x - (y + 1)");
+}
+
+#[test]
+pub fn error_spans() {
+    let error = Expression::parse("x + foo(y)").unwrap_err();
+    assert_eq!(error.span, Span::new(4, 7));
+
+    let error = Expression::parse("x + $").unwrap_err();
+    assert_eq!(error.span, Span::new(4, 5));
+
+    let error = Expression::parse("sin(x").unwrap_err();
+    assert_eq!(error.span, Span::new(3, 4));
+
+    let error = Expression::parse("x + 1)").unwrap_err();
+    assert_eq!(error.span, Span::new(5, 6));
+
+    let error = Expression::parse("x * ").unwrap_err();
+    assert_eq!(error.span, Span::new(4, 4));
+
+    let error = Expression::parse("sin x").unwrap_err();
+    assert_eq!(error.span, Span::new(0, 5));
+
+    let error = Expression::parse("   ").unwrap_err();
+    assert_eq!(error.span, Span::new(3, 3));
+}
+
+#[test]
+pub fn restricted_variables() {
+    let allowed = [Variable::X, Variable::Y];
+    assert!(Expression::parse_with_variables("x * y", &allowed).is_ok());
+
+    let error = Expression::parse_with_variables("x * z", &allowed).unwrap_err();
+    assert_eq!(error.span, Span::new(4, 5));
+}
+
+#[test]
+pub fn contains_variable() {
+    let expression = Expression::parse("x + sin(t)").unwrap();
+    assert!(expression.contains(Variable::X));
+    assert!(expression.contains(Variable::T));
+    assert!(!expression.contains(Variable::Y));
+}
+
+#[test]
+pub fn deep_expressions_fall_back_to_heap_stack() {
+    let source = (0..40).map(|_| "(x + ").collect::<String>() + "1" + &")".repeat(40);
+    assert_close(evaluate(&source, 1.0, 0.0), 41.0);
+}
+
+#[test]
+pub fn too_deep_nesting_is_an_error() {
+    let nested = |depth: usize| "(".repeat(depth) + "x" + &")".repeat(depth);
+    assert!(Expression::parse(&nested(Parser::MAX_DEPTH - 1)).is_ok());
+
+    let error = Expression::parse(&nested(100_000)).unwrap_err();
+    assert_eq!(error.span, Span::new(Parser::MAX_DEPTH, Parser::MAX_DEPTH + 1));
+
+    let error = Expression::parse(&"-".repeat(100_000)).unwrap_err();
+    assert_eq!(error.span, Span::new(Parser::MAX_DEPTH, Parser::MAX_DEPTH + 1));
+    assert!(Expression::parse(&"x^".repeat(100_000)).is_err());
+}
+
+#[test]
+pub fn tuples() {
+    let expressions = Expression::parse_tuple("cos(u), sin(u), atan(u * (v + 1))", &Variable::ALL).unwrap();
+    assert_eq!(expressions.len(), 3);
+    assert_close(expressions[0].evaluate(&Variables::new()), 1.0);
+
+    let error = Expression::parse_tuple("u, v, w", &Variable::ALL).unwrap_err();
+    assert_eq!(error.span, Span::new(6, 7));
+
+    let error = Expression::parse_tuple("u, , v", &Variable::ALL).unwrap_err();
+    assert_eq!(error.span, Span::new(3, 3));
+}
+
+#[test]
+pub fn equations_are_moved_to_one_side() {
+    let sphere = Expression::parse_equation("x^2 + y^2 + z^2 = 4", &Variable::ALL).unwrap();
+    assert_eq!(sphere.evaluate(&Variables::xyz(2.0, 0.0, 0.0)), 0.0);
+    assert_eq!(sphere.evaluate(&Variables::xyz(0.0, 0.0, 0.0)), -4.0);
+
+    let implicit = Expression::parse_equation("x^2 - 1", &Variable::ALL).unwrap();
+    assert_eq!(implicit.evaluate(&Variables::xyz(1.0, 0.0, 0.0)), 0.0);
+
+    let error = Expression::parse_equation("x = y = z", &Variable::ALL).unwrap_err();
+    assert_eq!(error.span, Span::new(6, 7));
+
+    // Spans on the right hand side refer to the whole source.
+    let error = Expression::parse_equation("x = y + ", &Variable::ALL).unwrap_err();
+    assert!(error.span.start >= 4);
+}
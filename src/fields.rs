@@ -0,0 +1,278 @@
+use crate::math::Vector3;
+
+use crate::implicit::BoundingBox;
+use crate::utils::{SurfaceData, Domain, Placement, math_to_world};
+
+
+/// One arrow of a vector field, in math coordinates. `magnitude` drives the length and color of
+/// the arrow, and isn't necessarily the length of `direction`; e.g. a gradient lifted onto its
+/// surface points along the surface but is colored by |∇f|.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Arrow {
+    pub origin: Vector3,
+    pub direction: Vector3,
+    pub magnitude: f32,
+}
+
+/// Floats per arrow in the instance buffer: origin, vector and color, all `vec3`s.
+pub const INSTANCE_ATTRIBUTE_SIZES: [i32; 3] = [3, 3, 3];
+pub const INSTANCE_LOCATION: u32 = 3;
+
+
+/// An arrow of unit length along +y, starting at the origin, in the same layout as
+/// `create_surface`. The shaft is a cylinder and the head a cone with a closed base.
+pub fn create_arrow(sides: usize) -> SurfaceData {
+    assert!(sides >= 3, "An arrow needs at least three sides.");
+
+    const SHAFT_RADIUS: f32 = 0.035;
+    const HEAD_RADIUS:  f32 = 0.09;
+    const HEAD_START:   f32 = 0.7;
+
+    let mut vertices = Vec::new();
+    let mut indices  = Vec::new();
+    let push = |vertices: &mut Vec<f32>, position: [f32; 3], uv: [f32; 2], normal: [f32; 3]| {
+        vertices.extend_from_slice(&position);
+        vertices.extend_from_slice(&uv);
+        vertices.extend_from_slice(&normal);
+        (vertices.len() / SurfaceData::COMPONENTS_PER_VERTEX - 1) as u32
+    };
+
+    // Slope of the cone, for its normals.
+    let head_length = 1.0 - HEAD_START;
+    let slope = (head_length * head_length + HEAD_RADIUS * HEAD_RADIUS).sqrt();
+    let (normal_radial, normal_up) = (head_length / slope, HEAD_RADIUS / slope);
+
+    let center = push(&mut vertices, [0.0, HEAD_START, 0.0], [0.5, HEAD_START], [0.0, -1.0, 0.0]);
+
+    for j in 0..sides {
+        let angle = |j: usize| 2.0 * std::f32::consts::PI * (j % sides) as f32 / sides as f32;
+        let (a0, a1) = (angle(j), angle(j + 1));
+        let (c0, s0, c1, s1) = (a0.cos(), a0.sin(), a1.cos(), a1.sin());
+        let (u0, u1) = (j as f32 / sides as f32, (j + 1) as f32 / sides as f32);
+
+        // Shaft, counter-clockwise seen from the outside.
+        let a = push(&mut vertices, [SHAFT_RADIUS * c0, 0.0, SHAFT_RADIUS * s0], [u0, 0.0], [c0, 0.0, s0]);
+        let b = push(&mut vertices, [SHAFT_RADIUS * c0, HEAD_START, SHAFT_RADIUS * s0], [u0, HEAD_START], [c0, 0.0, s0]);
+        let c = push(&mut vertices, [SHAFT_RADIUS * c1, 0.0, SHAFT_RADIUS * s1], [u1, 0.0], [c1, 0.0, s1]);
+        let d = push(&mut vertices, [SHAFT_RADIUS * c1, HEAD_START, SHAFT_RADIUS * s1], [u1, HEAD_START], [c1, 0.0, s1]);
+        indices.extend_from_slice(&[a, b, c, c, b, d]);
+
+        // Base of the head, facing down.
+        let e = push(&mut vertices, [HEAD_RADIUS * c0, HEAD_START, HEAD_RADIUS * s0], [u0, HEAD_START], [0.0, -1.0, 0.0]);
+        let f = push(&mut vertices, [HEAD_RADIUS * c1, HEAD_START, HEAD_RADIUS * s1], [u1, HEAD_START], [0.0, -1.0, 0.0]);
+        indices.extend_from_slice(&[center, e, f]);
+
+        // Head, with the tip duplicated per side so each side gets its own normal.
+        let mid = (a0 + a1) / 2.0;
+        let g = push(&mut vertices, [HEAD_RADIUS * c0, HEAD_START, HEAD_RADIUS * s0], [u0, HEAD_START], [normal_radial * c0, normal_up, normal_radial * s0]);
+        let h = push(&mut vertices, [0.0, 1.0, 0.0], [(u0 + u1) / 2.0, 1.0], [normal_radial * mid.cos(), normal_up, normal_radial * mid.sin()]);
+        let i = push(&mut vertices, [HEAD_RADIUS * c1, HEAD_START, HEAD_RADIUS * s1], [u1, HEAD_START], [normal_radial * c1, normal_up, normal_radial * s1]);
+        indices.extend_from_slice(&[g, h, i]);
+    }
+
+    SurfaceData { vertices, indices }
+}
+
+
+/// Samples ∇f at the centers of a `density` by `density` grid over the domain. `f` gives the
+/// height (only used on the surface) and `gradient` the partial derivatives (f_x, f_y). Points
+/// where either is undefined are skipped.
+pub fn sample_gradient_field<F, G>(f: F, gradient: G, domain: Domain, density: usize, placement: Placement) -> Vec<Arrow>
+    where F: Fn(f32, f32) -> f32,
+          G: Fn(f32, f32) -> (f32, f32),
+{
+    // Lift the arrows a little off the surface so they aren't buried in it.
+    const SURFACE_OFFSET: f32 = 0.01;
+
+    let mut arrows = Vec::with_capacity(density * density * 2);
+    for i in 0..density {
+        for j in 0..density {
+            let (x, y) = domain.lerp((i as f32 + 0.5) / density as f32, (j as f32 + 0.5) / density as f32);
+            let (dx, dy) = gradient(x, y);
+            if !(dx.is_finite() && dy.is_finite()) {
+                continue;
+            }
+            let magnitude = (dx * dx + dy * dy).sqrt();
+
+            if placement.on_plane() {
+                arrows.push(Arrow { origin: Vector3::new(x, y, 0.0), direction: Vector3::new(dx, dy, 0.0), magnitude });
+            }
+            if placement.on_surface() {
+                let z = f(x, y);
+                if z.is_finite() {
+                    // Moving along ∇f changes the height by |∇f|^2, which keeps the arrow tangent.
+                    let direction = Vector3::new(dx, dy, magnitude * magnitude);
+                    arrows.push(Arrow { origin: Vector3::new(x, y, z + SURFACE_OFFSET), direction, magnitude });
+                }
+            }
+        }
+    }
+    arrows
+}
+
+
+/// Maps t in [0, 1] from dark purple through teal and green to yellow, so that weak and strong vectors
+/// are distinguishable even for viewers with a red-green deficiency.
+pub fn colormap(t: f32) -> Vector3 {
+    const STOPS: [[f32; 3]; 5] = [
+        [0.27, 0.00, 0.33],
+        [0.23, 0.32, 0.55],
+        [0.13, 0.57, 0.55],
+        [0.37, 0.79, 0.38],
+        [0.99, 0.91, 0.15],
+    ];
+
+    let t = if t.is_finite() { t.clamp(0.0, 1.0) } else { 0.0 };
+    let scaled = t * (STOPS.len() - 1) as f32;
+    let i = (scaled as usize).min(STOPS.len() - 2);
+    let s = scaled - i as f32;
+
+    let [r0, g0, b0] = STOPS[i];
+    let [r1, g1, b1] = STOPS[i + 1];
+    Vector3::new(r0 + s * (r1 - r0), g0 + s * (g1 - g0), b0 + s * (b1 - b0))
+}
+
+
+/// Blue for -1 through white at 0 to red for 1, for values whose sign matters. Values outside
+/// [-1, 1] are clamped.
+/// `DivergingColorMaterial` does the same on the GPU.
+pub fn diverging_colormap(t: f32) -> Vector3 {
+    let negative = Vector3::new(0.20, 0.40, 0.95);
+    let neutral  = Vector3::new(0.95, 0.95, 0.95);
+    let positive = Vector3::new(0.95, 0.25, 0.20);
+
+    let t = if t.is_finite() { t.clamp(-1.0, 1.0) } else { 0.0 };
+    if t < 0.0 {
+        neutral.lerp(negative, -t)
+    } else {
+        neutral.lerp(positive, t)
+    }
+}
+
+
+/// The instance buffer for the arrows, in world coordinates. Lengths and colors are relative to
+/// the largest magnitude, which is drawn `max_length` long.
+pub fn create_instances(arrows: &[Arrow], max_length: f32) -> Vec<f32> {
+    let largest = arrows.iter().map(|arrow| arrow.magnitude).fold(0.0, f32::max);
+    create_instances_with_largest(arrows, max_length, largest)
+}
+
+/// Like `create_instances`, but relative to a given largest magnitude, so the colors can match
+/// other parts of the plot. Longer arrows are clamped to `max_length`.
+pub fn create_instances_with_largest(arrows: &[Arrow], max_length: f32, largest: f32) -> Vec<f32> {
+    let mut data = Vec::with_capacity(arrows.len() * 9);
+    for arrow in arrows {
+        let t = if largest > 0.0 { (arrow.magnitude / largest).min(1.0) } else { 0.0 };
+        let length = arrow.direction.norm();
+        let vector = if length > 0.0 { arrow.direction * (max_length * t / length) } else { Vector3::ZERO };
+        let color  = colormap(t);
+
+        data.extend_from_slice(&math_to_world(arrow.origin.x, arrow.origin.y, arrow.origin.z));
+        data.extend_from_slice(&math_to_world(vector.x, vector.y, vector.z));
+        data.extend_from_slice(&[color.x, color.y, color.z]);
+    }
+    data
+}
+
+
+/// A vector field F(x, y, z) in math coordinates. The function can be anything, e.g. compiled
+/// expressions or a callback into JS; where it's undefined it should return non-finite values.
+pub struct VectorField {
+    function: Box<dyn Fn(Vector3) -> Vector3>,
+}
+
+impl VectorField {
+    pub fn new<F>(function: F) -> Self
+        where F: Fn(Vector3) -> Vector3 + 'static
+    {
+        Self { function: Box::new(function) }
+    }
+
+    pub fn evaluate(&self, p: Vector3) -> Vector3 {
+        (self.function)(p)
+    }
+
+    /// F at the centers of a `density`^3 lattice over the box, for drawing as arrows. Points where
+    /// F is undefined are skipped.
+    pub fn sample_lattice(&self, bounds: BoundingBox, density: usize) -> Vec<Arrow> {
+        let mut arrows = Vec::with_capacity(density * density * density);
+        let center = |i: usize| (i as f32 + 0.5) / density as f32;
+        for k in 0..density {
+            for j in 0..density {
+                for i in 0..density {
+                    let origin = bounds.lerp(center(i), center(j), center(k));
+                    let direction = self.evaluate(origin);
+                    if direction.is_finite() {
+                        arrows.push(Arrow { origin, direction, magnitude: direction.norm() });
+                    }
+                }
+            }
+        }
+        arrows
+    }
+
+    /// One classical Runge-Kutta step of size `h` along dp/ds = F(p).
+    pub fn rk4_step(&self, p: Vector3, h: f32) -> Vector3 {
+        let k1 = self.evaluate(p);
+        let k2 = self.evaluate(p + k1 * (h / 2.0));
+        let k3 = self.evaluate(p + k2 * (h / 2.0));
+        let k4 = self.evaluate(p + k3 * h);
+        p + (k1 + k2 * 2.0 + k3 * 2.0 + k4) * (h / 6.0)
+    }
+
+    /// The streamline through `seed`, traced forwards and backwards with RK4 steps of size
+    /// `step` (in parameter, not length) for at most `max_steps` each way. A direction stops where
+    /// it leaves the box, reaches a point where F vanishes or is undefined.
+    pub fn streamline(&self, seed: Vector3, bounds: BoundingBox, step: f32, max_steps: usize) -> Vec<Vector3> {
+        let mut backward = self.trace(seed, bounds, -step, max_steps);
+        let forward = self.trace(seed, bounds, step, max_steps);
+
+        backward.reverse();
+        backward.pop();  // The seed is the start of both.
+        backward.extend(forward);
+        backward
+    }
+
+    fn trace(&self, seed: Vector3, bounds: BoundingBox, h: f32, max_steps: usize) -> Vec<Vector3> {
+        let inside = |p: &Vector3| (0..3).all(|i| p[i] >= bounds.min[i] && p[i] <= bounds.max[i]);
+
+        let mut points = vec![seed];
+        let mut p = seed;
+        for _ in 0..max_steps {
+            let velocity = self.evaluate(p);
+            if !velocity.is_finite() || velocity.norm() < 1e-6 {
+                break;  // Undefined, or at a stagnation point where the line stops anyway.
+            }
+            let next = self.rk4_step(p, h);
+            if !next.is_finite() || !inside(&next) {
+                break;
+            }
+            points.push(next);
+            p = next;
+        }
+        points
+    }
+
+    /// World positions and colors (6 floats per vertex) of the streamlines, to be drawn with
+    /// `GL::LINES`. Colors map |F| relative to `largest`, like the arrows of `create_instances`.
+    pub fn create_streamline_lines(&self, streamlines: &[Vec<Vector3>], largest: f32) -> Vec<f32> {
+        let mut vertices = Vec::new();
+        for line in streamlines {
+            let colors: Vec<Vector3> = line.iter()
+                .map(|&p| colormap(if largest > 0.0 { self.evaluate(p).norm() / largest } else { 0.0 }))
+                .collect();
+            for i in 1..line.len() {
+                for j in [i - 1, i] {
+                    vertices.extend_from_slice(&math_to_world(line[j].x, line[j].y, line[j].z));
+                    vertices.extend_from_slice(&[colors[j].x, colors[j].y, colors[j].z]);
+                }
+            }
+        }
+        vertices
+    }
+}
+
+
+#[cfg(test)]
+#[path = "tests/fields.rs"]
+mod tests;
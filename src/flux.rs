@@ -0,0 +1,136 @@
+use crate::math::Vector3;
+
+use crate::fields::diverging_colormap;
+use crate::integration::{double_integral, Region, Rule};
+use crate::utils::{math_to_world, SurfaceData};
+
+
+/// A parametric surface r(u, v) with the normal N = r_u × r_v (not normalized), or its opposite,
+/// which orients the surface.
+pub struct OrientedSurface {
+    position: Box<dyn Fn(f32, f32) -> Vector3>,
+    normal: Box<dyn Fn(f32, f32) -> Vector3>,
+}
+
+impl OrientedSurface {
+    pub fn new<R, N>(position: R, normal: N) -> Self
+        where R: Fn(f32, f32) -> Vector3 + 'static,
+              N: Fn(f32, f32) -> Vector3 + 'static
+    {
+        Self { position: Box::new(position), normal: Box::new(normal) }
+    }
+
+    pub fn position(&self, u: f32, v: f32) -> Vector3 {
+        (self.position)(u, v)
+    }
+
+    /// N(u, v), whose length is the area scaling dS = |N| du dv.
+    pub fn normal(&self, u: f32, v: f32) -> Vector3 {
+        (self.normal)(u, v)
+    }
+}
+
+
+/// ∬ F·n dS = ∬ F(r(u, v))·N(u, v) du dv over the parameter rectangle, with the rule on `n`
+/// subintervals along each parameter.
+pub fn flux<F>(surface: &OrientedSurface, field: F, u_range: (f64, f64), v_range: (f64, f64), n: usize, rule: Rule) -> f64
+    where F: Fn(Vector3) -> Vector3
+{
+    let region = Region::Rectangle { x: u_range, y: v_range };
+    double_integral(|u, v| {
+        let (u, v) = (u as f32, v as f32);
+        (field(surface.position(u, v))).dot(&surface.normal(u, v)) as f64
+    }, &region, n, rule)
+}
+
+
+/// The surface and the field at a parameter value.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FluxSample {
+    pub point: Vector3,
+    /// The unit normal n.
+    pub normal: Vector3,
+    /// F·n, the flux per unit of area.
+    pub density: f32,
+}
+
+impl FluxSample {
+    /// Returns `None` where the surface or field is undefined, or the surface is degenerate
+    /// (N = 0, e.g. at the poles of a sphere).
+    pub fn at<F>(surface: &OrientedSurface, field: &F, u: f32, v: f32) -> Option<Self>
+        where F: Fn(Vector3) -> Vector3
+    {
+        let point  = surface.position(u, v);
+        let normal = surface.normal(u, v);
+        let length = normal.norm();
+        if !point.is_finite() || !length.is_finite() || length == 0.0 {
+            return None;
+        }
+
+        let normal  = normal / length;
+        let density = field(point).dot(&normal);
+        if density.is_finite() { Some(Self { point, normal, density }) } else { None }
+    }
+}
+
+/// The samples at the centers of a `count` by `count` grid over the parameter rectangle.
+pub fn sample_flux<F>(surface: &OrientedSurface, field: F, u_range: (f32, f32), v_range: (f32, f32), count: usize) -> Vec<FluxSample>
+    where F: Fn(Vector3) -> Vector3
+{
+    let mut samples = Vec::with_capacity(count * count);
+    for i in 0..count {
+        for j in 0..count {
+            let u = u_range.0 + (u_range.1 - u_range.0) * (i as f32 + 0.5) / count as f32;
+            let v = v_range.0 + (v_range.1 - v_range.0) * (j as f32 + 0.5) / count as f32;
+            samples.extend(FluxSample::at(surface, &field, u, v));
+        }
+    }
+    samples
+}
+
+
+/// Stores F·n at each vertex of a surface built by `mesh::ParametricSurface` in the first
+/// texture coordinate, mapped from [-largest, largest] to [0, 1], for `DivergingColorMaterial`.
+/// Vertices where it's undefined are neutral.
+pub fn apply_flux_colors<F>(data: &mut SurfaceData, surface: &OrientedSurface, field: F, u_range: (f32, f32), v_range: (f32, f32))
+    where F: Fn(Vector3) -> Vector3
+{
+    const TEXTURE_OFFSET: usize = 3;
+
+    // The builder lays the texture coordinates out as (u, v) scaled to [0, 1].
+    let densities: Vec<Option<f32>> = data.vertices.chunks(SurfaceData::COMPONENTS_PER_VERTEX).map(|vertex| {
+        let u = u_range.0 + vertex[TEXTURE_OFFSET] * (u_range.1 - u_range.0);
+        let v = v_range.0 + vertex[TEXTURE_OFFSET + 1] * (v_range.1 - v_range.0);
+        FluxSample::at(surface, &field, u, v).map(|sample| sample.density)
+    }).collect();
+    let largest = densities.iter().flatten().map(|density| density.abs()).fold(0.0, f32::max);
+
+    for (vertex, density) in data.vertices.chunks_mut(SurfaceData::COMPONENTS_PER_VERTEX).zip(densities) {
+        let t = match density {
+            Some(density) if largest > 0.0 => density / largest,
+            _ => 0.0,
+        };
+        vertex[TEXTURE_OFFSET] = 0.5 + 0.5 * t;
+    }
+}
+
+/// Arrows along the unit normal with the given length, colored by F·n relative to the largest
+/// |F·n|, in the instance layout of `fields::create_instances`.
+pub fn create_normal_instances(samples: &[FluxSample], length: f32) -> Vec<f32> {
+    let largest = samples.iter().map(|sample| sample.density.abs()).fold(0.0, f32::max);
+
+    let mut data = Vec::with_capacity(samples.len() * 9);
+    for sample in samples {
+        let vector = sample.normal * length;
+        let color  = diverging_colormap(if largest > 0.0 { sample.density / largest } else { 0.0 });
+        data.extend_from_slice(&math_to_world(sample.point.x, sample.point.y, sample.point.z));
+        data.extend_from_slice(&math_to_world(vector.x, vector.y, vector.z));
+        data.extend_from_slice(&[color.x, color.y, color.z]);
+    }
+    data
+}
+
+
+#[cfg(test)]
+#[path = "tests/flux.rs"]
+mod tests;
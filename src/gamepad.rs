@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+
+use crate::camera::FPSCamera;
+use crate::errors::ClientError;
+
+
+/// The axes and buttons of a gamepad at one moment, laid out like the browser's "standard"
+/// mapping: the left stick on axes 0 and 1, the right stick on 2 and 3, with y pointing down.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct GamepadSnapshot {
+    pub axes: Vec<f32>,
+    /// From 0 when released to 1 when fully pressed. The triggers are analog.
+    pub buttons: Vec<f32>,
+}
+
+impl GamepadSnapshot {
+    pub const LEFT_STICK: (usize, usize)  = (0, 1);
+    pub const RIGHT_STICK: (usize, usize) = (2, 3);
+    pub const LEFT_BUMPER: usize   = 4;
+    pub const RIGHT_BUMPER: usize  = 5;
+    pub const LEFT_TRIGGER: usize  = 6;
+    pub const RIGHT_TRIGGER: usize = 7;
+
+    /// Missing axes are centered, e.g. on a gamepad without a right stick.
+    pub fn axis(&self, index: usize) -> f32 {
+        self.axes.get(index).copied().filter(|value| value.is_finite()).unwrap_or(0.0)
+    }
+
+    pub fn button(&self, index: usize) -> f32 {
+        self.buttons.get(index).copied().filter(|value| value.is_finite()).unwrap_or(0.0)
+    }
+
+    pub fn stick(&self, (x, y): (usize, usize)) -> (f32, f32) {
+        (self.axis(x), self.axis(y))
+    }
+}
+
+
+/// How stick deflection turns into motion.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GamepadSettings {
+    /// Deflections shorter than this are ignored, since sticks rarely rest exactly at the center.
+    pub dead_zone: f32,
+    /// The rest of the deflection is raised to this power, so values above 1 give finer control
+    /// near the center.
+    pub exponent: f32,
+    /// Units per second at full deflection.
+    pub move_speed: f32,
+    /// Radians per second at full deflection.
+    pub look_speed: f32,
+    /// Pushing the right stick up looks down.
+    pub invert_y: bool,
+}
+
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        Self { dead_zone: 0.15, exponent: 2.0, move_speed: 1.0, look_speed: std::f32::consts::FRAC_PI_2, invert_y: false }
+    }
+}
+
+impl GamepadSettings {
+    pub fn new(dead_zone: f32, exponent: f32, move_speed: f32, look_speed: f32, invert_y: bool) -> Result<Self, ClientError> {
+        if !(0.0..1.0).contains(&dead_zone) {
+            return Err(ClientError::invalid_argument("dead_zone", format!("Expected a value in [0, 1), got {}", dead_zone)));
+        }
+        if !(exponent.is_finite() && exponent > 0.0) {
+            return Err(ClientError::invalid_argument("exponent", format!("Expected a positive exponent, got {}", exponent)));
+        }
+        for (field, speed) in [("move_speed", move_speed), ("look_speed", look_speed)] {
+            if !(speed.is_finite() && speed >= 0.0) {
+                return Err(ClientError::invalid_argument(field, format!("Expected a non-negative speed, got {}", speed)));
+            }
+        }
+        Ok(Self { dead_zone, exponent, move_speed, look_speed, invert_y })
+    }
+
+    /// The stick deflection with the dead zone cut out and the rest rescaled to [0, 1] and curved,
+    /// keeping its direction. The dead zone is radial, so diagonals aren't snapped to the axes.
+    pub fn shape_stick(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let length = (x * x + y * y).sqrt();
+        if length <= self.dead_zone {
+            return (0.0, 0.0);
+        }
+        let magnitude = ((length - self.dead_zone) / (1.0 - self.dead_zone)).min(1.0).powf(self.exponent);
+        (x / length * magnitude, y / length * magnitude)
+    }
+
+    /// A trigger or button value with the dead zone cut out, for worn triggers that don't fully
+    /// release.
+    pub fn shape_trigger(&self, value: f32) -> f32 {
+        let value = value.clamp(0.0, 1.0);
+        if value <= self.dead_zone { 0.0 } else { (value - self.dead_zone) / (1.0 - self.dead_zone) }
+    }
+}
+
+
+/// What a gamepad does to the `FPSCamera` over a frame: the left stick walks, the right stick
+/// looks around, the triggers move down and up and the bumpers roll.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct CameraMotion {
+    pub right: f32,
+    pub up: f32,
+    pub forward: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub roll: f32,
+}
+
+impl CameraMotion {
+    /// The motion over `dt` milliseconds.
+    pub fn from_snapshot(snapshot: &GamepadSnapshot, settings: &GamepadSettings, dt: f32) -> Self {
+        let seconds = dt / 1000.0;
+        let (walk_x, walk_y) = settings.shape_stick(snapshot.stick(GamepadSnapshot::LEFT_STICK));
+        let (look_x, look_y) = settings.shape_stick(snapshot.stick(GamepadSnapshot::RIGHT_STICK));
+        let pitch_sign = if settings.invert_y { -1.0 } else { 1.0 };
+
+        let trigger = |index| settings.shape_trigger(snapshot.button(index));
+        let bumper  = |index| if snapshot.button(index) > 0.5 { 1.0 } else { 0.0 };
+
+        let move_distance = settings.move_speed * seconds;
+        let look_angle    = settings.look_speed * seconds;
+        Self {
+            right:   walk_x * move_distance,
+            up:      (trigger(GamepadSnapshot::RIGHT_TRIGGER) - trigger(GamepadSnapshot::LEFT_TRIGGER)) * move_distance,
+            forward: -walk_y * move_distance,
+            // Positive yaw turns left, and the stick's y points down, like positive pitch.
+            yaw:     -look_x * look_angle,
+            pitch:   pitch_sign * look_y * look_angle,
+            roll:    (bumper(GamepadSnapshot::RIGHT_BUMPER) - bumper(GamepadSnapshot::LEFT_BUMPER)) * look_angle,
+        }
+    }
+
+    pub fn is_still(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn apply(&self, camera: &mut FPSCamera) {
+        if self.is_still() {
+            return;
+        }
+        camera.move_right(self.right);
+        camera.move_up(self.up);
+        camera.move_forward(self.forward);
+        camera.rotate(self.yaw, self.pitch, self.roll);
+    }
+}
+
+
+#[cfg(test)]
+#[path = "tests/gamepad.rs"]
+mod tests;
@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+
+/// A finger or pen on the canvas. Touch identifiers and pointer ids are counted separately, so
+/// they can't collide.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ContactId {
+    Touch(i32),
+    Pointer(i32),
+}
+
+
+/// The gestures since the last frame, in pixels with y up like the mouse deltas.
+///
+/// One finger dragging gives `rotate`, two or more moving together give `pan`, and spreading them
+/// apart gives a `zoom` above 1 (the ratio of their spread). A trackpad pinch gives `zoom` too.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GestureDelta {
+    pub rotate_x: f32,
+    pub rotate_y: f32,
+    pub pan_x: f32,
+    pub pan_y: f32,
+    pub zoom: f32,
+}
+
+impl Default for GestureDelta {
+    fn default() -> Self {
+        Self { rotate_x: 0.0, rotate_y: 0.0, pan_x: 0.0, pan_y: 0.0, zoom: 1.0 }
+    }
+}
+
+impl GestureDelta {
+    pub fn is_none(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+
+/// Turns the contacts on the canvas into gestures. Adding or lifting a finger only changes which
+/// gesture the following moves make, so the view doesn't jump.
+#[derive(Debug, Clone, Default)]
+pub struct Gestures {
+    // Positions from the upper left corner, like the offsets of the events.
+    contacts: BTreeMap<ContactId, (f32, f32)>,
+    delta: GestureDelta,
+}
+
+impl Gestures {
+    /// Fingers closer than this (in pixels) don't pinch, since the ratio of their spread is noise.
+    pub const MIN_PINCH_SPREAD: f32 = 10.0;
+
+    pub fn contact_count(&self) -> usize {
+        self.contacts.len()
+    }
+
+    /// Whether a pen is on the canvas. Only pens come as `ContactId::Pointer`.
+    pub fn has_pen(&self) -> bool {
+        self.contacts.keys().any(|id| matches!(id, ContactId::Pointer(_)))
+    }
+
+    pub fn start(&mut self, id: ContactId, x: f32, y: f32) {
+        self.contacts.insert(id, (x, y));
+    }
+
+    pub fn end(&mut self, id: ContactId) {
+        self.contacts.remove(&id);
+    }
+
+    /// E.g. when the browser cancels the touches to scroll, or the canvas loses focus.
+    pub fn cancel_all(&mut self) {
+        self.contacts.clear();
+    }
+
+    pub fn moved(&mut self, id: ContactId, x: f32, y: f32) {
+        let before = match self.contacts.get(&id) {
+            Some(position) => *position,
+            None => return,
+        };
+        let (center_before, spread_before) = self.center_and_spread();
+        self.contacts.insert(id, (x, y));
+
+        if self.contacts.len() == 1 {
+            self.delta.rotate_x += x - before.0;
+            self.delta.rotate_y -= y - before.1;
+            return;
+        }
+
+        let (center, spread) = self.center_and_spread();
+        self.delta.pan_x += center.0 - center_before.0;
+        self.delta.pan_y -= center.1 - center_before.1;
+        if spread_before >= Self::MIN_PINCH_SPREAD && spread >= Self::MIN_PINCH_SPREAD {
+            self.delta.zoom *= spread / spread_before;
+        }
+    }
+
+    /// A pinch on a trackpad, which browsers send as wheel events with the control key held.
+    /// Positive deltas pinch in.
+    pub fn trackpad_pinch(&mut self, wheel_delta: f32) {
+        self.delta.zoom *= (-wheel_delta / 100.0).exp();
+    }
+
+    /// The gestures since the last call.
+    pub fn take_delta(&mut self) -> GestureDelta {
+        std::mem::take(&mut self.delta)
+    }
+
+    // The centroid and the mean distance to it.
+    fn center_and_spread(&self) -> ((f32, f32), f32) {
+        let count = self.contacts.len() as f32;
+        let (sum_x, sum_y) = self.contacts.values().fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+        let center = (sum_x / count, sum_y / count);
+        let spread = self.contacts.values()
+            .map(|(x, y)| ((x - center.0).powi(2) + (y - center.1).powi(2)).sqrt())
+            .sum::<f32>() / count;
+        (center, spread)
+    }
+}
+
+
+#[cfg(test)]
+#[path = "tests/gestures.rs"]
+mod tests;
@@ -3,6 +3,7 @@ use wasm_bindgen::JsValue;
 use web_sys::*;
 use web_sys::WebGl2RenderingContext as GL;
 use serde::{Serialize, Deserialize};
+use crate::utils::to_js_value;
 
 
 #[derive(Serialize, Deserialize)]
@@ -11,16 +12,15 @@ struct ContextOptions {
 }
 
 
-pub fn initialize_webgl_context() -> Result<(WebGl2RenderingContext, web_sys::HtmlCanvasElement), JsValue> {
+pub fn initialize_webgl_context(canvas_id: &str) -> Result<(WebGl2RenderingContext, web_sys::HtmlCanvasElement), JsValue> {
     let document = web_sys::window().unwrap().document().unwrap();
-    let canvas = document.get_element_by_id("canvas").unwrap();
+    let canvas = document.get_element_by_id(canvas_id)
+        .ok_or_else(|| JsValue::from(format!("No element with the id {}", canvas_id)))?;
     let canvas: web_sys::HtmlCanvasElement = canvas.dyn_into::<web_sys::HtmlCanvasElement>()?;
 
     let gl: WebGl2RenderingContext = canvas
         .get_context_with_context_options(
-            "webgl2", &JsValue::from_serde(
-                &ContextOptions { antialias: true }
-            ).unwrap())?
+            "webgl2", &to_js_value(&ContextOptions { antialias: true }))?
         .unwrap()
         .dyn_into()?;
 
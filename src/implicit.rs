@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use crate::math::Vector3;
+
+use crate::utils::{SurfaceData, math_to_world};
+
+
+/// An axis aligned box in math coordinates.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox {
+    pub min: Vector3,
+    pub max: Vector3,
+}
+
+impl BoundingBox {
+    pub fn new(min: Vector3, max: Vector3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn size(&self) -> Vector3 {
+        self.max - self.min
+    }
+
+    /// Maps (s, t, u) in [0, 1]^3 to a point in the box.
+    pub fn lerp(&self, s: f32, t: f32, u: f32) -> Vector3 {
+        self.min + self.size().component_mul(Vector3::new(s, t, u))
+    }
+}
+
+
+/// The gradient of `f` by central differences, for when no exact gradient is at hand.
+pub fn numerical_gradient<F>(f: &F, p: Vector3, h: f32) -> Vector3
+    where F: Fn(Vector3) -> f32
+{
+    let dx = Vector3::new(h, 0.0, 0.0);
+    let dy = Vector3::new(0.0, h, 0.0);
+    let dz = Vector3::new(0.0, 0.0, h);
+    Vector3::new(f(p + dx) - f(p - dx), f(p + dy) - f(p - dy), f(p + dz) - f(p - dz)) / (2.0 * h)
+}
+
+
+/// The six tetrahedra of a cube, as corner indices where bit 0, 1 and 2 are the x, y and z
+/// offsets. All share the diagonal from corner 0 to 7, and each walks to it along the axes in a
+/// different order. Every face is then split along the diagonal through its lowest corner, so
+/// neighbouring cubes agree and the surface has no cracks.
+const TETRAHEDRA: [[usize; 4]; 6] = [
+    [0, 1, 3, 7],
+    [0, 1, 5, 7],
+    [0, 2, 3, 7],
+    [0, 2, 6, 7],
+    [0, 4, 5, 7],
+    [0, 4, 6, 7],
+];
+
+
+/// Extracts the surface F(x, y, z) = 0 inside the box, sampled on a grid of `resolution`^3 cubes
+/// (in math coordinates, laid out like `create_surface`).
+///
+/// This is marching tetrahedra: each cube of the marching cubes grid is split into six
+/// tetrahedra, where the surface is one triangle or a quad. Unlike plain marching cubes it has no
+/// ambiguous cases and needs no 256 entry table, at the price of about twice the triangles.
+/// Vertices on a grid edge are shared between the triangles around it, and their normals are the
+/// normalized `gradient` of F, so they point towards increasing F. Regions where F is undefined
+/// are left open.
+pub fn extract_isosurface<F, G>(f: F, gradient: G, bounds: BoundingBox, resolution: usize) -> SurfaceData
+    where F: Fn(Vector3) -> f32,
+          G: Fn(Vector3) -> Vector3,
+{
+    assert!(resolution >= 1, "An isosurface needs at least one cube.");
+
+    let n = resolution + 1;
+    let index = |i: usize, j: usize, k: usize| (k * n + j) * n + i;
+    let point = |i: usize, j: usize, k: usize| {
+        bounds.lerp(i as f32 / resolution as f32, j as f32 / resolution as f32, k as f32 / resolution as f32)
+    };
+
+    let mut values = Vec::with_capacity(n * n * n);
+    for k in 0..n {
+        for j in 0..n {
+            for i in 0..n {
+                values.push(f(point(i, j, k)));
+            }
+        }
+    }
+
+    // Step for the numerical fallback, where the exact gradient is undefined or zero.
+    let h = 1e-3 * bounds.size().max_component();
+
+    let mut vertices = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+    let mut edges: HashMap<(usize, usize), u32> = HashMap::new();
+
+    // The vertex where the surface crosses the grid edge between samples a and b.
+    let mut vertex = |a: (usize, Vector3), b: (usize, Vector3), vertices: &mut Vec<f32>| -> (u32, Vector3, Vector3) {
+        let key = (a.0.min(b.0), a.0.max(b.0));
+        let (va, vb) = (values[a.0], values[b.0]);
+        let t = if va == vb { 0.5 } else { va / (va - vb) };
+        let position = a.1 + (b.1 - a.1) * t;
+
+        let normal = {
+            let exact = gradient(position);
+            let gradient = if exact.is_finite() && exact.norm() > 0.0 { exact } else { numerical_gradient(&f, position, h) };
+            if gradient.norm() > 0.0 { gradient.normalize() } else { Vector3::new(0.0, 0.0, 1.0) }
+        };
+
+        let id = *edges.entry(key).or_insert_with(|| {
+            let id = (vertices.len() / SurfaceData::COMPONENTS_PER_VERTEX) as u32;
+            let uv = (position - bounds.min).component_div(bounds.size());
+            vertices.extend_from_slice(&math_to_world(position.x, position.y, position.z));
+            vertices.extend_from_slice(&[uv.x, uv.y]);
+            vertices.extend_from_slice(&math_to_world(normal.x, normal.y, normal.z));
+            id
+        });
+        (id, position, normal)
+    };
+
+    for k in 0..resolution {
+        for j in 0..resolution {
+            for i in 0..resolution {
+                let corners: [(usize, Vector3); 8] = [0, 1, 2, 3, 4, 5, 6, 7].map(|c| {
+                    let (ci, cj, ck) = (i + (c & 1), j + ((c >> 1) & 1), k + ((c >> 2) & 1));
+                    (index(ci, cj, ck), point(ci, cj, ck))
+                });
+                if corners.iter().any(|corner| !values[corner.0].is_finite()) {
+                    continue;
+                }
+
+                for tetrahedron in TETRAHEDRA.iter() {
+                    let corners = tetrahedron.map(|c| corners[c]);
+                    let (inside, outside): (Vec<_>, Vec<_>) = corners.iter().partition(|corner| values[corner.0] < 0.0);
+
+                    let polygon = match (inside.len(), outside.len()) {
+                        (1, 3) => outside.iter().map(|&&o| vertex(*inside[0], o, &mut vertices)).collect::<Vec<_>>(),
+                        (3, 1) => inside.iter().map(|&&i| vertex(i, *outside[0], &mut vertices)).collect::<Vec<_>>(),
+                        // The crossings on the four edges between the pairs form a quad, in order around it.
+                        (2, 2) => vec![
+                            vertex(*inside[0], *outside[0], &mut vertices),
+                            vertex(*inside[0], *outside[1], &mut vertices),
+                            vertex(*inside[1], *outside[1], &mut vertices),
+                            vertex(*inside[1], *outside[0], &mut vertices),
+                        ],
+                        _ => continue,
+                    };
+
+                    for triangle in [[0, 1, 2], [0, 2, 3]].iter().take(polygon.len() - 2) {
+                        let [a, b, c] = triangle.map(|t| polygon[t]);
+                        let face = (b.1 - a.1).cross(&(c.1 - a.1));
+                        if face.norm() <= f32::EPSILON * bounds.size().norm_squared() {
+                            continue;  // The surface passes through a corner, which gives slivers.
+                        }
+
+                        // Wind counter-clockwise around the direction F increases in, like the normals.
+                        if face.dot(&(a.2 + b.2 + c.2)) >= 0.0 {
+                            indices.extend_from_slice(&[a.0, b.0, c.0]);
+                        } else {
+                            indices.extend_from_slice(&[a.0, c.0, b.0]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    SurfaceData { vertices, indices }
+}
+
+
+#[cfg(test)]
+#[path = "tests/implicit.rs"]
+mod tests;
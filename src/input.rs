@@ -0,0 +1,226 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ClientError;
+
+
+/// What the keys do. Keys are bound to actions by their `KeyboardEvent.code`, so the bindings
+/// are the same physical keys on every layout unless rebound.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    MoveForward,
+    MoveBackwards,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    RollLeft,
+    RollRight,
+    ToggleMouseLock,
+}
+
+impl Action {
+    pub const COUNT: usize = 9;
+    pub const ALL: [Action; Action::COUNT] = [
+        Action::MoveForward,
+        Action::MoveBackwards,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::RollLeft,
+        Action::RollRight,
+        Action::ToggleMouseLock,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|action| action.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveForward     => "move_forward",
+            Action::MoveBackwards   => "move_backwards",
+            Action::MoveLeft        => "move_left",
+            Action::MoveRight       => "move_right",
+            Action::MoveUp          => "move_up",
+            Action::MoveDown        => "move_down",
+            Action::RollLeft        => "roll_left",
+            Action::RollRight       => "roll_right",
+            Action::ToggleMouseLock => "toggle_mouse_lock",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+
+    fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::MoveForward     => &["KeyW", "ArrowUp"],
+            Action::MoveBackwards   => &["KeyS", "ArrowDown"],
+            Action::MoveLeft        => &["KeyA", "ArrowLeft"],
+            Action::MoveRight       => &["KeyD", "ArrowRight"],
+            Action::MoveUp          => &["Space"],
+            Action::MoveDown        => &["ControlLeft"],
+            Action::RollLeft        => &["KeyQ"],
+            Action::RollRight       => &["KeyE"],
+            Action::ToggleMouseLock => &["Escape"],
+        }
+    }
+}
+
+
+/// The keys bound to each action. An action can have any number of keys, and a key can trigger
+/// several actions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ActionMap {
+    bindings: BTreeMap<Action, Vec<String>>,
+}
+
+impl Default for ActionMap {
+    fn default() -> Self {
+        let bindings = Action::ALL.iter().map(|action| {
+            (*action, action.default_keys().iter().map(|key| String::from(*key)).collect())
+        }).collect();
+        Self { bindings }
+    }
+}
+
+impl ActionMap {
+    pub fn keys(&self, action: Action) -> &[String] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn actions(&self, key: &str) -> impl Iterator<Item = Action> + '_ {
+        let key = String::from(key);
+        self.bindings.iter()
+            .filter(move |(_, keys)| keys.contains(&key))
+            .map(|(action, _)| *action)
+    }
+
+    pub fn is_bound(&self, key: &str, action: Action) -> bool {
+        self.keys(action).iter().any(|bound| bound == key)
+    }
+
+    /// Adds a key to the action, keeping the keys it already has.
+    pub fn bind(&mut self, action: Action, key: &str) -> Result<(), ClientError> {
+        if key.is_empty() {
+            return Err(ClientError::invalid_argument("key", String::from("Expected a key code, e.g. 'KeyW'")));
+        }
+        let keys = self.bindings.entry(action).or_default();
+        if !keys.iter().any(|bound| bound == key) {
+            keys.push(String::from(key));
+        }
+        Ok(())
+    }
+
+    pub fn unbind(&mut self, action: Action, key: &str) {
+        if let Some(keys) = self.bindings.get_mut(&action) {
+            keys.retain(|bound| bound != key);
+        }
+    }
+
+    pub fn clear(&mut self, action: Action) {
+        self.bindings.insert(action, Vec::new());
+    }
+
+    /// E.g. `{"move_forward": ["KeyW", "ArrowUp"], ...}`.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Bindings are always serializable")
+    }
+
+    /// Actions missing from the JSON keep their default keys, so saved bindings stay usable when
+    /// actions are added.
+    pub fn from_json(json: &str) -> Result<Self, ClientError> {
+        let loaded: BTreeMap<Action, Vec<String>> = serde_json::from_str(json)
+            .map_err(|error| ClientError::invalid_argument("bindings", error.to_string()))?;
+        if loaded.values().flatten().any(String::is_empty) {
+            return Err(ClientError::invalid_argument("bindings", String::from("Key codes can't be empty")));
+        }
+
+        let mut map = Self::default();
+        map.bindings.extend(loaded);
+        Ok(map)
+    }
+}
+
+
+/// Whether each action is active this frame and was in the previous one.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ActionStates {
+    current: [bool; Action::COUNT],
+    previous: [bool; Action::COUNT],
+}
+
+impl ActionStates {
+    /// The states of the next frame, with `current` as the actions active in it.
+    pub fn next(&self, current: [bool; Action::COUNT]) -> Self {
+        Self { current, previous: self.current }
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.current[action.index()]
+    }
+
+    /// Whether the action started this frame.
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.current[action.index()] && !self.previous[action.index()]
+    }
+
+    /// Whether the action stopped this frame.
+    pub fn just_released(&self, action: Action) -> bool {
+        !self.current[action.index()] && self.previous[action.index()]
+    }
+
+    /// 1 if only `positive` is pressed, -1 if only `negative` is, otherwise 0.
+    pub fn axis(&self, negative: Action, positive: Action) -> f32 {
+        self.pressed(positive) as i32 as f32 - self.pressed(negative) as i32 as f32
+    }
+}
+
+
+/// The held keys, which are turned into action states once per frame.
+#[derive(Debug, Clone, Default)]
+pub struct Keyboard {
+    pub bindings: ActionMap,
+    held: HashSet<String>,
+    // Keys pressed since the last frame, so that a tap between two frames still counts.
+    tapped: HashSet<String>,
+}
+
+impl Keyboard {
+    pub fn key_down(&mut self, key: &str) {
+        self.held.insert(String::from(key));
+        self.tapped.insert(String::from(key));
+    }
+
+    pub fn key_up(&mut self, key: &str) {
+        self.held.remove(key);
+    }
+
+    /// Releases every key, e.g. when the page loses focus and the key up events go elsewhere.
+    pub fn release_all(&mut self) {
+        self.held.clear();
+    }
+
+    /// Advances the action states to a new frame.
+    pub fn frame_states(&mut self, previous: &ActionStates) -> ActionStates {
+        let mut current = [false; Action::COUNT];
+        for key in self.held.iter().chain(self.tapped.iter()) {
+            for action in self.bindings.actions(key) {
+                current[action.index()] = true;
+            }
+        }
+        self.tapped.clear();
+        previous.next(current)
+    }
+}
+
+
+#[cfg(test)]
+#[path = "tests/input.rs"]
+mod tests;
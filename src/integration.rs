@@ -0,0 +1,250 @@
+use crate::math::Vector3;
+use serde::Serialize;
+
+use crate::fields::colormap;
+use crate::utils::math_to_world;
+
+
+/// A composite quadrature rule, applied on each of the subintervals.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Rule {
+    Midpoint,
+    Simpson,
+    /// Gauss-Legendre with this many points per subinterval.
+    GaussLegendre(usize),
+}
+
+impl Rule {
+    pub const MAX_GAUSS_POINTS: usize = 16;
+
+    /// `"midpoint"`, `"simpson"` or `"gauss"`, optionally followed by the number of points per
+    /// subinterval (3 by default), e.g. `"gauss5"`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "midpoint" => Some(Rule::Midpoint),
+            "simpson"  => Some(Rule::Simpson),
+            _ => {
+                let points = name.strip_prefix("gauss")?;
+                let points = if points.is_empty() { 3 } else { points.parse().ok()? };
+                if (1..=Self::MAX_GAUSS_POINTS).contains(&points) { Some(Rule::GaussLegendre(points)) } else { None }
+            },
+        }
+    }
+
+    /// The order p of the error, which is O(h^p) for smooth integrands.
+    pub fn order(&self) -> i32 {
+        match self {
+            Rule::Midpoint => 2,
+            Rule::Simpson  => 4,
+            Rule::GaussLegendre(points) => 2 * *points as i32,
+        }
+    }
+}
+
+
+/// The nodes and weights of the `n` point Gauss-Legendre rule on [-1, 1]. The nodes are the roots
+/// of the Legendre polynomial P_n, found by Newton's method from the usual initial guesses.
+pub fn gauss_legendre(n: usize) -> Vec<(f64, f64)> {
+    assert!(n >= 1, "Gauss-Legendre needs at least one point.");
+
+    (1..=n).map(|i| {
+        let mut x = (std::f64::consts::PI * (i as f64 - 0.25) / (n as f64 + 0.5)).cos();
+        let mut derivative = 0.0;
+        for _ in 0..100 {
+            // P_n(x) and P_(n-1)(x) by the three term recurrence.
+            let (mut p, mut previous) = (1.0, 0.0);
+            for k in 1..=n {
+                let next = ((2 * k - 1) as f64 * x * p - (k - 1) as f64 * previous) / k as f64;
+                previous = p;
+                p = next;
+            }
+            derivative = n as f64 * (x * p - previous) / (x * x - 1.0);
+            let dx = p / derivative;
+            x -= dx;
+            if dx.abs() < 1e-15 {
+                break;
+            }
+        }
+        (x, 2.0 / ((1.0 - x * x) * derivative * derivative))
+    }).collect()
+}
+
+
+/// ∫ f over [a, b] with the rule on `n` equal subintervals. Simpson's rule needs an even number
+/// of subintervals, so an odd `n` is rounded up.
+pub fn integrate<F>(f: F, a: f64, b: f64, n: usize, rule: Rule) -> f64
+    where F: Fn(f64) -> f64
+{
+    assert!(n >= 1, "Integration needs at least one subinterval.");
+
+    match rule {
+        Rule::Midpoint => {
+            let h = (b - a) / n as f64;
+            (0..n).map(|i| f(a + (i as f64 + 0.5) * h)).sum::<f64>() * h
+        },
+        Rule::Simpson => {
+            let n = n + n % 2;
+            let h = (b - a) / n as f64;
+            let inner: f64 = (1..n).map(|i| f(a + i as f64 * h) * if i % 2 == 1 { 4.0 } else { 2.0 }).sum();
+            (f(a) + inner + f(b)) * h / 3.0
+        },
+        Rule::GaussLegendre(points) => {
+            let nodes = gauss_legendre(points);
+            let h = (b - a) / n as f64;
+            (0..n).map(|i| {
+                let center = a + (i as f64 + 0.5) * h;
+                nodes.iter().map(|(x, w)| w * f(center + x * h / 2.0)).sum::<f64>() * h / 2.0
+            }).sum()
+        },
+    }
+}
+
+
+/// A region of the xy-plane to integrate over.
+pub enum Region {
+    Rectangle { x: (f64, f64), y: (f64, f64) },
+    /// Type I: a <= x <= b and g_1(x) <= y <= g_2(x).
+    TypeI { x: (f64, f64), y_lower: Box<dyn Fn(f64) -> f64>, y_upper: Box<dyn Fn(f64) -> f64> },
+    /// Type II: c <= y <= d and h_1(y) <= x <= h_2(y).
+    TypeII { y: (f64, f64), x_lower: Box<dyn Fn(f64) -> f64>, x_upper: Box<dyn Fn(f64) -> f64> },
+}
+
+impl Region {
+    /// The range of the outer variable (x, or y for type II).
+    pub fn outer(&self) -> (f64, f64) {
+        match self {
+            Region::Rectangle { x, .. } | Region::TypeI { x, .. } => *x,
+            Region::TypeII { y, .. } => *y,
+        }
+    }
+
+    /// The range of the inner variable at a value of the outer one.
+    pub fn inner(&self, outer: f64) -> (f64, f64) {
+        match self {
+            Region::Rectangle { y, .. } => *y,
+            Region::TypeI  { y_lower, y_upper, .. } => (y_lower(outer), y_upper(outer)),
+            Region::TypeII { x_lower, x_upper, .. } => (x_lower(outer), x_upper(outer)),
+        }
+    }
+
+    /// (x, y) from the outer and inner variables.
+    pub fn point(&self, outer: f64, inner: f64) -> (f64, f64) {
+        match self {
+            Region::TypeII { .. } => (inner, outer),
+            _ => (outer, inner),
+        }
+    }
+}
+
+
+/// ∬ f dA over the region as an iterated integral, with `n` subintervals in each direction.
+pub fn double_integral<F>(f: F, region: &Region, n: usize, rule: Rule) -> f64
+    where F: Fn(f64, f64) -> f64
+{
+    let (a, b) = region.outer();
+    integrate(|outer| {
+        let (c, d) = region.inner(outer);
+        integrate(|inner| { let (x, y) = region.point(outer, inner); f(x, y) }, c, d, n, rule)
+    }, a, b, n, rule)
+}
+
+/// ∭ f dV over the box [x_0, x_1] x [y_0, y_1] x [z_0, z_1], with `n` subintervals along each side.
+pub fn triple_integral<F>(f: F, x: (f64, f64), y: (f64, f64), z: (f64, f64), n: usize, rule: Rule) -> f64
+    where F: Fn(f64, f64, f64) -> f64
+{
+    integrate(|x| {
+        integrate(|y| {
+            integrate(|z| f(x, y, z), z.0, z.1, n, rule)
+        }, y.0, y.1, n, rule)
+    }, x.0, x.1, n, rule)
+}
+
+
+/// A numerical integral with an estimate of its error.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+pub struct Estimate {
+    pub value: f64,
+    pub error: f64,
+}
+
+impl Estimate {
+    /// Richardson's estimate from the results with n and 2n subintervals: if the error is O(h^p),
+    /// the error of the finer result is about |fine - coarse| / (2^p - 1). The value is the finer
+    /// result. The estimate is only meaningful where the integrand is smooth.
+    pub fn from_refinement<F>(integral: F, n: usize, rule: Rule) -> Self
+        where F: Fn(usize) -> f64
+    {
+        let coarse = integral(n);
+        let fine   = integral(2 * n);
+        Self { value: fine, error: (fine - coarse).abs() / (2f64.powi(rule.order()) - 1.0) }
+    }
+}
+
+
+/// A box of the Riemann sum under the surface: the cell of the region centered at (x, y), with
+/// the value of f at the center as the (possibly negative) height.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Prism {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub depth: f32,
+    pub height: f32,
+}
+
+/// The midpoint Riemann sum of f over the region on `n` by `n` cells, where the inner cells of
+/// each column follow the bounds at the column's center. Cells where f is undefined are skipped.
+pub fn riemann_prisms<F>(f: F, region: &Region, n: usize) -> Vec<Prism>
+    where F: Fn(f64, f64) -> f64
+{
+    let (a, b) = region.outer();
+    let h = (b - a) / n as f64;
+
+    let mut prisms = Vec::with_capacity(n * n);
+    for i in 0..n {
+        let outer = a + (i as f64 + 0.5) * h;
+        let (c, d) = region.inner(outer);
+        let k = (d - c) / n as f64;
+        for j in 0..n {
+            let inner  = c + (j as f64 + 0.5) * k;
+            let (x, y) = region.point(outer, inner);
+            let (width, depth) = match region {
+                Region::TypeII { .. } => (k, h),
+                _ => (h, k),
+            };
+            let height = f(x, y);
+            if height.is_finite() {
+                prisms.push(Prism { x: x as f32, y: y as f32, width: width.abs() as f32, depth: depth.abs() as f32, height: height as f32 });
+            }
+        }
+    }
+    prisms
+}
+
+/// Floats per prism in the instance buffer: center, size and color, all `vec3`s.
+pub const PRISM_ATTRIBUTE_SIZES: [i32; 3] = [3, 3, 3];
+pub const PRISM_LOCATION: u32 = 3;
+
+/// The instance buffer for the prisms as boxes standing on the xy-plane, in world coordinates,
+/// colored by height from the lowest to the highest.
+pub fn create_prism_instances(prisms: &[Prism]) -> Vec<f32> {
+    let lowest  = prisms.iter().map(|prism| prism.height).fold(f32::INFINITY, f32::min);
+    let highest = prisms.iter().map(|prism| prism.height).fold(f32::NEG_INFINITY, f32::max);
+
+    let mut data = Vec::with_capacity(prisms.len() * 9);
+    for prism in prisms {
+        let t = if highest > lowest { (prism.height - lowest) / (highest - lowest) } else { 0.5 };
+        let color: Vector3 = colormap(t);
+
+        // A negative value gives a box hanging below the plane.
+        data.extend_from_slice(&math_to_world(prism.x, prism.y, prism.height / 2.0));
+        data.extend_from_slice(&[prism.width, prism.height.abs(), prism.depth]);
+        data.extend_from_slice(&[color.x, color.y, color.z]);
+    }
+    data
+}
+
+
+#[cfg(test)]
+#[path = "tests/integration.rs"]
+mod tests;
@@ -0,0 +1,241 @@
+use crate::math::Vector3;
+use serde::Serialize;
+
+use crate::contours::{marching_squares, ScalarGrid};
+use crate::critical::{hessian_norm, Classification, CriticalPoint};
+use crate::utils::{math_to_world, Domain};
+
+
+/// A function of (x, y) with its gradient and Hessian, e.g. compiled from an expression and its
+/// symbolic derivatives.
+pub struct SmoothFunction {
+    value: Box<dyn Fn(f32, f32) -> f32>,
+    gradient: Box<dyn Fn(f32, f32) -> (f32, f32)>,
+    hessian: Box<dyn Fn(f32, f32) -> [[f32; 2]; 2]>,
+}
+
+impl SmoothFunction {
+    pub fn new<F, G, H>(value: F, gradient: G, hessian: H) -> Self
+        where F: Fn(f32, f32) -> f32 + 'static,
+              G: Fn(f32, f32) -> (f32, f32) + 'static,
+              H: Fn(f32, f32) -> [[f32; 2]; 2] + 'static
+    {
+        Self { value: Box::new(value), gradient: Box::new(gradient), hessian: Box::new(hessian) }
+    }
+
+    pub fn value(&self, x: f32, y: f32) -> f32 {
+        (self.value)(x, y)
+    }
+
+    pub fn gradient(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.gradient)(x, y)
+    }
+
+    pub fn hessian(&self, x: f32, y: f32) -> [[f32; 2]; 2] {
+        (self.hessian)(x, y)
+    }
+}
+
+
+/// A solution of ∇f = λ∇g, g = 0. The classification is by the bordered Hessian, so it's only
+/// ever a (local) minimum, maximum or degenerate.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+pub struct ConstrainedExtremum {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub lambda: f32,
+    pub kind: Classification,
+}
+
+impl ConstrainedExtremum {
+    pub fn to_critical_point(self) -> CriticalPoint {
+        CriticalPoint { x: self.x, y: self.y, z: self.z, kind: self.kind }
+    }
+}
+
+
+/// The second derivative test on g = 0, by the determinant of the bordered Hessian of the
+/// Lagrangian L = f - λg: positive for a maximum and negative for a minimum.
+pub fn bordered_hessian_test(f: &SmoothFunction, g: &SmoothFunction, x: f32, y: f32, lambda: f32) -> Classification {
+    let (gx, gy) = g.gradient(x, y);
+    let [[fxx, fxy], [_, fyy]] = f.hessian(x, y);
+    let [[gxx, gxy], [_, gyy]] = g.hessian(x, y);
+    let (lxx, lxy, lyy) = (fxx - lambda * gxx, fxy - lambda * gxy, fyy - lambda * gyy);
+
+    let determinant = -(gx * gx * lyy - 2.0 * gx * gy * lxy + gy * gy * lxx);
+    // Relative to the sizes of ∇g and the Hessian of L, like `Classification::from_hessian`.
+    let scale = (gx * gx + gy * gy) * hessian_norm([[lxx, lxy], [lxy, lyy]]);
+    if !determinant.is_finite() || determinant.abs() <= Classification::DEGENERATE_TOLERANCE * scale {
+        Classification::Degenerate
+    } else if determinant > 0.0 {
+        Classification::Maximum
+    } else {
+        Classification::Minimum
+    }
+}
+
+
+/// Solves a x = b by Cramer's rule, or `None` if a is singular.
+fn solve_3x3(a: [[f32; 3]; 3], b: [f32; 3]) -> Option<[f32; 3]> {
+    let determinant = |m: [[f32; 3]; 3]| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+
+    let d = determinant(a);
+    if d == 0.0 || !d.is_finite() {
+        return None;
+    }
+
+    let mut solution = [0.0; 3];
+    for (column, value) in solution.iter_mut().enumerate() {
+        let mut m = a;
+        for (row, b) in m.iter_mut().zip(b.iter()) {
+            row[column] = *b;
+        }
+        *value = determinant(m) / d;
+    }
+    Some(solution)
+}
+
+
+const MAX_ITERATIONS: usize = 50;
+/// Newton has converged when a step in (x, y) is shorter than this, relative to the domain.
+const STEP_TOLERANCE: f32 = 1e-6;
+/// The residual of the Lagrange system must be below this at a converged point. Like
+/// `critical::GRADIENT_TOLERANCE` it's relative: ∇f - λ∇g to the sizes of ∇f and λ∇g, and g to how
+/// much g changes across the domain, so steep and flat functions are treated alike.
+const RESIDUAL_TOLERANCE: f32 = 1e-4;
+
+/// Whether (x, y, λ) solves ∇f = λ∇g, g = 0 up to rounding.
+fn is_converged(f: &SmoothFunction, g: &SmoothFunction, domain: Domain, x: f32, y: f32, lambda: f32) -> bool {
+    let (fx, fy) = f.gradient(x, y);
+    let (gx, gy) = g.gradient(x, y);
+    let gradient_scale = fx.hypot(fy) + lambda.abs() * gx.hypot(gy);
+    let constraint_scale = gx.hypot(gy) * domain.width().max(domain.depth());
+    (fx - lambda * gx).hypot(fy - lambda * gy) <= RESIDUAL_TOLERANCE * gradient_scale
+        && g.value(x, y).abs() <= RESIDUAL_TOLERANCE * constraint_scale
+}
+
+/// Newton's method on (fx - λ gx, fy - λ gy, g) = 0 from (x, y), starting with the λ that best
+/// fits ∇f = λ∇g there. Returns (x, y, λ), or `None` if it doesn't converge inside the domain.
+pub fn newton(f: &SmoothFunction, g: &SmoothFunction, domain: Domain, x: f32, y: f32) -> Option<(f32, f32, f32)> {
+    let scale = domain.width().max(domain.depth());
+    let residual = |x: f32, y: f32, lambda: f32| {
+        let (fx, fy) = f.gradient(x, y);
+        let (gx, gy) = g.gradient(x, y);
+        [fx - lambda * gx, fy - lambda * gy, g.value(x, y)]
+    };
+
+    let (fx, fy) = f.gradient(x, y);
+    let (gx, gy) = g.gradient(x, y);
+    let (mut x, mut y, mut lambda) = (x, y, (fx * gx + fy * gy) / (gx * gx + gy * gy));
+    if !lambda.is_finite() {
+        return None;
+    }
+
+    for _ in 0..MAX_ITERATIONS {
+        let (gx, gy) = g.gradient(x, y);
+        let [[fxx, fxy], [_, fyy]] = f.hessian(x, y);
+        let [[gxx, gxy], [_, gyy]] = g.hessian(x, y);
+        let jacobian = [
+            [fxx - lambda * gxx, fxy - lambda * gxy, -gx],
+            [fxy - lambda * gxy, fyy - lambda * gyy, -gy],
+            [gx, gy, 0.0],
+        ];
+
+        let [dx, dy, dlambda] = solve_3x3(jacobian, residual(x, y, lambda))?;
+        x -= dx;
+        y -= dy;
+        lambda -= dlambda;
+        if !domain.contains(x, y) || !lambda.is_finite() {
+            return None;
+        }
+
+        if (dx * dx + dy * dy).sqrt() < STEP_TOLERANCE * scale {
+            return if is_converged(f, g, domain, x, y, lambda) { Some((x, y, lambda)) } else { None };
+        }
+    }
+    None
+}
+
+
+/// The curve g = 0 in the domain, as segments by marching squares on a `resolution` by
+/// `resolution` grid.
+pub fn constraint_curve(g: &SmoothFunction, domain: Domain, resolution: usize) -> Vec<[(f32, f32); 2]> {
+    let grid = ScalarGrid::sample(|x, y| g.value(x, y), domain, resolution, resolution);
+    marching_squares(&grid, 0.0)
+}
+
+/// At most this many points of the constraint curve are used as starting points.
+pub const MAX_SEEDS: usize = 256;
+
+/// The extrema of f on the curve g = 0, found by Newton's method from points along the curve.
+/// Solutions closer than a thousandth of the domain are merged.
+pub fn solve(f: &SmoothFunction, g: &SmoothFunction, domain: Domain, curve: &[[(f32, f32); 2]]) -> Vec<ConstrainedExtremum> {
+    let merge_distance = 1e-3 * domain.width().max(domain.depth());
+    let stride = (curve.len() / MAX_SEEDS).max(1);
+
+    let mut solutions: Vec<ConstrainedExtremum> = Vec::new();
+    for segment in curve.iter().step_by(stride) {
+        let (x, y) = segment[0];
+        let (x, y, lambda) = match newton(f, g, domain, x, y) {
+            Some(solution) => solution,
+            None => continue,
+        };
+
+        let z = f.value(x, y);
+        let is_new = solutions.iter().all(|solution| (solution.x - x).hypot(solution.y - y) > merge_distance);
+        if z.is_finite() && is_new {
+            solutions.push(ConstrainedExtremum { x, y, z, lambda, kind: bordered_hessian_test(f, g, x, y, lambda) });
+        }
+    }
+    solutions
+}
+
+
+pub const CONSTRAINT_COLOR: [f32; 3] = [0.95, 0.95, 0.95];
+pub const GRADIENT_F_COLOR: [f32; 3] = [0.95, 0.60, 0.20];
+pub const GRADIENT_G_COLOR: [f32; 3] = [0.75, 0.35, 0.95];
+
+/// The constraint curve lifted onto z = f(x, y) as line segments, in the vertex layout of
+/// `contours::create_contour_lines`. Segments where f is undefined are left out.
+pub fn create_constraint_lines(f: &SmoothFunction, curve: &[[(f32, f32); 2]]) -> Vec<f32> {
+    let mut vertices = Vec::with_capacity(curve.len() * 12);
+    for [(x_0, y_0), (x_1, y_1)] in curve {
+        let (z_0, z_1) = (f.value(*x_0, *y_0), f.value(*x_1, *y_1));
+        if z_0.is_finite() && z_1.is_finite() {
+            // Slightly above the surface, so the line isn't hidden by it.
+            vertices.extend_from_slice(&math_to_world(*x_0, *y_0, z_0 + 0.01));
+            vertices.extend_from_slice(&CONSTRAINT_COLOR);
+            vertices.extend_from_slice(&math_to_world(*x_1, *y_1, z_1 + 0.01));
+            vertices.extend_from_slice(&CONSTRAINT_COLOR);
+        }
+    }
+    vertices
+}
+
+/// Arrows of ∇f and ∇g at each solution, in the instance layout of `fields::create_instances`.
+/// Both are drawn horizontally on the surface with the given length, so that being parallel is
+/// easy to see.
+pub fn create_gradient_instances(f: &SmoothFunction, g: &SmoothFunction, solutions: &[ConstrainedExtremum], length: f32) -> Vec<f32> {
+    let mut data = Vec::with_capacity(solutions.len() * 18);
+    for solution in solutions {
+        let (x, y) = (solution.x, solution.y);
+        for ((dx, dy), color) in [(f.gradient(x, y), GRADIENT_F_COLOR), (g.gradient(x, y), GRADIENT_G_COLOR)] {
+            let direction = Vector3::new(dx, dy, 0.0);
+            let vector = if direction.norm() > 0.0 { direction.normalize() * length } else { Vector3::ZERO };
+            data.extend_from_slice(&math_to_world(x, y, solution.z));
+            data.extend_from_slice(&math_to_world(vector.x, vector.y, vector.z));
+            data.extend_from_slice(&color);
+        }
+    }
+    data
+}
+
+
+#[cfg(test)]
+#[path = "tests/lagrange.rs"]
+mod tests;
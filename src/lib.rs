@@ -1,14 +1,10 @@
 #![allow(unused_imports)]
 #![allow(dead_code)]
 
-#[macro_use]
-extern crate lazy_static;
-extern crate nalgebra;
 extern crate wasm_bindgen;
 extern crate console_error_panic_hook;
 extern crate web_sys;
 extern crate js_sys;
-extern crate nalgebra_glm as glm;
 
 #[macro_use]
 mod macros;
@@ -26,28 +22,57 @@ mod gl_setup;
 mod materials;
 mod renderer;
 mod textures;
-
+mod expression;
+mod errors;
+mod input;
+mod gestures;
+mod gamepad;
+mod controls;
+mod recording;
+mod plots;
+mod curves;
+mod calculus;
+mod fields;
+mod contours;
+mod implicit;
+mod integration;
+mod critical;
+mod lagrange;
+mod line_integral;
+mod flux;
+mod picking;
+mod tangent;
+
+#[cfg(test)]
+#[path = "tests/support.rs"]
+mod test_support;
+
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::Arc;
 
 use wasm_bindgen::JsValue;
 use wasm_bindgen::prelude::*;
 use web_sys::*;
 use web_sys::WebGl2RenderingContext as GL;
-use nalgebra::{Matrix4, Vector3};
+use crate::math::{Matrix4, Vector3};
 use js_sys::Math::abs;
 
 use crate::app::*;
-use crate::camera::Camera;
+use crate::camera::{Camera, CameraMode};
 use crate::mesh::{Model, Mesh, VERTICES_TEXTURE_AND_NORMAL_3D_CUBE};
 use crate::renderer::Renderer;
-use crate::utils::create_grid;
+use crate::utils::{create_grid, to_js_value, Domain};
+use crate::plots::{Plots, PlotKind, SurfacePlot, ParametricSurfacePlot, ImplicitSurfacePlot, VectorFieldPlot, FieldSource, RiemannSumPlot, LineIntegralPlot, FluxOverlay, RegionBounds, integrate_region, integrate_box, CurvePlot, CurveStyle, GradientOverlay, ConstraintOverlay, ContourOverlay, ContourLevels, placement_from_name, points_from_slice, range_from_slice};
+use crate::errors::ClientError;
+use crate::input::{Action, ActionMap};
+use crate::gamepad::GamepadSettings;
+use crate::recording::{Frame, Recording, Replay};
+use crate::implicit::BoundingBox;
+use crate::picking::Ray;
 use crate::materials::{SingleColorMaterial, DefaultMaterial, DrawConfig};
 
 
-macro_rules! console_log {
-    ($($t:tt)*) => (crate::log(&format_args!($($t)*).to_string()))
-}
-
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console)]
@@ -60,29 +85,29 @@ pub struct Client {
     gl: GL,
     canvas: web_sys::HtmlCanvasElement,
     models: Vec<Model>,
+    plots: Plots,
     camera: Camera,
+    input: Rc<RefCell<Input>>,
+    listeners: EventListeners,
+    gamepad: GamepadSettings,
+    recording: Option<Recording>,
+    replay: Option<Replay>,
 }
 
-// https://nalgebra.org/cg_recipes/
-
 
 #[wasm_bindgen]
 impl Client {
+    /// Draws on the canvas with the id `canvas_id`, or "canvas" if none is given. Each client
+    /// has its own input, so several can be on one page.
     #[wasm_bindgen(constructor)]
-    pub fn new() -> Self {
+    pub fn new(canvas_id: Option<String>) -> Self {
         console_error_panic_hook::set_once();
-        let (gl, canvas) = gl_setup::initialize_webgl_context().unwrap();
-
-        attach_mouse_down_callback(&canvas, update_mouse_down)
-            .expect("Couldn't attached mouse down callback!");
-        attach_mouse_up_callback(&canvas, update_mouse_up)
-            .expect("Couldn't attach mouse up callback!");
-        attach_mouse_move_callback(&canvas, update_mouse_move)
-            .expect("Couldn't attach mouse move callback!");
-        attach_key_up_callback(&canvas, update_key_up)
-            .expect("Couldn't attach key up callback!");
-        attach_key_down_callback(&canvas, update_key_down)
-            .expect("Couldn't attach key down callback!");
+        let canvas_id = canvas_id.unwrap_or_else(|| String::from("canvas"));
+        let (gl, canvas) = gl_setup::initialize_webgl_context(&canvas_id).unwrap();
+
+        let input = Rc::new(RefCell::new(Input::new()));
+        let listeners = attach_input_listeners(&canvas, &input)
+            .expect("Couldn't attach the input listeners!");
 
         let (vertices, indices) = create_grid(20, 20);
 
@@ -103,10 +128,263 @@ impl Client {
             gl,
             canvas,
             models,
+            plots: Plots::new(),
             camera,
+            input,
+            listeners,
+            gamepad: GamepadSettings::default(),
+            recording: None,
+            replay: None,
         }
     }
 
+    /// Stops listening to the input. Also happens when the client is freed.
+    pub fn destroy(&mut self) {
+        self.listeners.remove_all();
+    }
+
+    /// Plots the height field z = f(x, y) for x in `x_range` and y in `y_range`, sampled on a
+    /// `resolution` by `resolution` grid. Returns a handle to the plot.
+    pub fn add_surface(&mut self, expression: &str, x_range: &[f32], y_range: &[f32], resolution: u32) -> Result<u32, JsValue> {
+        let (x_min, x_max) = range_from_slice("x_range", x_range)?;
+        let (y_min, y_max) = range_from_slice("y_range", y_range)?;
+        let surface = SurfacePlot::new(expression, Domain::new(x_min, x_max, y_min, y_max), resolution as usize)?;
+
+        Ok(self.plots.add(&self.gl, PlotKind::Surface(surface))?)
+    }
+
+    /// Plots the parametric surface r(u, v) given as `x, y, z`, e.g. `cos(u) sin(v), sin(u) sin(v), cos(v)`
+    /// for a sphere. Mark a parameter as periodic if the surface wraps around along it.
+    pub fn add_parametric_surface(&mut self, expression: &str, u_range: &[f32], v_range: &[f32], resolution: u32, periodic_u: bool, periodic_v: bool) -> Result<u32, JsValue> {
+        let u_range = range_from_slice("u_range", u_range)?;
+        let v_range = range_from_slice("v_range", v_range)?;
+        let surface = ParametricSurfacePlot::new(expression, u_range, v_range, resolution as usize, (periodic_u, periodic_v))?;
+
+        Ok(self.plots.add(&self.gl, PlotKind::ParametricSurface(surface))?)
+    }
+
+    /// Plots the implicit surface given as an equation in x, y and z, e.g. `x^2 + y^2 + z^2 = 1` for
+    /// a sphere, inside the box spanned by the ranges. `resolution` is the number of cubes along
+    /// each side of the box.
+    pub fn add_implicit_surface(&mut self, expression: &str, x_range: &[f32], y_range: &[f32], z_range: &[f32], resolution: u32) -> Result<u32, JsValue> {
+        let (x_min, x_max) = range_from_slice("x_range", x_range)?;
+        let (y_min, y_max) = range_from_slice("y_range", y_range)?;
+        let (z_min, z_max) = range_from_slice("z_range", z_range)?;
+        let bounds  = BoundingBox::new(Vector3::new(x_min, y_min, z_min), Vector3::new(x_max, y_max, z_max));
+        let surface = ImplicitSurfacePlot::new(expression, bounds, resolution as usize)?;
+
+        Ok(self.plots.add(&self.gl, PlotKind::ImplicitSurface(surface))?)
+    }
+
+    /// Plots the vector field F(x, y, z) given as `P, Q, R`, e.g. `-y, x, 0` for a rotation, inside
+    /// the box spanned by the ranges. Arrows are drawn on a lattice of `density` points along each
+    /// side (0 for none), and streamlines through each of the `seeds` (a flat array of x, y, z).
+    pub fn add_vector_field(&mut self, expression: &str, x_range: &[f32], y_range: &[f32], z_range: &[f32], density: u32, seeds: &[f32]) -> Result<u32, JsValue> {
+        let field = FieldSource::expressions(expression)?;
+        self.add_vector_field_from(field, x_range, y_range, z_range, density, seeds)
+    }
+
+    /// Like `add_vector_field`, with the field given by a JS function `(x, y, z) => [P, Q, R]`.
+    /// Points where it throws or returns anything else are treated as undefined.
+    pub fn add_vector_field_callback(&mut self, callback: js_sys::Function, x_range: &[f32], y_range: &[f32], z_range: &[f32], density: u32, seeds: &[f32]) -> Result<u32, JsValue> {
+        self.add_vector_field_from(FieldSource::Callback(callback), x_range, y_range, z_range, density, seeds)
+    }
+
+    fn add_vector_field_from(&mut self, field: FieldSource, x_range: &[f32], y_range: &[f32], z_range: &[f32], density: u32, seeds: &[f32]) -> Result<u32, JsValue> {
+        let (x_min, x_max) = range_from_slice("x_range", x_range)?;
+        let (y_min, y_max) = range_from_slice("y_range", y_range)?;
+        let (z_min, z_max) = range_from_slice("z_range", z_range)?;
+        let bounds = BoundingBox::new(Vector3::new(x_min, y_min, z_min), Vector3::new(x_max, y_max, z_max));
+        let plot   = VectorFieldPlot::new(field, bounds, density as usize, points_from_slice("seeds", seeds)?)?;
+
+        Ok(self.plots.add(&self.gl, PlotKind::VectorField(plot))?)
+    }
+
+    /// Returns `{ value, error }` for the double integral of f(x, y) over a region. `region` is
+    /// 'rectangle', 'type_i' (x in `outer_range` and `inner_bounds` like '0, x^2' bounding y) or
+    /// 'type_ii' (y in `outer_range` and `inner_bounds` as functions of y bounding x); for a
+    /// rectangle the bounds are constants. `rule` is 'midpoint', 'simpson' or 'gauss' (optionally followed
+    /// by the number of points, e.g. 'gauss5'), applied on `subdivisions` subintervals in each
+    /// direction. The error is estimated by comparing with twice the subdivisions.
+    pub fn integrate(&self, expression: &str, region: &str, outer_range: &[f32], inner_bounds: &str, rule: &str, subdivisions: u32) -> Result<JsValue, JsValue> {
+        let bounds = RegionBounds::new(region, outer_range, inner_bounds)?;
+        Ok(to_js_value(&integrate_region(expression, &bounds, rule, subdivisions as usize)?))
+    }
+
+    /// Returns `{ value, error }` for the triple integral of f(x, y, z) over the box spanned by the
+    /// ranges, like `integrate`.
+    pub fn integrate_box(&self, expression: &str, x_range: &[f32], y_range: &[f32], z_range: &[f32], rule: &str, subdivisions: u32) -> Result<JsValue, JsValue> {
+        let (x_min, x_max) = range_from_slice("x_range", x_range)?;
+        let (y_min, y_max) = range_from_slice("y_range", y_range)?;
+        let (z_min, z_max) = range_from_slice("z_range", z_range)?;
+        let bounds = BoundingBox::new(Vector3::new(x_min, y_min, z_min), Vector3::new(x_max, y_max, z_max));
+        Ok(to_js_value(&integrate_box(expression, bounds, rule, subdivisions as usize)?))
+    }
+
+    /// Draws the midpoint Riemann sum of f(x, y) over a region (given as for `integrate`) as boxes
+    /// between the xy-plane and the surface, `subdivisions` along each direction.
+    pub fn add_riemann_sum(&mut self, expression: &str, region: &str, outer_range: &[f32], inner_bounds: &str, subdivisions: u32) -> Result<u32, JsValue> {
+        let bounds = RegionBounds::new(region, outer_range, inner_bounds)?;
+        let sum    = RiemannSumPlot::new(expression, bounds, subdivisions as usize)?;
+
+        Ok(self.plots.add(&self.gl, PlotKind::RiemannSum(sum))?)
+    }
+
+    /// Returns `{ value, error }` for the line integral ∫ F·dr of the vector field F(x, y, z) given
+    /// as `P, Q, R` along the curve r(t) given as `x, y, z`, with `rule` on `subdivisions`
+    /// subintervals of `t_range` as for `integrate`. E.g. `cos(t), sin(t), 0` and `-y, x, 0` over
+    /// [0, 2π] give 2π.
+    pub fn line_integral(&self, curve: &str, t_range: &[f32], field: &str, rule: &str, subdivisions: u32) -> Result<JsValue, JsValue> {
+        let t_range  = range_from_slice("t_range", t_range)?;
+        let integral = LineIntegralPlot::new(curve, FieldSource::expressions(field)?, t_range, 2, None)?;
+        Ok(to_js_value(&integral.integrate(rule, subdivisions as usize)?))
+    }
+
+    /// Draws the curve r(t) colored by F·T, the work per unit of arc length done by the field
+    /// F(x, y, z) (both given as for `line_integral`): red where F pushes along the curve and blue
+    /// where it pushes against it. With an `animation_period` (in seconds), the unit tangent and
+    /// the field vector move along the curve. `set_expression` replaces the curve.
+    pub fn add_line_integral(&mut self, curve: &str, t_range: &[f32], field: &str, samples: u32, animation_period: Option<f32>) -> Result<u32, JsValue> {
+        let t_range  = range_from_slice("t_range", t_range)?;
+        let integral = LineIntegralPlot::new(curve, FieldSource::expressions(field)?, t_range, samples as usize, animation_period)?;
+
+        Ok(self.plots.add(&self.gl, PlotKind::LineIntegral(integral))?)
+    }
+
+    /// Plots the space curve r(t) given as `x, y, z`, e.g. `cos(t), sin(t), t / 5` for a helix.
+    /// The `style` is `"line"`, or `"tube"`/`"tube_frenet"` with the given radius, where the latter
+    /// orients the tube by the Frenet frame instead of a rotation minimizing one. With an
+    /// `animation_period` (in seconds), a point moves along the curve.
+    pub fn add_curve(&mut self, expression: &str, t_range: &[f32], samples: u32, style: &str, radius: f32, animation_period: Option<f32>) -> Result<u32, JsValue> {
+        let t_range = range_from_slice("t_range", t_range)?;
+        let style   = CurveStyle::from_name(style, radius)?;
+        let curve   = CurvePlot::new(expression, t_range, samples as usize, style, animation_period)?;
+
+        Ok(self.plots.add(&self.gl, PlotKind::Curve(curve))?)
+    }
+
+    /// Replaces the expression of a plot, keeping its domain and resolution. On error the old
+    /// plot is left untouched. Fields added with `add_vector_field_callback` can't be changed.
+    pub fn set_expression(&mut self, handle: u32, expression: &str) -> Result<(), JsValue> {
+        Ok(self.plots.set_expression(&self.gl, handle, expression)?)
+    }
+
+    /// Overlays arrows of the gradient on a surface z = f(x, y). `placement` is 'plane', 'surface',
+    /// 'both' or 'off'; `density` is the number of arrows along each side of the domain. Arrows are
+    /// colored and scaled by |∇f|.
+    pub fn set_gradient_field(&mut self, handle: u32, placement: &str, density: u32) -> Result<(), JsValue> {
+        let overlay = match placement_from_name("placement", placement)? {
+            Some(placement) => Some(GradientOverlay::new(placement, density as usize)?),
+            None => None,
+        };
+        Ok(self.plots.set_gradient(&self.gl, handle, overlay)?)
+    }
+
+    /// Draws the level curves f(x, y) = c of a surface z = f(x, y) for each value in `levels`.
+    /// `placement` is 'plane', 'surface', 'both' or 'off'. Contours are colored from the lowest
+    /// to the highest level.
+    pub fn set_contours(&mut self, handle: u32, levels: &[f32], placement: &str) -> Result<(), JsValue> {
+        let overlay = match placement_from_name("placement", placement)? {
+            Some(placement) => Some(ContourOverlay::new(ContourLevels::Values(levels.to_vec()), placement)?),
+            None => None,
+        };
+        Ok(self.plots.set_contours(&self.gl, handle, overlay)?)
+    }
+
+    /// Like `set_contours`, with `count` levels evenly spaced over the range of f in the domain.
+    pub fn set_contour_count(&mut self, handle: u32, count: u32, placement: &str) -> Result<(), JsValue> {
+        let overlay = match placement_from_name("placement", placement)? {
+            Some(placement) => Some(ContourOverlay::new(ContourLevels::Count(count as usize), placement)?),
+            None => None,
+        };
+        Ok(self.plots.set_contours(&self.gl, handle, overlay)?)
+    }
+
+    /// Draws the tangent plane and normal of a surface z = f(x, y) at the point under the pixel
+    /// (`screen_x`, `screen_y`), measured from the top left of the canvas. Returns
+    /// `{ x, y, z, fx, fy, normal, equation }`, or null if the pixel isn't on the surface.
+    pub fn pick_tangent_plane(&mut self, handle: u32, screen_x: f32, screen_y: f32) -> Result<JsValue, JsValue> {
+        let ray = Ray::through_pixel(
+            &self.camera.view_matrix(),
+            &self.camera.projection_matrix(),
+            screen_x,
+            screen_y,
+            self.canvas.width() as f32,
+            self.canvas.height() as f32,
+        );
+        let hit = match ray {
+            Some(ray) => self.plots.pick_surface(handle, &ray)?,
+            None => None,
+        };
+        match hit {
+            Some((x, y)) => self.set_tangent_plane(handle, x, y),
+            None => Ok(JsValue::NULL),
+        }
+    }
+
+    /// Draws the tangent plane and normal of a surface z = f(x, y) at (x, y), and returns it like
+    /// `pick_tangent_plane`. The linearization is the plane's `equation`.
+    pub fn set_tangent_plane(&mut self, handle: u32, x: f32, y: f32) -> Result<JsValue, JsValue> {
+        let plane = self.plots.set_tangent_plane(&self.gl, handle, Some((x, y)))?;
+        Ok(plane.map_or(JsValue::NULL, |plane| to_js_value(&plane.info())))
+    }
+
+    pub fn hide_tangent_plane(&mut self, handle: u32) -> Result<(), JsValue> {
+        self.plots.set_tangent_plane(&self.gl, handle, None)?;
+        Ok(())
+    }
+
+    /// Marks the critical points of a surface z = f(x, y), found by Newton's method from a `seeds`
+    /// by `seeds` grid of starting points, with cubes colored by the second derivative test.
+    /// Returns an array of `{ x, y, z, kind }` where `kind` is 'minimum', 'maximum', 'saddle' or
+    /// 'degenerate'.
+    pub fn find_critical_points(&mut self, handle: u32, seeds: u32) -> Result<JsValue, JsValue> {
+        Ok(to_js_value(&self.plots.set_critical_points(&self.gl, handle, Some(seeds as usize))?))
+    }
+
+    pub fn hide_critical_points(&mut self, handle: u32) -> Result<(), JsValue> {
+        self.plots.set_critical_points(&self.gl, handle, None)?;
+        Ok(())
+    }
+
+    /// Finds the extrema of a surface z = f(x, y) on the curve given by an equation in x and y, e.g.
+    /// `x^2 + y^2 = 1`, by Lagrange multipliers. Draws the curve lifted onto the surface, the level
+    /// curve of f through each solution and the parallel gradients of f and g there. Returns an
+    /// array of `{ x, y, z, lambda, kind }` where `kind` is 'minimum', 'maximum' or 'degenerate'.
+    pub fn set_constraint(&mut self, handle: u32, constraint: &str) -> Result<JsValue, JsValue> {
+        let overlay = ConstraintOverlay::new(constraint)?;
+        Ok(to_js_value(&self.plots.set_constraint(&self.gl, handle, Some(overlay))?))
+    }
+
+    pub fn hide_constraint(&mut self, handle: u32) -> Result<(), JsValue> {
+        self.plots.set_constraint(&self.gl, handle, None)?;
+        Ok(())
+    }
+
+    /// Colors a parametric surface by F·n, the flux per unit of area of the field F(x, y, z) given
+    /// as `P, Q, R`: red where F flows out along the normal and blue where it flows in. The normal is
+    /// r_u × r_v, or its opposite with `flip_orientation`. Draws `normal_density` by
+    /// `normal_density` normal arrows, or none with 0. Returns `{ value, error }` for the flux
+    /// ∬ F·n dS, e.g. 4π for `x, y, z` out of the unit sphere.
+    pub fn set_flux(&mut self, handle: u32, field: &str, flip_orientation: bool, normal_density: u32) -> Result<JsValue, JsValue> {
+        let overlay = FluxOverlay::new(FieldSource::expressions(field)?, flip_orientation, normal_density as usize)?;
+        Ok(to_js_value(&self.plots.set_flux(&self.gl, handle, Some(overlay))?))
+    }
+
+    pub fn hide_flux(&mut self, handle: u32) -> Result<(), JsValue> {
+        self.plots.set_flux(&self.gl, handle, None)?;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, handle: u32) -> Result<(), JsValue> {
+        Ok(self.plots.remove(&self.gl, handle)?)
+    }
+
+    /// Returns an array of `{ handle, kind, expression }` for every plot.
+    pub fn list(&self) -> JsValue {
+        to_js_value(&self.plots.list())
+    }
+
     fn get_window_size(window: &web_sys::Window) -> (u32, u32) {
         let width  = window.inner_width().unwrap().as_f64().unwrap() as u32;
         let height = window.inner_height().unwrap().as_f64().unwrap() as u32;
@@ -140,56 +418,140 @@ impl Client {
             Self::resize_canvas(&self.gl, &self.canvas, width as u32, height as u32);
         }
 
-        let state = app::get_state_of_frame_start(dt, width, height);
+        let frame = self.next_frame(dt, width, height);
+        let gamepad = self.replay.as_ref().map_or(self.gamepad, |replay| replay.recording().gamepad);
+        self.plots.animate(&self.gl, frame.state.time / 1000.0);
+        controls::update_camera(&mut self.camera, &frame, &gamepad);
+
+        // log(format!("Key input: {}, {}, {} | {}", &delta_x, &delta_y, &delta_z, &roll).as_str());
+        // log(format!("Mouse input: {}, {}", &state.delta_mouse_x, &state.delta_mouse_y).as_str());
+        // log(format!("Cam pos:     {}, {}, {}", &self.camera.position.x, &self.camera.position.y, &self.camera.position.z).as_str());
+        // log(format!("Cam right:   {}, {}, {}", &self.camera.direction.right.x, &self.camera.direction.right.y, &self.camera.direction.right.z).as_str());
+        // log(format!("Cam up:      {}, {}, {}", &self.camera.direction.up.x, &self.camera.direction.up.y, &self.camera.direction.up.z).as_str());
+        // log(format!("Cam forward: {}, {}, {}", &self.camera.direction.forward.x, &self.camera.direction.forward.y, &self.camera.direction.forward.z).as_str());
+
+        Ok(())
+    }
 
-        if state.mouse_down {
-            self.models[0].rotation.y -= (state.delta_mouse_x / state.canvas_width)  * std::f32::consts::PI * (dt/100.0);
-            self.models[0].rotation.x -= (state.delta_mouse_y / state.canvas_height) * std::f32::consts::PI * (dt/100.0);
-            self.models[0].position.x = (2.0 * state.mouse_x - state.canvas_width)  / state.canvas_width;
-            self.models[0].position.y = (2.0 * state.mouse_y - state.canvas_height) / state.canvas_height;
-        } else if state.mouse_locked {
-            let offset_from_center_x = (state.mouse_x - state.canvas_width  / 2.0) / state.canvas_width;
-            let offset_from_center_y = (state.mouse_y - state.canvas_height / 2.0) / state.canvas_height;
+    /// The live input of the frame, recorded if recording, or the next recorded frame if replaying.
+    fn next_frame(&mut self, dt: f32, width: f32, height: f32) -> Frame {
+        let frame = Frame::capture(&mut self.input.borrow_mut(), dt, width, height, app::poll_gamepad(), self.camera.mode());
 
-            if offset_from_center_x.abs() >= 0.1 {
-                self.camera.direction.yaw -= offset_from_center_x * (dt/1000.0);
-            }
-            if offset_from_center_y.abs() >= 0.1 {
-                self.camera.direction.pitch -= offset_from_center_y * (dt/1000.0);
+        if let Some(replay) = &mut self.replay {
+            // The live input is dropped while replaying, so nothing is left over when it ends.
+            if let Some(recorded) = replay.next_frame() {
+                return recorded.clone();
             }
+            self.replay = None;
+        }
+        if let Some(recording) = &mut self.recording {
+            recording.frames.push(frame.clone());
         }
+        frame
+    }
 
-        let forward   = state.key_pressed[KEY_FORWARD_INDEX]      as i32;
-        let backward  = state.key_pressed[KEY_BACKWARDS_INDEX]    as i32;
-        let left      = state.key_pressed[KEY_LEFT_INDEX]         as i32;
-        let right     = state.key_pressed[KEY_RIGHT_INDEX]        as i32;
-        let up        = state.key_pressed[KEY_UP_INDEX]           as i32;
-        let down      = state.key_pressed[KEY_DOWN_INDEX]         as i32;
-        let rot_left  = state.key_pressed[KEY_ROTATE_LEFT_INDEX]  as i32;
-        let rot_right = state.key_pressed[KEY_ROTATE_RIGHT_INDEX] as i32;
+    /// Records the input from the next frame on, until `stop_recording`. Replaying the recording
+    /// starts from the current view and retraces the camera exactly. Changes made through other
+    /// methods than `set_camera_mode`, e.g. `set_orbit_target`, aren't recorded.
+    pub fn start_recording(&mut self) {
+        let snapshot = self.camera.snapshot();
+        // Live and replay start from the same snapshot, so they can't differ in the last bits.
+        self.camera.restore(&snapshot);
+        self.recording = Some(Recording::new(snapshot, self.gamepad));
+        self.input.borrow_mut().set_recording(true);
+    }
 
-        // Should take camera direction into account, i.e. movement should be local to camera.
-        let delta_x = (right   - left)     as f32 * dt / 1000.0;
-        let delta_y = (up      - down)     as f32 * dt / 1000.0;
-        let delta_z = (forward - backward) as f32 * dt / 1000.0;
+    /// The recording in a compact binary form, or `undefined` if not recording.
+    pub fn stop_recording(&mut self) -> Option<Vec<u8>> {
+        self.input.borrow_mut().set_recording(false);
+        self.recording.take().map(|recording| recording.to_bytes())
+    }
 
-        self.camera.move_right(delta_x);
-        self.camera.move_up(delta_y);
-        self.camera.move_forward(delta_z);
+    /// Moves the camera back to where the recording started and replays it, ignoring the live
+    /// input until it's over.
+    pub fn play_recording(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        let recording = Recording::from_bytes(bytes)?;
+        self.camera.restore(&recording.camera);
+        self.replay = Some(Replay::new(recording));
+        Ok(())
+    }
 
-        let rotation = (rot_left - rot_right) as f32 * dt / 1000.0;
-        self.camera.rotate(rotation, 0.0, 0.0);
+    pub fn stop_playback(&mut self) {
+        self.replay = None;
+    }
 
-        // log(format!("Key input: {}, {}, {} | {}", &delta_x, &delta_y, &delta_z, &rotation).as_str());
-        // log(format!("Mouse input: {}, {}", &state.delta_mouse_x, &state.delta_mouse_y).as_str());
-        // log(format!("Cam pos:     {}, {}, {}", &self.camera.position.x, &self.camera.position.y, &self.camera.position.z).as_str());
-        // log(format!("Cam right:   {}, {}, {}", &self.camera.direction.right.x, &self.camera.direction.right.y, &self.camera.direction.right.z).as_str());
-        // log(format!("Cam up:      {}, {}, {}", &self.camera.direction.up.x, &self.camera.direction.up.y, &self.camera.direction.up.z).as_str());
-        // log(format!("Cam forward: {}, {}, {}", &self.camera.direction.forward.x, &self.camera.direction.forward.y, &self.camera.direction.forward.z).as_str());
+    pub fn is_playing(&self) -> bool {
+        self.replay.as_ref().is_some_and(|replay| !replay.is_finished())
+    }
+
+    /// Switches between `"fps"`, which flies around with WASD and the mouse, and `"orbit"`, which
+    /// circles a target with left drag, pans with right drag and zooms with the wheel. The view
+    /// moves smoothly from one to the other.
+    pub fn set_camera_mode(&mut self, mode: &str) -> Result<(), JsValue> {
+        let mode = CameraMode::from_name(mode).ok_or_else(|| {
+            ClientError::invalid_argument("mode", format!("Expected 'fps' or 'orbit', got '{}'", mode))
+        })?;
+        self.camera.set_mode(mode);
+        Ok(())
+    }
+
+    pub fn camera_mode(&self) -> String {
+        String::from(self.camera.mode().name())
+    }
+
+    /// Sets the point (x, y, z) the orbit camera circles around, e.g. the center of a plot.
+    pub fn set_orbit_target(&mut self, x: f32, y: f32, z: f32) -> Result<(), JsValue> {
+        if !(x.is_finite() && y.is_finite() && z.is_finite()) {
+            return Err(ClientError::invalid_argument("target", format!("Expected finite coordinates, got ({}, {}, {})", x, y, z)).into());
+        }
+        let [x, y, z] = utils::math_to_world(x, y, z);
+        self.camera.orbit.target = Vector3::new(x, y, z);
+        Ok(())
+    }
+
+    /// How the gamepad moves the fps camera: stick deflections within `dead_zone` (in [0, 1)) are
+    /// ignored, the rest is raised to `exponent`, and full deflection moves `move_speed` units or
+    /// turns `look_speed` radians per second.
+    pub fn set_gamepad_settings(&mut self, dead_zone: f32, exponent: f32, move_speed: f32, look_speed: f32, invert_y: bool) -> Result<(), JsValue> {
+        self.gamepad = GamepadSettings::new(dead_zone, exponent, move_speed, look_speed, invert_y)?;
+        Ok(())
+    }
+
+    /// Adds the key, by its `KeyboardEvent.code` (e.g. "KeyW"), to the keys of the action.
+    pub fn bind_key(&mut self, action: &str, key: &str) -> Result<(), JsValue> {
+        let action = Self::parse_action(action)?;
+        self.input.borrow_mut().keyboard.bindings.bind(action, key)?;
+        Ok(())
+    }
 
+    pub fn unbind_key(&mut self, action: &str, key: &str) -> Result<(), JsValue> {
+        let action = Self::parse_action(action)?;
+        self.input.borrow_mut().keyboard.bindings.unbind(action, key);
         Ok(())
     }
 
+    /// The bindings as JSON, e.g. `{"move_forward": ["KeyW", "ArrowUp"], ...}`, to be saved.
+    pub fn bindings_json(&self) -> String {
+        self.input.borrow().keyboard.bindings.to_json()
+    }
+
+    /// Loads bindings saved by `bindings_json`. Actions missing from them keep their default keys.
+    pub fn load_bindings(&mut self, json: &str) -> Result<(), JsValue> {
+        self.input.borrow_mut().keyboard.bindings = ActionMap::from_json(json)?;
+        Ok(())
+    }
+
+    pub fn reset_bindings(&mut self) {
+        self.input.borrow_mut().keyboard.bindings = ActionMap::default();
+    }
+
+    fn parse_action(name: &str) -> Result<Action, ClientError> {
+        Action::from_name(name).ok_or_else(|| {
+            let names: Vec<&str> = Action::ALL.iter().map(Action::name).collect();
+            ClientError::invalid_argument("action", format!("Expected one of {}, got '{}'", names.join(", "), name))
+        })
+    }
+
     pub fn render(&self) {
         // let state = app::get_current_state();
         // log(format!("Keys: {:?} | Mouse: {}", state.key_pressed, state.mouse_down).as_str());
@@ -197,12 +559,14 @@ impl Client {
         self.gl.clear_color(0.1, 0.1, 0.1, 0.1);
         self.gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
 
-        Renderer::draw(&self.gl, &self.models.as_slice(), &self.camera).unwrap();
+        Renderer::draw(&self.gl, self.models.as_slice(), &self.camera).unwrap();
+        for plot in self.plots.iter() {
+            Renderer::draw(&self.gl, &plot.models, &self.camera).unwrap();
+        }
 
         let error = self.gl.get_error();
         if error != GL::NO_ERROR {
             log(format!("GL ERROR: {}", error).as_str());
-            log(format!("Test: {:?}", vectors::Vector3::default()).as_str());
         }
     }
 }
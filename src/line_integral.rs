@@ -0,0 +1,118 @@
+use crate::math::Vector3;
+
+use crate::fields::diverging_colormap;
+use crate::integration::{integrate, Rule};
+use crate::utils::math_to_world;
+
+
+/// ∫ F·dr along r(t) for t in [a, b], as ∫ F(r(t))·r'(t) dt with the rule on `n` subintervals.
+/// The result isn't finite if F or the curve is undefined anywhere the rule samples.
+pub fn line_integral<R, D, F>(r: R, dr: D, field: F, a: f64, b: f64, n: usize, rule: Rule) -> f64
+    where R: Fn(f32) -> Vector3,
+          D: Fn(f32) -> Vector3,
+          F: Fn(Vector3) -> Vector3
+{
+    integrate(|t| (field(r(t as f32))).dot(&dr(t as f32)) as f64, a, b, n, rule)
+}
+
+
+/// The curve and the field at a parameter value.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct WorkSample {
+    pub t: f32,
+    pub point: Vector3,
+    /// The unit tangent T.
+    pub tangent: Vector3,
+    pub field: Vector3,
+}
+
+impl WorkSample {
+    /// Returns `None` where the curve or field is undefined, or the curve stops (r' = 0).
+    pub fn at<R, D, F>(r: &R, dr: &D, field: &F, t: f32) -> Option<Self>
+        where R: Fn(f32) -> Vector3,
+              D: Fn(f32) -> Vector3,
+              F: Fn(Vector3) -> Vector3
+    {
+        let point = r(t);
+        let velocity = dr(t);
+        let speed = velocity.norm();
+        if !point.is_finite() || !speed.is_finite() || speed == 0.0 {
+            return None;
+        }
+
+        let field = field(point);
+        if field.is_finite() {
+            Some(Self { t, point, tangent: velocity / speed, field })
+        } else {
+            None
+        }
+    }
+
+    /// F·T, the work done per unit of arc length.
+    pub fn contribution(&self) -> f32 {
+        self.field.dot(&self.tangent)
+    }
+}
+
+/// `samples` evenly spaced samples over [t_min, t_max], skipping those where anything is undefined.
+pub fn sample_work<R, D, F>(r: R, dr: D, field: F, t_min: f32, t_max: f32, samples: usize) -> Vec<WorkSample>
+    where R: Fn(f32) -> Vector3,
+          D: Fn(f32) -> Vector3,
+          F: Fn(Vector3) -> Vector3
+{
+    assert!(samples >= 2, "Sampling needs at least two samples.");
+    (0..samples)
+        .filter_map(|i| {
+            let t = t_min + (t_max - t_min) * i as f32 / (samples - 1) as f32;
+            WorkSample::at(&r, &dr, &field, t)
+        })
+        .collect()
+}
+
+
+/// Line segments between consecutive samples in world coordinates, in the vertex layout of
+/// `contours::create_contour_lines`, colored by F·T relative to the largest |F·T|: red where the
+/// field helps the motion along the curve and blue where it works against it. Gaps are left where
+/// a sample was skipped, i.e. where the parameters aren't consecutive.
+pub fn create_work_lines(samples: &[WorkSample], t_step: f32) -> Vec<f32> {
+    let largest = samples.iter().map(|sample| sample.contribution().abs()).fold(0.0, f32::max);
+
+    let mut vertices = Vec::with_capacity(samples.len() * 12);
+    for pair in samples.windows(2) {
+        let (start, end) = (&pair[0], &pair[1]);
+        if end.t - start.t > 1.5 * t_step {
+            continue;
+        }
+
+        let average = 0.5 * (start.contribution() + end.contribution());
+        let color = diverging_colormap(if largest > 0.0 { average / largest } else { 0.0 });
+        for sample in [start, end] {
+            vertices.extend_from_slice(&math_to_world(sample.point.x, sample.point.y, sample.point.z));
+            vertices.extend_from_slice(&[color.x, color.y, color.z]);
+        }
+    }
+    vertices
+}
+
+pub const TANGENT_COLOR: [f32; 3] = [0.95, 0.95, 0.95];
+pub const FIELD_COLOR: [f32; 3] = [0.95, 0.60, 0.20];
+
+/// The unit tangent with the given length and the field vector relative to the largest |F| at a
+/// sample, in the instance layout of `fields::create_instances`.
+pub fn create_sample_instances(sample: &WorkSample, length: f32, largest_field: f32) -> Vec<f32> {
+    let tangent = sample.tangent * length;
+    let field   = if largest_field > 0.0 { sample.field * (length / largest_field) } else { Vector3::ZERO };
+
+    let mut data = Vec::with_capacity(18);
+    for (vector, color) in [(tangent, TANGENT_COLOR), (field, FIELD_COLOR)] {
+        data.extend_from_slice(&math_to_world(sample.point.x, sample.point.y, sample.point.z));
+        data.extend_from_slice(&math_to_world(vector.x, vector.y, vector.z));
+        data.extend_from_slice(&color);
+    }
+    data
+}
+
+
+#[cfg(test)]
+#[path = "tests/line_integral.rs"]
+mod tests;
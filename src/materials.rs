@@ -1,6 +1,6 @@
 use web_sys::*;
 use web_sys::WebGl2RenderingContext as GL;
-use glm::{Mat4, Vec3, value_ptr};
+use crate::math::{Matrix4, Vector3};
 
 use crate::programs::create_program;
 use crate::shaders::compile;
@@ -93,11 +93,13 @@ void main()
 pub const SINGLE_COLOR_FRAGMENT_SHADER : &str = r#"#version 300 es
 precision mediump float;
 
+uniform vec3 color;
+
 out vec4 FragColor;
 
 void main()
 {
-    FragColor = vec4(0.4);
+    FragColor = vec4(color, 1.0);
 }
 "#;
 
@@ -105,6 +107,12 @@ void main()
 pub trait Material {
     fn enable(&self, gl: &GL, camera: &Camera);
     fn upload(&self, gl: &GL, model: &Model);
+    fn program(&self) -> &WebGlProgram;
+
+    /// Frees the shader program. Each material compiles its own, so nothing else uses it.
+    fn delete(&self, gl: &GL) {
+        gl.delete_program(Some(self.program()));
+    }
 }
 
 
@@ -144,30 +152,41 @@ impl ColorMaterial {
 impl Material for ColorMaterial {
     fn enable(&self, gl: &GL, camera: &Camera) {  // Camera will disappear when camera is in UBO.
         gl.use_program(Some(&self.id));
-        gl.uniform_matrix4fv_with_f32_array(Some(&self.view), false, &value_ptr(&camera.view_matrix()));
-        gl.uniform_matrix4fv_with_f32_array(Some(&self.projection), false, &value_ptr(&camera.projection_matrix()));
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.view), false, &camera.view_matrix().to_column_array());
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.projection), false, &camera.projection_matrix().to_column_array());
     }
     fn upload(&self, gl: &GL, model: &Model) {
-        let transform = glm::translation(&model.position);
-        let transform = glm::rotate_x(&transform, model.rotation.x);
-        let transform = glm::rotate_y(&transform, model.rotation.y);
-        let transform = glm::rotate_z(&transform, model.rotation.z);
-        gl.uniform_matrix4fv_with_f32_array(Some(&self.model), false, &value_ptr(&transform));
+        let transform = Matrix4::translation(model.position)
+            * Matrix4::rotation_x(model.rotation.x)
+            * Matrix4::rotation_y(model.rotation.y)
+            * Matrix4::rotation_z(model.rotation.z);
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.model), false, &transform.to_column_array());
+    }
+
+    fn program(&self) -> &WebGlProgram {
+        &self.id
     }
 }
 
 
 pub struct SingleColorMaterial {
     id: WebGlProgram,
+    color: Vector3,
 
     // Uniforms.
     model: WebGlUniformLocation,
     view:  WebGlUniformLocation,       // These should be global uniforms (UBO).
     projection: WebGlUniformLocation,  // These should be global uniforms (UBO).
+
+    color_location: WebGlUniformLocation,
 }
 
 impl SingleColorMaterial {
     pub fn new(gl: &GL) -> Result<Self, String> {
+        Self::with_color(gl, Vector3::new(0.4, 0.4, 0.4))
+    }
+
+    pub fn with_color(gl: &GL, color: Vector3) -> Result<Self, String> {
         let program = create_program(gl, POSITION_3D_VERTEX_SHADER, SINGLE_COLOR_FRAGMENT_SHADER)?;
 
         let model = gl.
@@ -179,12 +198,17 @@ impl SingleColorMaterial {
         let projection = gl.
             get_uniform_location(&program, "projection").
             ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'projection'.")?;
+        let color_location = gl.
+            get_uniform_location(&program, "color").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'color'.")?;
 
         Ok(Self {
             id: program,
+            color,
             model,
             view,
             projection,
+            color_location,
         })
     }
 }
@@ -192,20 +216,472 @@ impl SingleColorMaterial {
 impl Material for SingleColorMaterial {
     fn enable(&self, gl: &GL, camera: &Camera) {  // Camera will disappear when camera is in UBO.
         gl.use_program(Some(&self.id));
-        gl.uniform_matrix4fv_with_f32_array(Some(&self.view), false, &value_ptr(&camera.view_matrix()));
-        gl.uniform_matrix4fv_with_f32_array(Some(&self.projection), false, &value_ptr(&camera.projection_matrix()));
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.view), false, &camera.view_matrix().to_column_array());
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.projection), false, &camera.projection_matrix().to_column_array());
+        gl.uniform3f(Some(&self.color_location), self.color.x, self.color.y, self.color.z);
+    }
+
+    fn upload(&self, gl: &GL, model: &Model) {
+        let transform = Matrix4::translation(model.position)
+            * Matrix4::rotation_x(model.rotation.x)
+            * Matrix4::rotation_y(model.rotation.y)
+            * Matrix4::rotation_z(model.rotation.z);
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.model), false, &transform.to_column_array());
+    }
+
+    fn program(&self) -> &WebGlProgram {
+        &self.id
+    }
+}
+
+
+
+
+pub const SHADED_FRAGMENT_SHADER : &str = r#"#version 300 es
+precision mediump float;
+
+in vec4 out_position;
+in vec2 out_texture_coordinate;
+in vec3 out_normal;
+
+uniform vec3 color;
+
+out vec4 FragColor;
+
+void main()
+{
+    // Two-sided diffuse lighting, as surfaces are open and looked at from both sides.
+    vec3 light   = normalize(vec3(0.4, 1.0, 0.3));
+    vec3 normal  = normalize(out_normal);
+    float diffuse = abs(dot(normal, light));
+
+    FragColor = vec4(color * (0.25 + 0.75 * diffuse), 1.0);
+}
+"#;
+
+
+pub struct ShadedMaterial {
+    id: WebGlProgram,
+    color: Vector3,
+
+    // Uniforms.
+    model: WebGlUniformLocation,
+    view:  WebGlUniformLocation,       // These should be global uniforms (UBO).
+    projection: WebGlUniformLocation,  // These should be global uniforms (UBO).
+
+    color_location: WebGlUniformLocation,
+}
+
+impl ShadedMaterial {
+    pub fn new(gl: &GL, color: Vector3) -> Result<Self, String> {
+        let program = create_program(gl, COLOR_VERTEX_SHADER, SHADED_FRAGMENT_SHADER)?;
+
+        let model = gl.
+            get_uniform_location(&program, "model").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'model'.")?;
+        let view = gl.
+            get_uniform_location(&program, "view").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'view'.")?;
+        let projection = gl.
+            get_uniform_location(&program, "projection").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'projection'.")?;
+        let color_location = gl.
+            get_uniform_location(&program, "color").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'color'.")?;
+
+        Ok(Self {
+            id: program,
+            color,
+            model,
+            view,
+            projection,
+            color_location,
+        })
+    }
+}
+
+impl Material for ShadedMaterial {
+    fn enable(&self, gl: &GL, camera: &Camera) {  // Camera will disappear when camera is in UBO.
+        gl.use_program(Some(&self.id));
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.view), false, &camera.view_matrix().to_column_array());
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.projection), false, &camera.projection_matrix().to_column_array());
+        gl.uniform3f(Some(&self.color_location), self.color.x, self.color.y, self.color.z);
+    }
+
+    fn upload(&self, gl: &GL, model: &Model) {
+        let transform = Matrix4::translation(model.position)
+            * Matrix4::rotation_x(model.rotation.x)
+            * Matrix4::rotation_y(model.rotation.y)
+            * Matrix4::rotation_z(model.rotation.z);
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.model), false, &transform.to_column_array());
+    }
+
+    fn program(&self) -> &WebGlProgram {
+        &self.id
+    }
+}
+
+
+
+pub const VERTEX_COLOR_VERTEX_SHADER : &str = r#"#version 300 es
+layout (location = 0) in vec3 position;
+layout (location = 1) in vec3 color;
+
+uniform mat4 model;
+uniform mat4 view;
+uniform mat4 projection;
+
+out vec3 out_color;
+
+void main()
+{
+    out_color = color;
+    gl_Position = projection * view * model * vec4(position, 1.0);
+}
+"#;
+
+pub const VERTEX_COLOR_FRAGMENT_SHADER : &str = r#"#version 300 es
+precision mediump float;
+
+in vec3 out_color;
+
+out vec4 FragColor;
+
+void main()
+{
+    FragColor = vec4(out_color, 1.0);
+}
+"#;
+
+
+/// Unlit, with a color per vertex; for lines such as contours (see `Mesh::from_positions_and_colors_3d`).
+pub struct VertexColorMaterial {
+    id: WebGlProgram,
+
+    // Uniforms.
+    model: WebGlUniformLocation,
+    view:  WebGlUniformLocation,       // These should be global uniforms (UBO).
+    projection: WebGlUniformLocation,  // These should be global uniforms (UBO).
+}
+
+impl VertexColorMaterial {
+    pub fn new(gl: &GL) -> Result<Self, String> {
+        let program = create_program(gl, VERTEX_COLOR_VERTEX_SHADER, VERTEX_COLOR_FRAGMENT_SHADER)?;
+
+        let model = gl.
+            get_uniform_location(&program, "model").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'model'.")?;
+        let view = gl.
+            get_uniform_location(&program, "view").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'view'.")?;
+        let projection = gl.
+            get_uniform_location(&program, "projection").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'projection'.")?;
+
+        Ok(Self {
+            id: program,
+            model,
+            view,
+            projection,
+        })
+    }
+}
+
+impl Material for VertexColorMaterial {
+    fn enable(&self, gl: &GL, camera: &Camera) {  // Camera will disappear when camera is in UBO.
+        gl.use_program(Some(&self.id));
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.view), false, &camera.view_matrix().to_column_array());
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.projection), false, &camera.projection_matrix().to_column_array());
+    }
+
+    fn upload(&self, gl: &GL, model: &Model) {
+        let transform = Matrix4::translation(model.position)
+            * Matrix4::rotation_x(model.rotation.x)
+            * Matrix4::rotation_y(model.rotation.y)
+            * Matrix4::rotation_z(model.rotation.z);
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.model), false, &transform.to_column_array());
+    }
+
+    fn program(&self) -> &WebGlProgram {
+        &self.id
+    }
+}
+
+
+
+pub const DIVERGING_COLOR_FRAGMENT_SHADER : &str = r#"#version 300 es
+precision mediump float;
+
+in vec4 out_position;
+in vec2 out_texture_coordinate;
+in vec3 out_normal;
+
+out vec4 FragColor;
+
+void main()
+{
+    // The same colors as `fields::diverging_colormap`, with t in [-1, 1] stored as [0, 1].
+    vec3 negative = vec3(0.20, 0.40, 0.95);
+    vec3 neutral  = vec3(0.95, 0.95, 0.95);
+    vec3 positive = vec3(0.95, 0.25, 0.20);
+    float t = clamp(2.0 * out_texture_coordinate.x - 1.0, -1.0, 1.0);
+    vec3 color = mix(neutral, t < 0.0 ? negative : positive, abs(t));
+
+    vec3 light   = normalize(vec3(0.4, 1.0, 0.3));
+    vec3 normal  = normalize(out_normal);
+    float diffuse = abs(dot(normal, light));
+
+    FragColor = vec4(color * (0.25 + 0.75 * diffuse), 1.0);
+}
+"#;
+
+
+/// Shades a surface like `ShadedMaterial`, colored by a signed value stored in the first texture
+/// coordinate (see `flux::apply_flux_colors`).
+pub struct DivergingColorMaterial {
+    id: WebGlProgram,
+
+    // Uniforms.
+    model: WebGlUniformLocation,
+    view:  WebGlUniformLocation,       // These should be global uniforms (UBO).
+    projection: WebGlUniformLocation,  // These should be global uniforms (UBO).
+}
+
+impl DivergingColorMaterial {
+    pub fn new(gl: &GL) -> Result<Self, String> {
+        let program = create_program(gl, COLOR_VERTEX_SHADER, DIVERGING_COLOR_FRAGMENT_SHADER)?;
+
+        let model = gl.
+            get_uniform_location(&program, "model").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'model'.")?;
+        let view = gl.
+            get_uniform_location(&program, "view").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'view'.")?;
+        let projection = gl.
+            get_uniform_location(&program, "projection").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'projection'.")?;
+
+        Ok(Self {
+            id: program,
+            model,
+            view,
+            projection,
+        })
+    }
+}
+
+impl Material for DivergingColorMaterial {
+    fn enable(&self, gl: &GL, camera: &Camera) {  // Camera will disappear when camera is in UBO.
+        gl.use_program(Some(&self.id));
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.view), false, &camera.view_matrix().to_column_array());
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.projection), false, &camera.projection_matrix().to_column_array());
+    }
+
+    fn upload(&self, gl: &GL, model: &Model) {
+        let transform = Matrix4::translation(model.position)
+            * Matrix4::rotation_x(model.rotation.x)
+            * Matrix4::rotation_y(model.rotation.y)
+            * Matrix4::rotation_z(model.rotation.z);
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.model), false, &transform.to_column_array());
+    }
+
+    fn program(&self) -> &WebGlProgram {
+        &self.id
+    }
+}
+
+
+
+pub const ARROW_VERTEX_SHADER : &str = r#"#version 300 es
+layout (location = 0) in vec3 position;
+layout (location = 1) in vec2 texture_coordinate;
+layout (location = 2) in vec3 normal;
+
+// Per instance: where the arrow starts, the world vector it points along and its color.
+layout (location = 3) in vec3 instance_origin;
+layout (location = 4) in vec3 instance_vector;
+layout (location = 5) in vec3 instance_color;
+
+uniform mat4 model;
+uniform mat4 view;
+uniform mat4 projection;
+
+out vec3 out_normal;
+out vec3 out_color;
+
+void main()
+{
+    // The glyph points along +y with unit length, so rotate +y onto the vector and scale it.
+    float len  = length(instance_vector);
+    vec3 axis  = len > 0.0 ? instance_vector / len : vec3(0.0, 1.0, 0.0);
+    vec3 other = abs(axis.y) < 0.99 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0);
+    vec3 side  = normalize(cross(other, axis));
+    mat3 basis = mat3(side, axis, cross(side, axis));
+
+    vec4 world = model * vec4(instance_origin + basis * (position * len), 1.0);
+    out_normal = mat3(transpose(inverse(model))) * (basis * normal);
+    out_color  = instance_color;
+
+    gl_Position = projection * view * world;
+}
+"#;
+
+pub const ARROW_FRAGMENT_SHADER : &str = r#"#version 300 es
+precision mediump float;
+
+in vec3 out_normal;
+in vec3 out_color;
+
+out vec4 FragColor;
+
+void main()
+{
+    vec3 light   = normalize(vec3(0.4, 1.0, 0.3));
+    vec3 normal  = normalize(out_normal);
+    float diffuse = abs(dot(normal, light));
+
+    FragColor = vec4(out_color * (0.25 + 0.75 * diffuse), 1.0);
+}
+"#;
+
+
+/// Shades instanced arrow glyphs, each with its own origin, vector and color (see `fields.rs`).
+pub struct ArrowMaterial {
+    id: WebGlProgram,
+
+    // Uniforms.
+    model: WebGlUniformLocation,
+    view:  WebGlUniformLocation,       // These should be global uniforms (UBO).
+    projection: WebGlUniformLocation,  // These should be global uniforms (UBO).
+}
+
+impl ArrowMaterial {
+    pub fn new(gl: &GL) -> Result<Self, String> {
+        let program = create_program(gl, ARROW_VERTEX_SHADER, ARROW_FRAGMENT_SHADER)?;
+
+        let model = gl.
+            get_uniform_location(&program, "model").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'model'.")?;
+        let view = gl.
+            get_uniform_location(&program, "view").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'view'.")?;
+        let projection = gl.
+            get_uniform_location(&program, "projection").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'projection'.")?;
+
+        Ok(Self {
+            id: program,
+            model,
+            view,
+            projection,
+        })
+    }
+}
+
+impl Material for ArrowMaterial {
+    fn enable(&self, gl: &GL, camera: &Camera) {  // Camera will disappear when camera is in UBO.
+        gl.use_program(Some(&self.id));
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.view), false, &camera.view_matrix().to_column_array());
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.projection), false, &camera.projection_matrix().to_column_array());
     }
 
     fn upload(&self, gl: &GL, model: &Model) {
-        let transform = glm::translation(&model.position);
-        let transform = glm::rotate_x(&transform, model.rotation.x);
-        let transform = glm::rotate_y(&transform, model.rotation.y);
-        let transform = glm::rotate_z(&transform, model.rotation.z);
-        gl.uniform_matrix4fv_with_f32_array(Some(&self.model), false, &value_ptr(&transform));
+        let transform = Matrix4::translation(model.position)
+            * Matrix4::rotation_x(model.rotation.x)
+            * Matrix4::rotation_y(model.rotation.y)
+            * Matrix4::rotation_z(model.rotation.z);
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.model), false, &transform.to_column_array());
+    }
+
+    fn program(&self) -> &WebGlProgram {
+        &self.id
+    }
+}
+
+
+
+pub const PRISM_VERTEX_SHADER : &str = r#"#version 300 es
+layout (location = 0) in vec3 position;
+layout (location = 1) in vec2 texture_coordinate;
+layout (location = 2) in vec3 normal;
+
+// Per instance: the center and size of the box and its color, for a unit cube mesh.
+layout (location = 3) in vec3 instance_center;
+layout (location = 4) in vec3 instance_size;
+layout (location = 5) in vec3 instance_color;
+
+uniform mat4 model;
+uniform mat4 view;
+uniform mat4 projection;
+
+out vec3 out_normal;
+out vec3 out_color;
+
+void main()
+{
+    vec4 world = model * vec4(instance_center + position * instance_size, 1.0);
+    // Normals of a scaled box stay axis aligned, so the inverse transpose of the scale isn't needed.
+    out_normal = mat3(transpose(inverse(model))) * normal;
+    out_color  = instance_color;
+
+    gl_Position = projection * view * world;
+}
+"#;
+
+
+/// Shades instanced boxes, each with its own center, size and color (see `integration.rs`).
+/// The fragment shader is the one of the arrows.
+pub struct PrismMaterial {
+    id: WebGlProgram,
+
+    // Uniforms.
+    model: WebGlUniformLocation,
+    view:  WebGlUniformLocation,       // These should be global uniforms (UBO).
+    projection: WebGlUniformLocation,  // These should be global uniforms (UBO).
+}
+
+impl PrismMaterial {
+    pub fn new(gl: &GL) -> Result<Self, String> {
+        let program = create_program(gl, PRISM_VERTEX_SHADER, ARROW_FRAGMENT_SHADER)?;
+
+        let model = gl.
+            get_uniform_location(&program, "model").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'model'.")?;
+        let view = gl.
+            get_uniform_location(&program, "view").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'view'.")?;
+        let projection = gl.
+            get_uniform_location(&program, "projection").
+            ok_or("[WEBGL2 - UNIFORM ERROR]: Couldn't get uniform 'projection'.")?;
+
+        Ok(Self {
+            id: program,
+            model,
+            view,
+            projection,
+        })
     }
 }
 
+impl Material for PrismMaterial {
+    fn enable(&self, gl: &GL, camera: &Camera) {  // Camera will disappear when camera is in UBO.
+        gl.use_program(Some(&self.id));
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.view), false, &camera.view_matrix().to_column_array());
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.projection), false, &camera.projection_matrix().to_column_array());
+    }
+
+    fn upload(&self, gl: &GL, model: &Model) {
+        let transform = Matrix4::translation(model.position)
+            * Matrix4::rotation_x(model.rotation.x)
+            * Matrix4::rotation_y(model.rotation.y)
+            * Matrix4::rotation_z(model.rotation.z);
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.model), false, &transform.to_column_array());
+    }
 
+    fn program(&self) -> &WebGlProgram {
+        &self.id
+    }
+}
 
 
 
@@ -282,16 +758,16 @@ impl DefaultMaterial {
 impl Material for DefaultMaterial {
     fn enable(&self, gl: &GL, camera: &Camera) {  // Camera will disappear when camera is in UBO.
         gl.use_program(Some(&self.id));
-        gl.uniform_matrix4fv_with_f32_array(Some(&self.view), false, &value_ptr(&camera.view_matrix()));
-        gl.uniform_matrix4fv_with_f32_array(Some(&self.projection), false, &value_ptr(&camera.projection_matrix()));
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.view), false, &camera.view_matrix().to_column_array());
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.projection), false, &camera.projection_matrix().to_column_array());
     }
 
     fn upload(&self, gl: &GL, model: &Model) {
-        let transform = glm::translation(&model.position);
-        let transform = glm::rotate_x(&transform, model.rotation.x);
-        let transform = glm::rotate_y(&transform, model.rotation.y);
-        let transform = glm::rotate_z(&transform, model.rotation.z);
-        gl.uniform_matrix4fv_with_f32_array(Some(&self.model), false, &value_ptr(&transform));
+        let transform = Matrix4::translation(model.position)
+            * Matrix4::rotation_x(model.rotation.x)
+            * Matrix4::rotation_y(model.rotation.y)
+            * Matrix4::rotation_z(model.rotation.z);
+        gl.uniform_matrix4fv_with_f32_array(Some(&self.model), false, &transform.to_column_array());
 
         // let albedo = Texture::from_pixels(gl, 1, 1, Some(&[255u8]));
         // let metallic = Texture::from_pixels(gl, 1, 1, Some(&[255u8]));
@@ -303,4 +779,13 @@ impl Material for DefaultMaterial {
         gl.bind_texture(GL::TEXTURE_2D, Some(&self.albedo));
         gl.uniform1i(Some(&self.albedo_location), 0);
     }
+
+    fn program(&self) -> &WebGlProgram {
+        &self.id
+    }
+
+    fn delete(&self, gl: &GL) {
+        gl.delete_program(Some(&self.id));
+        gl.delete_texture(Some(&self.albedo));
+    }
 }
\ No newline at end of file
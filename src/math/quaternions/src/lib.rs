@@ -1,38 +1,40 @@
-#![allow(unused_imports)]
-#![allow(unused_variables)]
+extern crate nalgebra as na;
 
-extern crate nalgebra_glm as glm;
+use vectors::{Matrix3, Matrix4, Vector3};
+use na::UnitQuaternion;
 
-use glm::{Vec3, Mat4};
-
-use std::ops::{Add, Mul};
+use std::ops::{Add, Div, Mul, Neg, Sub};
 
 type Radians = f32;
 
-/// Invariants:
-///     * x + y + z = 1
+/// A quaternion w + xi + yj + zk, stored as the scalar w and the vector (x, y, z).
+///
+/// Invariants (for rotations):
 ///     * x^2 + y^2 + z^2 + w^2 = 1
+///     * q and -q are the same rotation
 
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Quaternion {
     pub scalar: f32,
-    pub vector: Vec3,
+    pub vector: Vector3,
 }
 
 impl Quaternion {
+    /// The identity rotation.
     pub fn new() -> Self {
-        Self { scalar: 1.0, vector: Vec3::new(0.0, 0.0, 0.0) }
+        Self { scalar: 1.0, vector: Vector3::new(0.0, 0.0, 0.0) }
     }
 
-    fn from_raw(scalar: f32, vector: Vec3) -> Self {
+    pub fn from_raw(scalar: f32, vector: Vector3) -> Self {
         Self { scalar, vector }
     }
 
-    pub fn from_axis_rotation(angle: Radians, axis: Vec3) -> Self {
+    /// The counterclockwise rotation by `angle` around `axis`, looking against the axis.
+    pub fn from_axis_rotation(angle: Radians, axis: Vector3) -> Self {
         let axis = axis.normalize();
         Self {
-            scalar: f32::cos(angle / 2.9),
-            vector: Vec3::new(
+            scalar: f32::cos(angle / 2.0),
+            vector: Vector3::new(
                 axis.x * f32::sin(angle / 2.0),
                 axis.y * f32::sin(angle / 2.0),
                 axis.z * f32::sin(angle / 2.0),
@@ -40,8 +42,107 @@ impl Quaternion {
         }
     }
 
+    /// The angle (in [0, 2π]) and unit axis of a unit quaternion. The axis of the identity is
+    /// arbitrary (x).
+    pub fn to_axis_rotation(&self) -> (Radians, Vector3) {
+        let angle  = 2.0 * Self::clamp_unit(self.scalar).acos();
+        let length = self.vector.norm();
+        if length < 1e-7 {
+            (angle, Vector3::new(1.0, 0.0, 0.0))
+        } else {
+            (angle, self.vector / length)
+        }
+    }
+
+    /// The rotation by `roll` around x, then `pitch` around y and last `yaw` around z.
+    pub fn from_euler(roll: Radians, pitch: Radians, yaw: Radians) -> Self {
+        let roll  = Self::from_axis_rotation(roll,  Vector3::new(1.0, 0.0, 0.0));
+        let pitch = Self::from_axis_rotation(pitch, Vector3::new(0.0, 1.0, 0.0));
+        let yaw   = Self::from_axis_rotation(yaw,   Vector3::new(0.0, 0.0, 1.0));
+        yaw * pitch * roll
+    }
+
+    /// The `(roll, pitch, yaw)` of `from_euler`, with pitch in [-π/2, π/2]. At ±π/2 pitch, roll
+    /// and yaw turn around the same axis and only their difference is recovered (as yaw).
+    pub fn to_euler(&self) -> (Radians, Radians, Radians) {
+        let (w, x, y, z) = (self.scalar, self.vector.x, self.vector.y, self.vector.z);
+
+        let sin_pitch = Self::clamp_unit(2.0 * (w * y - z * x));
+        let pitch = sin_pitch.asin();
+        if sin_pitch.abs() > 0.99999 {
+            let yaw = -2.0 * sin_pitch.signum() * f32::atan2(x, w);
+            return (0.0, pitch, yaw);
+        }
+
+        let roll = f32::atan2(2.0 * (w * x + y * z), 1.0 - 2.0 * (x * x + y * y));
+        let yaw  = f32::atan2(2.0 * (w * z + x * y), 1.0 - 2.0 * (y * y + z * z));
+        (roll, pitch, yaw)
+    }
+
+    /// The rotation taking +z to `forward` and +y as close to `up` as possible. `up` may not be
+    /// parallel to `forward`.
+    pub fn look_rotation(forward: Vector3, up: Vector3) -> Self {
+        let forward = forward.normalize();
+        let right   = up.cross(&forward).normalize();
+        let up      = forward.cross(&right);
+        Self::from_rotation_matrix(&Matrix3::from_columns([right, up, forward]))
+    }
+
+    /// The rotation of the upper left 3x3 block of a rotation matrix, as from `to_matrix`.
+    pub fn from_matrix4x4(matrix: &Matrix4) -> Self {
+        Self::from_rotation_matrix(&matrix.upper_left())
+    }
+
+    // https://www.euclideanspace.com/maths/geometry/rotations/conversions/matrixToQuaternion/index.htm
+    // Divides by the largest of the four candidates to stay numerically stable.
+    fn from_rotation_matrix(m: &Matrix3) -> Self {
+        let trace = m[(0, 0)] + m[(1, 1)] + m[(2, 2)];
+        let quaternion = if trace > 0.0 {
+            let s = 2.0 * (trace + 1.0).sqrt();  // 4w
+            Self::from_raw(0.25 * s, Vector3::new(
+                (m[(2, 1)] - m[(1, 2)]) / s,
+                (m[(0, 2)] - m[(2, 0)]) / s,
+                (m[(1, 0)] - m[(0, 1)]) / s,
+            ))
+        } else if m[(0, 0)] > m[(1, 1)] && m[(0, 0)] > m[(2, 2)] {
+            let s = 2.0 * (1.0 + m[(0, 0)] - m[(1, 1)] - m[(2, 2)]).sqrt();  // 4x
+            Self::from_raw((m[(2, 1)] - m[(1, 2)]) / s, Vector3::new(
+                0.25 * s,
+                (m[(0, 1)] + m[(1, 0)]) / s,
+                (m[(0, 2)] + m[(2, 0)]) / s,
+            ))
+        } else if m[(1, 1)] > m[(2, 2)] {
+            let s = 2.0 * (1.0 + m[(1, 1)] - m[(0, 0)] - m[(2, 2)]).sqrt();  // 4y
+            Self::from_raw((m[(0, 2)] - m[(2, 0)]) / s, Vector3::new(
+                (m[(0, 1)] + m[(1, 0)]) / s,
+                0.25 * s,
+                (m[(1, 2)] + m[(2, 1)]) / s,
+            ))
+        } else {
+            let s = 2.0 * (1.0 + m[(2, 2)] - m[(0, 0)] - m[(1, 1)]).sqrt();  // 4z
+            Self::from_raw((m[(1, 0)] - m[(0, 1)]) / s, Vector3::new(
+                (m[(0, 2)] + m[(2, 0)]) / s,
+                (m[(1, 2)] + m[(2, 1)]) / s,
+                0.25 * s,
+            ))
+        };
+        quaternion.normalize()
+    }
+
+    fn clamp_unit(value: f32) -> f32 {
+        value.clamp(-1.0, 1.0)
+    }
+
     pub fn conjugate(&self) -> Self {
-        Self::from_raw(self.scalar, -&self.vector)
+        Self::from_raw(self.scalar, -self.vector)
+    }
+
+    pub fn dot(&self, other: &Self) -> f32 {
+        self.scalar * other.scalar + self.vector.dot(&other.vector)
+    }
+
+    pub fn norm_squared(&self) -> f32 {
+        self.dot(self)
     }
 
     pub fn norm(&self) -> f32 {
@@ -53,26 +154,44 @@ impl Quaternion {
         f32::sqrt(w*w + x*x + y*y + z*z)
     }
 
-    pub fn inverse(&self) -> Self {
-        *self * (1.0 / self.norm())
+    /// Scales the quaternion to unit length, which repeated multiplications slowly drift away from.
+    pub fn normalize(&self) -> Self {
+        *self / self.norm()
     }
 
-    pub fn rotate(&self, point: Vec3) -> Self {
-        *self * Self::from_raw(0.0, point) * self.conjugate()
-    }
+    /// Spherical linear interpolation, rotating from `self` at t = 0 to `other` at t = 1 at a
+    /// constant angular velocity the short way around. Both should be unit quaternions.
+    pub fn slerp(&self, other: &Self, t: f32) -> Self {
+        // q and -q are the same rotation; pick the one closest to self.
+        let mut cos_theta = self.dot(other);
+        let other = if cos_theta < 0.0 {
+            cos_theta = -cos_theta;
+            -*other
+        } else {
+            *other
+        };
+
+        // Nearly equal rotations divide by sin(θ) ≈ 0; interpolate linearly instead.
+        if cos_theta > 0.9995 {
+            return (*self * (1.0 - t) + other * t).normalize();
+        }
 
-    // pub fn to_euler() {
-    //     let roll  = Self::from_raw(f32::cos(y / 2), Vec3::new(f32::sin(y / 2), 0, 0));
-    //     let pitch = Self::from_raw(f32::cos(q / 2), Vec3::new(0, f32::sin(q / 2), 0));
-    //     let yaw   = Self::from_raw(f32::cos(f / 2), Vec3::new(0, 0, f32::sin(f / 2)));
-    // }
+        let theta = cos_theta.acos();
+        let sin_theta = theta.sin();
+        *self * (((1.0 - t) * theta).sin() / sin_theta) + other * ((t * theta).sin() / sin_theta)
+    }
 
-    pub fn from_matrix4x4(matrix: Mat4)
-    {
+    /// q^-1 = q* / |q|^2, so q q^-1 = 1 for any non-zero q (not only unit ones).
+    pub fn inverse(&self) -> Self {
+        self.conjugate() / self.norm_squared()
+    }
 
+    /// Rotates the point by q p q^-1.
+    pub fn rotate(&self, point: Vector3) -> Vector3 {
+        (*self * Self::from_raw(0.0, point) * self.inverse()).vector
     }
 
-    pub fn to_matrix(&self) -> Mat4 {
+    pub fn to_matrix(&self) -> Matrix4 {
         let x = self.vector.x;
         let y = self.vector.y;
         let z = self.vector.z;
@@ -91,12 +210,18 @@ impl Quaternion {
         let m21 = 2.0 * y * z + 2.0 * x * w;        // 2*y*z + 2*x*w
         let m22 = 1.0 - 2.0 * x * x - 2.0 * y * y;  // 1 - 2*x^2 - 2*y^2
 
-        Mat4::new(
-            m00, m01, m02, 0.0,
-            m10, m11, m12, 0.0,
-            m20, m21, m22, 0.0,
-            0.0, 0.0, 0.0, 0.0,
-        )
+        Matrix4::from_rows([
+            [m00, m01, m02, 0.0],
+            [m10, m11, m12, 0.0],
+            [m20, m21, m22, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -111,6 +236,25 @@ impl Add for Quaternion {
     }
 }
 
+impl Sub for Quaternion {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::from_raw(
+            self.scalar - rhs.scalar,
+            self.vector - rhs.vector,
+        )
+    }
+}
+
+impl Neg for Quaternion {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::from_raw(-self.scalar, -self.vector)
+    }
+}
+
 impl Mul<Quaternion> for Quaternion {
     type Output = Self;
 
@@ -121,8 +265,8 @@ impl Mul<Quaternion> for Quaternion {
         let v2 = rhs.vector;
 
         Self::from_raw(
-            r1 * r2 - glm::dot(&v1, &v2),
-            r1 * v2 + r2 * v1 + glm::cross(&v1, &v2),
+            r1 * r2 - v1.dot(&v2),
+            r1 * v2 + r2 * v1 + v1.cross(&v2),
         )
     }
 }
@@ -135,5 +279,60 @@ impl Mul<f32> for Quaternion {
     }
 }
 
+/// a / b = a b^-1.
+impl Div<Quaternion> for Quaternion {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.inverse()
+    }
+}
+
+impl Div<f32> for Quaternion {
+    type Output = Self;
+
+    fn div(self, rhs: f32) -> Self::Output {
+        Self::from_raw(self.scalar / rhs, self.vector / rhs)
+    }
+}
 
 
+// ---- NALGEBRA CONVERSIONS ----
+impl From<na::Quaternion<f32>> for Quaternion {
+    fn from(quaternion: na::Quaternion<f32>) -> Self {
+        Self::from_raw(quaternion.w, Vector3::from(quaternion.imag()))
+    }
+}
+
+impl From<Quaternion> for na::Quaternion<f32> {
+    fn from(quaternion: Quaternion) -> Self {
+        let v = quaternion.vector;
+        na::Quaternion::new(quaternion.scalar, v.x, v.y, v.z)
+    }
+}
+
+impl From<UnitQuaternion<f32>> for Quaternion {
+    fn from(quaternion: UnitQuaternion<f32>) -> Self {
+        Self::from(quaternion.into_inner())
+    }
+}
+
+/// Normalizes the quaternion.
+impl From<Quaternion> for UnitQuaternion<f32> {
+    fn from(quaternion: Quaternion) -> Self {
+        UnitQuaternion::new_normalize(na::Quaternion::from(quaternion))
+    }
+}
+
+impl From<Quaternion> for Matrix4 {
+    fn from(quaternion: Quaternion) -> Self {
+        quaternion.to_matrix()
+    }
+}
+
+impl From<Quaternion> for na::Matrix4<f32> {
+    fn from(quaternion: Quaternion) -> Self {
+        quaternion.to_matrix().into()
+    }
+}
@@ -1,38 +1,213 @@
 #[cfg(test)]
 mod tests {
     use quaternions::Quaternion;
-    use nalgebra_glm::{Mat4, Vec3};
+    use nalgebra::UnitQuaternion;
+    use nalgebra_glm::{Mat4, Quat};
+    use vectors::{Matrix4, Vector3};
+    use proptest::prelude::*;
+    use std::f32::consts::{FRAC_PI_2, PI};
 
-    pub fn print_matrix(m: &Mat4) {
-        println!("\
-Matrix(\n\
-\t{:.2}, {:.2}, {:.2}, {:.2},\n\
-\t{:.2}, {:.2}, {:.2}, {:.2},\n\
-\t{:.2}, {:.2}, {:.2}, {:.2},\n\
-\t{:.2}, {:.2}, {:.2}, {:.2},\n\
-)", m.m11, m.m12, m.m13, m.m14,
-                 m.m21, m.m22, m.m23, m.m24,
-                 m.m31, m.m32, m.m33, m.m34,
-                 m.m41, m.m42, m.m43, m.m44
-        );
+    const EPSILON: f32 = 1e-4;
+
+    fn assert_matrix_close(a: &Matrix4, b: &Matrix4) {
+        let differs = a.to_column_array().iter().zip(b.to_column_array().iter()).any(|(x, y)| (x - y).abs() > EPSILON);
+        assert!(!differs, "The matrices differ:\n{:?}\n{:?}", a, b);
+    }
+
+    fn assert_vector_close(a: &Vector3, b: &Vector3) {
+        assert!((*a - *b).norm() < EPSILON, "{:?} != {:?}", a, b);
+    }
+
+    /// Whether both are the same rotation, i.e. equal up to sign.
+    fn same_rotation(a: &Quaternion, b: &Quaternion) -> bool {
+        (a.dot(b).abs() - 1.0).abs() < EPSILON
+    }
+
+
+    // ---- STRATEGIES ----
+    fn angle() -> impl Strategy<Value = f32> {
+        -PI..PI
+    }
+
+    fn vector() -> impl Strategy<Value = Vector3> {
+        (-10.0f32..10.0, -10.0f32..10.0, -10.0f32..10.0).prop_map(|(x, y, z)| Vector3::new(x, y, z))
+    }
+
+    fn axis() -> impl Strategy<Value = Vector3> {
+        vector().prop_filter("axes can't be zero", |v| v.norm() > 0.1)
     }
 
-    fn default_rotation_matrix() -> Mat4 {
-        let mut a = Mat4::default();
-        a.fill_with_identity();
-        a.m44 = 0.0;
-        a
+    fn rotation() -> impl Strategy<Value = Quaternion> {
+        (angle(), axis()).prop_map(|(angle, axis)| Quaternion::from_axis_rotation(angle, axis))
     }
 
+    fn quaternion() -> impl Strategy<Value = Quaternion> {
+        (-10.0f32..10.0, vector()).prop_map(|(scalar, vector)| Quaternion::from_raw(scalar, vector))
+    }
+
+
     #[test]
     fn quaternion_to_matrix_default() {
         let quaternion = Quaternion::new();
-        assert_eq!(quaternion.to_matrix(), default_rotation_matrix());
+        assert_eq!(quaternion.to_matrix(), Matrix4::IDENTITY);
     }
 
     #[test]
     fn quaternion_to_matrix_rot_2pi_x() {
-        let quaternion = Quaternion::from_axis_rotation(2.0 * std::f32::consts::PI, Vec3::new(1.0, 0.0, 0.0));
-        assert_eq!(quaternion.to_matrix(), default_rotation_matrix());
+        let quaternion = Quaternion::from_axis_rotation(2.0 * PI, Vector3::new(1.0, 0.0, 0.0));
+        assert_matrix_close(&quaternion.to_matrix(), &Matrix4::IDENTITY);
+    }
+
+    #[test]
+    fn quaternion_from_axis_rotation_half_turn() {
+        let quaternion = Quaternion::from_axis_rotation(PI, Vector3::new(0.0, 0.0, 2.0));
+        assert!(quaternion.scalar.abs() < 1e-6);
+        assert!((quaternion.vector - Vector3::new(0.0, 0.0, 1.0)).norm() < 1e-6);
+    }
+
+    #[test]
+    fn quaternion_rotates_counterclockwise() {
+        let quaternion = Quaternion::from_axis_rotation(FRAC_PI_2, Vector3::new(0.0, 0.0, 1.0));
+        assert_vector_close(&quaternion.rotate(Vector3::new(1.0, 0.0, 0.0)), &Vector3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn quaternion_look_rotation_axes() {
+        let quaternion = Quaternion::look_rotation(Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+        assert_vector_close(&quaternion.rotate(Vector3::new(0.0, 0.0, 1.0)), &Vector3::new(1.0, 0.0, 0.0));
+        assert_vector_close(&quaternion.rotate(Vector3::new(0.0, 1.0, 0.0)), &Vector3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn quaternion_to_euler_at_gimbal_lock() {
+        for &pitch in &[FRAC_PI_2, -FRAC_PI_2] {
+            let quaternion = Quaternion::from_euler(0.3, pitch, -0.7);
+            let (roll, recovered_pitch, yaw) = quaternion.to_euler();
+            assert!((recovered_pitch - pitch).abs() < 1e-2);
+            assert!(same_rotation(&Quaternion::from_euler(roll, recovered_pitch, yaw), &quaternion));
+        }
+    }
+
+
+    proptest! {
+        #[test]
+        fn rotations_are_unit(q in rotation()) {
+            prop_assert!((q.norm() - 1.0).abs() < EPSILON);
+        }
+
+        #[test]
+        fn normalize_gives_unit_length(q in quaternion()) {
+            prop_assume!(q.norm() > 0.1);
+            prop_assert!((q.normalize().norm() - 1.0).abs() < EPSILON);
+        }
+
+        #[test]
+        fn inverse_is_the_multiplicative_inverse(q in quaternion()) {
+            prop_assume!(q.norm() > 0.1);
+            let product = q * q.inverse();
+            prop_assert!((product.scalar - 1.0).abs() < EPSILON);
+            prop_assert!(product.vector.norm() < EPSILON);
+            prop_assert!(same_rotation(&(q.inverse() * q), &Quaternion::new()));
+        }
+
+        #[test]
+        fn division_undoes_multiplication(a in quaternion(), b in quaternion()) {
+            prop_assume!(b.norm() > 0.1);
+            let quotient = (a * b) / b;
+            prop_assert!((quotient - a).norm() < EPSILON * (1.0 + a.norm()));
+            prop_assert!(((a * 2.0) / 2.0 - a).norm() < EPSILON);
+        }
+
+        #[test]
+        fn negation_and_subtraction(a in quaternion(), b in quaternion()) {
+            prop_assert_eq!(a - b, a + (-b));
+            prop_assert_eq!(-(-a), a);
+            prop_assert!((a - a).norm() == 0.0);
+        }
+
+        #[test]
+        fn negated_rotations_rotate_the_same(q in rotation(), p in vector()) {
+            prop_assert!((q.rotate(p) - (-q).rotate(p)).norm() < EPSILON * (1.0 + p.norm()));
+        }
+
+        #[test]
+        fn rotation_keeps_length(q in rotation(), p in vector()) {
+            prop_assert!((q.rotate(p).norm() - p.norm()).abs() < EPSILON * (1.0 + p.norm()));
+        }
+
+        #[test]
+        fn rotation_composes_by_multiplication(a in rotation(), b in rotation(), p in vector()) {
+            let composed = (a * b).rotate(p);
+            prop_assert!((composed - a.rotate(b.rotate(p))).norm() < EPSILON * (1.0 + p.norm()));
+        }
+
+        #[test]
+        fn matrix_rotates_like_the_quaternion(q in rotation(), p in vector()) {
+            let rotated = q.to_matrix() * p.extend(1.0);
+            prop_assert!((rotated.truncate() - q.rotate(p)).norm() < EPSILON * (1.0 + p.norm()));
+            prop_assert!((rotated.w - 1.0).abs() < EPSILON);
+        }
+
+        #[test]
+        fn matrix_round_trip(q in rotation()) {
+            prop_assert!(same_rotation(&Quaternion::from_matrix4x4(&q.to_matrix()), &q));
+        }
+
+        #[test]
+        fn axis_rotation_round_trip(angle in 0.01f32..PI, axis in axis()) {
+            let (recovered_angle, recovered_axis) = Quaternion::from_axis_rotation(angle, axis).to_axis_rotation();
+            prop_assert!((recovered_angle - angle).abs() < 1e-3);
+            prop_assert!((recovered_axis - axis.normalize()).norm() < 1e-3);
+        }
+
+        #[test]
+        fn euler_round_trip(roll in angle(), pitch in -1.5f32..1.5, yaw in angle()) {
+            let quaternion = Quaternion::from_euler(roll, pitch, yaw);
+            let (r, p, y) = quaternion.to_euler();
+            prop_assert!(same_rotation(&Quaternion::from_euler(r, p, y), &quaternion));
+            prop_assert!((p - pitch).abs() < 1e-3);
+        }
+
+        #[test]
+        fn slerp_hits_the_endpoints(a in rotation(), b in rotation()) {
+            prop_assert!(same_rotation(&a.slerp(&b, 0.0), &a));
+            prop_assert!(same_rotation(&a.slerp(&b, 1.0), &b));
+        }
+
+        #[test]
+        fn slerp_turns_at_a_constant_rate(a in rotation(), b in rotation(), t in 0.0f32..1.0) {
+            // The angle from a is proportional to t, and the rest of the way to b is the remainder.
+            let angle_between = |p: &Quaternion, q: &Quaternion| 2.0 * p.dot(q).abs().min(1.0).acos();
+            let total = angle_between(&a, &b);
+            let q = a.slerp(&b, t);
+            prop_assert!((q.norm() - 1.0).abs() < EPSILON);
+            prop_assert!((angle_between(&a, &q) - t * total).abs() < 2e-2);
+            prop_assert!((angle_between(&q, &b) - (1.0 - t) * total).abs() < 2e-2);
+        }
+
+        #[test]
+        fn look_rotation_looks_forward(forward in axis(), up in axis()) {
+            let forward = forward.normalize();
+            prop_assume!(forward.cross(&up.normalize()).norm() > 0.1);
+
+            let q = Quaternion::look_rotation(forward, up);
+            let rotated_up = q.rotate(Vector3::new(0.0, 1.0, 0.0));
+            prop_assert!((q.rotate(Vector3::new(0.0, 0.0, 1.0)) - forward).norm() < EPSILON);
+            prop_assert!(rotated_up.dot(&forward).abs() < EPSILON);
+            prop_assert!(rotated_up.dot(&up) > 0.0);
+        }
+
+        #[test]
+        fn nalgebra_round_trip(q in quaternion()) {
+            prop_assert_eq!(Quaternion::from(Quat::from(q)), q);
+        }
+
+        #[test]
+        fn nalgebra_rotates_the_same(q in rotation(), p in vector()) {
+            let unit = UnitQuaternion::from(q);
+            prop_assert!((Vector3::from(unit * nalgebra::Vector3::from(p)) - q.rotate(p)).norm() < EPSILON * (1.0 + p.norm()));
+            prop_assert!(same_rotation(&Quaternion::from(unit), &q));
+            prop_assert_eq!(Mat4::from(q), Mat4::from(q.to_matrix()));
+        }
     }
-}
\ No newline at end of file
+}
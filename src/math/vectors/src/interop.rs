@@ -0,0 +1,45 @@
+use super::{Matrix3, Matrix4, Real, Vector2, Vector3, Vector4};
+
+
+macro_rules! impl_vector_conversions {
+    ($Vector:ident, $size:literal) => {
+        impl From<nalgebra::$Vector<Real>> for $Vector {
+            fn from(vector: nalgebra::$Vector<Real>) -> Self {
+                let mut array = [0.0; $size];
+                array.copy_from_slice(vector.as_slice());
+                Self::from_array(array)
+            }
+        }
+
+        impl From<$Vector> for nalgebra::$Vector<Real> {
+            fn from(vector: $Vector) -> Self {
+                Self::from_column_slice(&vector.to_array())
+            }
+        }
+    };
+}
+
+macro_rules! impl_matrix_conversions {
+    ($Matrix:ident, $size:literal) => {
+        // Both are stored column by column.
+        impl From<nalgebra::$Matrix<Real>> for $Matrix {
+            fn from(matrix: nalgebra::$Matrix<Real>) -> Self {
+                let mut array = [0.0; $size];
+                array.copy_from_slice(matrix.as_slice());
+                Self::from_column_array(array)
+            }
+        }
+
+        impl From<$Matrix> for nalgebra::$Matrix<Real> {
+            fn from(matrix: $Matrix) -> Self {
+                Self::from_column_slice(&matrix.to_column_array())
+            }
+        }
+    };
+}
+
+impl_vector_conversions!(Vector2, 2);
+impl_vector_conversions!(Vector3, 3);
+impl_vector_conversions!(Vector4, 4);
+impl_matrix_conversions!(Matrix3, 9);
+impl_matrix_conversions!(Matrix4, 16);
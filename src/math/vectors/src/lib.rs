@@ -1,8 +1,29 @@
-#![feature(const_fn)]
-#![feature(const_fn_floating_point_arithmetic)]
+//! Small vectors and square matrices over `Real` for graphics.
+//!
+//! All the arithmetic is available as `const fn`s (`plus`, `scale`, `mul_matrix`, ...), so
+//! vertices, colors and transforms can be built in constants. The operators are implemented on
+//! top of them. Anything needing a square root or trigonometry (`normalize`, `perspective`,
+//! `look_at`, ...) isn't const. The types aren't generic, since trait methods can't be called in
+//! const contexts.
+//!
+//! The `nalgebra` feature (on by default) adds conversions to and from the nalgebra types.
 
-type Real = f32;
+pub type Real = f32;
 
+#[macro_use]
+mod macros;
+
+pub mod vector2;
 pub mod vector3;
-pub use vector3::*;
+pub mod vector4;
+pub mod matrix3;
+pub mod matrix4;
 
+#[cfg(feature = "nalgebra")]
+mod interop;
+
+pub use vector2::*;
+pub use vector3::*;
+pub use vector4::*;
+pub use matrix3::*;
+pub use matrix4::*;
@@ -0,0 +1,332 @@
+/// Implements the element-wise arithmetic of a vector type as `const fn`s, and the operators on
+/// top of them, given the index of each field.
+macro_rules! impl_vector {
+    ($Vector:ident, $size:literal, { $($index:literal => $field:ident),+ }) => {
+        impl $Vector {
+            pub const ZERO: Self = Self::splat(0.0);
+            pub const SIZE: usize = $size;
+
+            pub const fn new($($field: Real),+) -> Self {
+                Self { $($field),+ }
+            }
+
+            pub const fn splat(value: Real) -> Self {
+                Self { $($field: value),+ }
+            }
+
+            pub const fn from_array(array: [Real; $size]) -> Self {
+                Self { $($field: array[$index]),+ }
+            }
+
+            pub const fn to_array(&self) -> [Real; $size] {
+                [$(self.$field),+]
+            }
+
+            pub const fn plus(self, rhs: Self) -> Self {
+                Self { $($field: self.$field + rhs.$field),+ }
+            }
+
+            pub const fn minus(self, rhs: Self) -> Self {
+                Self { $($field: self.$field - rhs.$field),+ }
+            }
+
+            pub const fn scale(self, factor: Real) -> Self {
+                Self { $($field: self.$field * factor),+ }
+            }
+
+            pub const fn divide(self, divisor: Real) -> Self {
+                Self { $($field: self.$field / divisor),+ }
+            }
+
+            pub const fn negated(self) -> Self {
+                Self { $($field: -self.$field),+ }
+            }
+
+            pub const fn component_mul(self, rhs: Self) -> Self {
+                Self { $($field: self.$field * rhs.$field),+ }
+            }
+
+            pub const fn component_div(self, rhs: Self) -> Self {
+                Self { $($field: self.$field / rhs.$field),+ }
+            }
+
+            /// Whether no component is infinite or NaN.
+            pub fn is_finite(&self) -> bool {
+                true $(&& self.$field.is_finite())+
+            }
+
+            pub const fn dot(&self, other: &Self) -> Real {
+                0.0 $(+ self.$field * other.$field)+
+            }
+
+            pub const fn norm_squared(&self) -> Real {
+                self.dot(self)
+            }
+
+            pub fn norm(&self) -> Real {
+                Real::sqrt(self.norm_squared())
+            }
+
+            pub fn normalize(&self) -> Self {
+                self.divide(self.norm())
+            }
+
+            /// `self` at t = 0 and `other` at t = 1, both exactly.
+            pub const fn lerp(self, other: Self, t: Real) -> Self {
+                self.scale(1.0 - t).plus(other.scale(t))
+            }
+
+            /// The smaller of each pair of components.
+            pub const fn component_min(self, rhs: Self) -> Self {
+                Self { $($field: if rhs.$field < self.$field { rhs.$field } else { self.$field }),+ }
+            }
+
+            /// The larger of each pair of components.
+            pub const fn component_max(self, rhs: Self) -> Self {
+                Self { $($field: if rhs.$field > self.$field { rhs.$field } else { self.$field }),+ }
+            }
+
+            /// The largest component, e.g. the longest side of a box.
+            pub const fn max_component(&self) -> Real {
+                let mut largest = Real::NEG_INFINITY;
+                $(if self.$field > largest { largest = self.$field; })+
+                largest
+            }
+        }
+
+        impl Add for $Vector {
+            type Output = $Vector;
+
+            fn add(self, rhs: Self) -> Self::Output {
+                self.plus(rhs)
+            }
+        }
+
+        impl Add<Real> for $Vector {
+            type Output = $Vector;
+
+            fn add(self, rhs: Real) -> Self::Output {
+                self.plus(Self::splat(rhs))
+            }
+        }
+
+        impl Sub for $Vector {
+            type Output = $Vector;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                self.minus(rhs)
+            }
+        }
+
+        impl Sub<Real> for $Vector {
+            type Output = $Vector;
+
+            fn sub(self, rhs: Real) -> Self::Output {
+                self.minus(Self::splat(rhs))
+            }
+        }
+
+        impl Mul<Real> for $Vector {
+            type Output = $Vector;
+
+            fn mul(self, rhs: Real) -> Self::Output {
+                self.scale(rhs)
+            }
+        }
+
+        impl Mul<$Vector> for Real {
+            type Output = $Vector;
+
+            fn mul(self, rhs: $Vector) -> Self::Output {
+                rhs.scale(self)
+            }
+        }
+
+        impl Div<Real> for $Vector {
+            type Output = $Vector;
+
+            fn div(self, rhs: Real) -> Self::Output {
+                self.divide(rhs)
+            }
+        }
+
+        impl Neg for $Vector {
+            type Output = $Vector;
+
+            fn neg(self) -> Self::Output {
+                self.negated()
+            }
+        }
+
+        impl AddAssign for $Vector {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = self.plus(rhs);
+            }
+        }
+
+        impl SubAssign for $Vector {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = self.minus(rhs);
+            }
+        }
+
+        impl MulAssign<Real> for $Vector {
+            fn mul_assign(&mut self, rhs: Real) {
+                *self = self.scale(rhs);
+            }
+        }
+
+        impl DivAssign<Real> for $Vector {
+            fn div_assign(&mut self, rhs: Real) {
+                *self = self.divide(rhs);
+            }
+        }
+
+        impl Index<usize> for $Vector {
+            type Output = Real;
+
+            fn index(&self, index: usize) -> &Self::Output {
+                match index {
+                    $($index => &self.$field,)+
+                    _ => panic!("Index {} out of bounds for {}", index, stringify!($Vector)),
+                }
+            }
+        }
+
+        impl IndexMut<usize> for $Vector {
+            fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+                match index {
+                    $($index => &mut self.$field,)+
+                    _ => panic!("Index {} out of bounds for {}", index, stringify!($Vector)),
+                }
+            }
+        }
+
+        impl From<[Real; $size]> for $Vector {
+            fn from(array: [Real; $size]) -> Self {
+                Self::from_array(array)
+            }
+        }
+
+        impl From<$Vector> for [Real; $size] {
+            fn from(vector: $Vector) -> Self {
+                vector.to_array()
+            }
+        }
+    };
+}
+
+
+/// Implements the operators of a square matrix type on top of its `const fn`s.
+macro_rules! impl_matrix_operators {
+    ($Matrix:ident, $Vector:ident) => {
+        impl Add for $Matrix {
+            type Output = $Matrix;
+
+            fn add(self, rhs: Self) -> Self::Output {
+                self.plus(&rhs)
+            }
+        }
+
+        impl Sub for $Matrix {
+            type Output = $Matrix;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                self.minus(&rhs)
+            }
+        }
+
+        impl Neg for $Matrix {
+            type Output = $Matrix;
+
+            fn neg(self) -> Self::Output {
+                self.scale(-1.0)
+            }
+        }
+
+        impl Mul for $Matrix {
+            type Output = $Matrix;
+
+            fn mul(self, rhs: Self) -> Self::Output {
+                self.mul_matrix(&rhs)
+            }
+        }
+
+        impl Mul<$Vector> for $Matrix {
+            type Output = $Vector;
+
+            fn mul(self, rhs: $Vector) -> Self::Output {
+                self.mul_vector(rhs)
+            }
+        }
+
+        impl Mul<Real> for $Matrix {
+            type Output = $Matrix;
+
+            fn mul(self, rhs: Real) -> Self::Output {
+                self.scale(rhs)
+            }
+        }
+
+        impl Mul<$Matrix> for Real {
+            type Output = $Matrix;
+
+            fn mul(self, rhs: $Matrix) -> Self::Output {
+                rhs.scale(self)
+            }
+        }
+
+        impl Div<Real> for $Matrix {
+            type Output = $Matrix;
+
+            fn div(self, rhs: Real) -> Self::Output {
+                self.scale(1.0 / rhs)
+            }
+        }
+
+        impl AddAssign for $Matrix {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = self.plus(&rhs);
+            }
+        }
+
+        impl SubAssign for $Matrix {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = self.minus(&rhs);
+            }
+        }
+
+        impl MulAssign for $Matrix {
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = self.mul_matrix(&rhs);
+            }
+        }
+
+        impl MulAssign<Real> for $Matrix {
+            fn mul_assign(&mut self, rhs: Real) {
+                *self = self.scale(rhs);
+            }
+        }
+
+        /// `matrix[(row, column)]`.
+        impl Index<(usize, usize)> for $Matrix {
+            type Output = Real;
+
+            fn index(&self, (row, column): (usize, usize)) -> &Self::Output {
+                &self.columns[column][row]
+            }
+        }
+
+        impl IndexMut<(usize, usize)> for $Matrix {
+            fn index_mut(&mut self, (row, column): (usize, usize)) -> &mut Self::Output {
+                &mut self.columns[column][row]
+            }
+        }
+
+        impl Default for $Matrix {
+            fn default() -> Self {
+                Self::IDENTITY
+            }
+        }
+    };
+}
@@ -0,0 +1,121 @@
+use std::ops::{Add, AddAssign, Div, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
+use super::{Real, Vector2, Vector3};
+
+
+/// A column-major 3x3 matrix, e.g. a rotation or the normal matrix of a transform.
+#[repr(C)]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Matrix3 {
+    pub columns: [Vector3; 3],
+}
+
+impl Matrix3 {
+    pub const IDENTITY: Self = Self::from_rows([
+        [1.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [0.0, 0.0, 1.0],
+    ]);
+    pub const ZERO: Self = Self::from_columns([Vector3::ZERO; 3]);
+
+    pub const fn from_columns(columns: [Vector3; 3]) -> Self {
+        Self { columns }
+    }
+
+    /// Written out the way the matrix reads, row by row.
+    pub const fn from_rows(rows: [[Real; 3]; 3]) -> Self {
+        Self { columns: [
+            Vector3::new(rows[0][0], rows[1][0], rows[2][0]),
+            Vector3::new(rows[0][1], rows[1][1], rows[2][1]),
+            Vector3::new(rows[0][2], rows[1][2], rows[2][2]),
+        ]}
+    }
+
+    /// From the 9 values column by column, as OpenGL expects them.
+    pub const fn from_column_array(array: [Real; 9]) -> Self {
+        Self { columns: [
+            Vector3::new(array[0], array[1], array[2]),
+            Vector3::new(array[3], array[4], array[5]),
+            Vector3::new(array[6], array[7], array[8]),
+        ]}
+    }
+
+    pub const fn to_column_array(&self) -> [Real; 9] {
+        let [a, b, c] = self.columns;
+        [a.x, a.y, a.z, b.x, b.y, b.z, c.x, c.y, c.z]
+    }
+
+    pub const fn from_diagonal(diagonal: Vector3) -> Self {
+        Self::from_rows([
+            [diagonal.x, 0.0, 0.0],
+            [0.0, diagonal.y, 0.0],
+            [0.0, 0.0, diagonal.z],
+        ])
+    }
+
+    pub const fn get(&self, row: usize, column: usize) -> Real {
+        self.columns[column].to_array()[row]
+    }
+
+    pub const fn column(&self, column: usize) -> Vector3 {
+        self.columns[column]
+    }
+
+    pub const fn row(&self, row: usize) -> Vector3 {
+        Vector3::new(self.get(row, 0), self.get(row, 1), self.get(row, 2))
+    }
+
+    pub const fn transpose(&self) -> Self {
+        Self::from_columns([self.row(0), self.row(1), self.row(2)])
+    }
+
+    pub const fn plus(&self, rhs: &Self) -> Self {
+        let [a, b, c] = self.columns;
+        let [d, e, f] = rhs.columns;
+        Self::from_columns([a.plus(d), b.plus(e), c.plus(f)])
+    }
+
+    pub const fn minus(&self, rhs: &Self) -> Self {
+        self.plus(&rhs.scale(-1.0))
+    }
+
+    pub const fn scale(&self, factor: Real) -> Self {
+        let [a, b, c] = self.columns;
+        Self::from_columns([a.scale(factor), b.scale(factor), c.scale(factor)])
+    }
+
+    pub const fn mul_vector(&self, vector: Vector3) -> Vector3 {
+        let [a, b, c] = self.columns;
+        a.scale(vector.x).plus(b.scale(vector.y)).plus(c.scale(vector.z))
+    }
+
+    pub const fn mul_matrix(&self, rhs: &Self) -> Self {
+        let [a, b, c] = rhs.columns;
+        Self::from_columns([self.mul_vector(a), self.mul_vector(b), self.mul_vector(c)])
+    }
+
+    pub const fn determinant(&self) -> Real {
+        let [a, b, c] = self.columns;
+        a.dot(&b.cross(&c))
+    }
+
+    /// The inverse, or `None` if the matrix is singular.
+    pub const fn inverse(&self) -> Option<Self> {
+        let determinant = self.determinant();
+        if determinant == 0.0 {
+            return None;
+        }
+
+        // The rows of the inverse are the cross products of the columns over the determinant.
+        let [a, b, c] = self.columns;
+        let rows = Self::from_columns([b.cross(&c), c.cross(&a), a.cross(&b)]);
+        Some(rows.transpose().scale(1.0 / determinant))
+    }
+
+    /// The transform of a point in 2D homogeneous coordinates.
+    pub const fn transform_point(&self, point: Vector2) -> Vector2 {
+        let result = self.mul_vector(point.extend(1.0));
+        result.truncate().divide(result.z)
+    }
+}
+
+impl_matrix_operators!(Matrix3, Vector3);
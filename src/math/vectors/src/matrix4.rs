@@ -0,0 +1,284 @@
+use std::ops::{Add, AddAssign, Div, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
+use super::{Real, Matrix3, Vector3, Vector4};
+
+
+/// A column-major 4x4 matrix for transforms in homogeneous coordinates. The projections and
+/// `look_at` follow OpenGL (and nalgebra-glm): right-handed, with depth in [-1, 1].
+#[repr(C)]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Matrix4 {
+    pub columns: [Vector4; 4],
+}
+
+impl Matrix4 {
+    pub const IDENTITY: Self = Self::from_rows([
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+    pub const ZERO: Self = Self::from_columns([Vector4::ZERO; 4]);
+
+    pub const fn from_columns(columns: [Vector4; 4]) -> Self {
+        Self { columns }
+    }
+
+    /// Written out the way the matrix reads, row by row.
+    pub const fn from_rows(rows: [[Real; 4]; 4]) -> Self {
+        Self { columns: [
+            Vector4::new(rows[0][0], rows[1][0], rows[2][0], rows[3][0]),
+            Vector4::new(rows[0][1], rows[1][1], rows[2][1], rows[3][1]),
+            Vector4::new(rows[0][2], rows[1][2], rows[2][2], rows[3][2]),
+            Vector4::new(rows[0][3], rows[1][3], rows[2][3], rows[3][3]),
+        ]}
+    }
+
+    /// From the 16 values column by column, as OpenGL expects them.
+    pub const fn from_column_array(array: [Real; 16]) -> Self {
+        Self { columns: [
+            Vector4::new(array[0],  array[1],  array[2],  array[3]),
+            Vector4::new(array[4],  array[5],  array[6],  array[7]),
+            Vector4::new(array[8],  array[9],  array[10], array[11]),
+            Vector4::new(array[12], array[13], array[14], array[15]),
+        ]}
+    }
+
+    /// The values column by column, e.g. for `uniform_matrix4fv`.
+    pub const fn to_column_array(&self) -> [Real; 16] {
+        let [a, b, c, d] = self.columns;
+        [a.x, a.y, a.z, a.w, b.x, b.y, b.z, b.w, c.x, c.y, c.z, c.w, d.x, d.y, d.z, d.w]
+    }
+
+    pub const fn to_rows(&self) -> [[Real; 4]; 4] {
+        [self.row(0).to_array(), self.row(1).to_array(), self.row(2).to_array(), self.row(3).to_array()]
+    }
+
+    pub const fn from_diagonal(diagonal: Vector4) -> Self {
+        Self::from_rows([
+            [diagonal.x, 0.0, 0.0, 0.0],
+            [0.0, diagonal.y, 0.0, 0.0],
+            [0.0, 0.0, diagonal.z, 0.0],
+            [0.0, 0.0, 0.0, diagonal.w],
+        ])
+    }
+
+    /// The rotation (or other linear map) in the upper left, without translation.
+    pub const fn from_matrix3(matrix: &Matrix3) -> Self {
+        let [a, b, c] = matrix.columns;
+        Self::from_columns([a.extend(0.0), b.extend(0.0), c.extend(0.0), Vector4::new(0.0, 0.0, 0.0, 1.0)])
+    }
+
+    pub const fn upper_left(&self) -> Matrix3 {
+        let [a, b, c, _] = self.columns;
+        Matrix3::from_columns([a.truncate(), b.truncate(), c.truncate()])
+    }
+
+    pub const fn get(&self, row: usize, column: usize) -> Real {
+        self.columns[column].to_array()[row]
+    }
+
+    pub const fn column(&self, column: usize) -> Vector4 {
+        self.columns[column]
+    }
+
+    pub const fn row(&self, row: usize) -> Vector4 {
+        Vector4::new(self.get(row, 0), self.get(row, 1), self.get(row, 2), self.get(row, 3))
+    }
+
+    pub const fn transpose(&self) -> Self {
+        Self::from_columns([self.row(0), self.row(1), self.row(2), self.row(3)])
+    }
+
+    pub const fn plus(&self, rhs: &Self) -> Self {
+        let [a, b, c, d] = self.columns;
+        let [e, f, g, h] = rhs.columns;
+        Self::from_columns([a.plus(e), b.plus(f), c.plus(g), d.plus(h)])
+    }
+
+    pub const fn minus(&self, rhs: &Self) -> Self {
+        self.plus(&rhs.scale(-1.0))
+    }
+
+    pub const fn scale(&self, factor: Real) -> Self {
+        let [a, b, c, d] = self.columns;
+        Self::from_columns([a.scale(factor), b.scale(factor), c.scale(factor), d.scale(factor)])
+    }
+
+    pub const fn mul_vector(&self, vector: Vector4) -> Vector4 {
+        let [a, b, c, d] = self.columns;
+        a.scale(vector.x).plus(b.scale(vector.y)).plus(c.scale(vector.z)).plus(d.scale(vector.w))
+    }
+
+    pub const fn mul_matrix(&self, rhs: &Self) -> Self {
+        let [a, b, c, d] = rhs.columns;
+        Self::from_columns([self.mul_vector(a), self.mul_vector(b), self.mul_vector(c), self.mul_vector(d)])
+    }
+
+    /// Transforms a point, dividing by w (for projections).
+    pub const fn transform_point(&self, point: Vector3) -> Vector3 {
+        self.mul_vector(point.extend(1.0)).to_cartesian()
+    }
+
+    /// Transforms a direction, which translations don't affect.
+    pub const fn transform_vector(&self, vector: Vector3) -> Vector3 {
+        self.mul_vector(vector.extend(0.0)).truncate()
+    }
+
+    // The 2x2 sub-determinants of the upper two and lower two rows, shared by the determinant and
+    // the inverse (Laplace expansion along those rows).
+    const fn sub_determinants(&self) -> ([Real; 6], [Real; 6]) {
+        let [[a00, a01, a02, a03], [a10, a11, a12, a13], [a20, a21, a22, a23], [a30, a31, a32, a33]] = self.to_rows();
+        let upper = [
+            a00 * a11 - a10 * a01,
+            a00 * a12 - a10 * a02,
+            a00 * a13 - a10 * a03,
+            a01 * a12 - a11 * a02,
+            a01 * a13 - a11 * a03,
+            a02 * a13 - a12 * a03,
+        ];
+        let lower = [
+            a20 * a31 - a30 * a21,
+            a20 * a32 - a30 * a22,
+            a20 * a33 - a30 * a23,
+            a21 * a32 - a31 * a22,
+            a21 * a33 - a31 * a23,
+            a22 * a33 - a32 * a23,
+        ];
+        (upper, lower)
+    }
+
+    pub const fn determinant(&self) -> Real {
+        let ([s0, s1, s2, s3, s4, s5], [c0, c1, c2, c3, c4, c5]) = self.sub_determinants();
+        s0 * c5 - s1 * c4 + s2 * c3 + s3 * c2 - s4 * c1 + s5 * c0
+    }
+
+    /// The inverse, or `None` if the matrix is singular.
+    pub const fn inverse(&self) -> Option<Self> {
+        let [[a00, a01, a02, a03], [a10, a11, a12, a13], [a20, a21, a22, a23], [a30, a31, a32, a33]] = self.to_rows();
+        let ([s0, s1, s2, s3, s4, s5], [c0, c1, c2, c3, c4, c5]) = self.sub_determinants();
+
+        let determinant = s0 * c5 - s1 * c4 + s2 * c3 + s3 * c2 - s4 * c1 + s5 * c0;
+        if determinant == 0.0 {
+            return None;
+        }
+
+        let adjugate = Self::from_rows([
+            [
+                 a11 * c5 - a12 * c4 + a13 * c3,
+                -a01 * c5 + a02 * c4 - a03 * c3,
+                 a31 * s5 - a32 * s4 + a33 * s3,
+                -a21 * s5 + a22 * s4 - a23 * s3,
+            ],
+            [
+                -a10 * c5 + a12 * c2 - a13 * c1,
+                 a00 * c5 - a02 * c2 + a03 * c1,
+                -a30 * s5 + a32 * s2 - a33 * s1,
+                 a20 * s5 - a22 * s2 + a23 * s1,
+            ],
+            [
+                 a10 * c4 - a11 * c2 + a13 * c0,
+                -a00 * c4 + a01 * c2 - a03 * c0,
+                 a30 * s4 - a31 * s2 + a33 * s0,
+                -a20 * s4 + a21 * s2 - a23 * s0,
+            ],
+            [
+                -a10 * c3 + a11 * c1 - a12 * c0,
+                 a00 * c3 - a01 * c1 + a02 * c0,
+                -a30 * s3 + a31 * s1 - a32 * s0,
+                 a20 * s3 - a21 * s1 + a22 * s0,
+            ],
+        ]);
+        Some(adjugate.scale(1.0 / determinant))
+    }
+
+    /// The inverse transpose of the upper left, which transforms normals.
+    pub const fn normal_matrix(&self) -> Option<Matrix3> {
+        match self.upper_left().inverse() {
+            Some(inverse) => Some(inverse.transpose()),
+            None => None,
+        }
+    }
+
+
+    // ---- TRANSFORMS ----
+    pub const fn translation(offset: Vector3) -> Self {
+        Self::from_rows([
+            [1.0, 0.0, 0.0, offset.x],
+            [0.0, 1.0, 0.0, offset.y],
+            [0.0, 0.0, 1.0, offset.z],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub const fn scaling(factors: Vector3) -> Self {
+        Self::from_diagonal(factors.extend(1.0))
+    }
+
+    /// The counterclockwise rotation by `angle` around x, looking against the axis.
+    pub fn rotation_x(angle: Real) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::from_rows([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, cos, -sin, 0.0],
+            [0.0, sin, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn rotation_y(angle: Real) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::from_rows([
+            [cos, 0.0, sin, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [-sin, 0.0, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn rotation_z(angle: Real) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::from_rows([
+            [cos, -sin, 0.0, 0.0],
+            [sin, cos, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// The perspective projection with the vertical field of view `fov_y` (in radians), like
+    /// `glm::perspective`.
+    pub fn perspective(aspect_ratio: Real, fov_y: Real, near: Real, far: Real) -> Self {
+        let focal_length = 1.0 / (fov_y / 2.0).tan();
+        Self::from_rows([
+            [focal_length / aspect_ratio, 0.0, 0.0, 0.0],
+            [0.0, focal_length, 0.0, 0.0],
+            [0.0, 0.0, (far + near) / (near - far), 2.0 * far * near / (near - far)],
+            [0.0, 0.0, -1.0, 0.0],
+        ])
+    }
+
+    /// Maps the box to [-1, 1] in every direction, like `glm::ortho`.
+    pub const fn orthographic(left: Real, right: Real, bottom: Real, top: Real, near: Real, far: Real) -> Self {
+        Self::from_rows([
+            [2.0 / (right - left), 0.0, 0.0, -(right + left) / (right - left)],
+            [0.0, 2.0 / (top - bottom), 0.0, -(top + bottom) / (top - bottom)],
+            [0.0, 0.0, -2.0 / (far - near), -(far + near) / (far - near)],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// The view matrix of a camera at `eye` looking at `center`, like `glm::look_at`.
+    pub fn look_at(eye: Vector3, center: Vector3, up: Vector3) -> Self {
+        let forward = (center - eye).normalize();
+        let right   = forward.cross(&up).normalize();
+        let up      = right.cross(&forward);
+        Self::from_rows([
+            [right.x, right.y, right.z, -right.dot(&eye)],
+            [up.x, up.y, up.z, -up.dot(&eye)],
+            [-forward.x, -forward.y, -forward.z, forward.dot(&eye)],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+}
+
+impl_matrix_operators!(Matrix4, Vector4);
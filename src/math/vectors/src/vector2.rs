@@ -0,0 +1,28 @@
+use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
+use super::{Real, Vector3};
+
+
+#[repr(C)]
+#[derive(Debug, PartialOrd, PartialEq, Copy, Clone, Default)]
+pub struct Vector2 {
+    pub x: Real,
+    pub y: Real,
+}
+
+impl_vector!(Vector2, 2, { 0 => x, 1 => y });
+
+impl Vector2 {
+    /// The z-component of the cross product of (x, y, 0) and (other.x, other.y, 0).
+    pub const fn perp_dot(&self, other: &Self) -> Real {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// Rotated a quarter turn counterclockwise.
+    pub const fn perpendicular(&self) -> Self {
+        Self { x: -self.y, y: self.x }
+    }
+
+    pub const fn extend(&self, z: Real) -> Vector3 {
+        Vector3::new(self.x, self.y, z)
+    }
+}
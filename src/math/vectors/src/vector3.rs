@@ -1,7 +1,8 @@
-use std::ops::{Mul, Add, Sub};
-use super::Real;
+use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
+use super::{Real, Vector2, Vector4};
 
 
+#[repr(C)]
 #[derive(Debug, PartialOrd, PartialEq, Copy, Clone, Default)]
 pub struct Vector3 {
     pub x: Real,
@@ -9,36 +10,13 @@ pub struct Vector3 {
     pub z: Real,
 }
 
-impl Vector3  {
-    pub const fn new(x: Real, y: Real, z: Real) -> Self {
-        Self { x, y, z }
-    }
+impl_vector!(Vector3, 3, { 0 => x, 1 => y, 2 => z });
 
+impl Vector3  {
     pub const fn default() -> Self {
         Self { x: 0.0, y: 0.0, z: 0.0 }
     }
 
-    pub fn norm(&self) -> Real {
-        Real::sqrt(self.x * self.x + self.y * self.y + self.z * self.z)
-    }
-
-    pub const fn norm_squared(&self) -> Real {
-        self.x * self.x + self.y * self.y + self.z * self.z
-    }
-
-    pub fn normalize(&self) -> Self {
-        let norm = self.norm();
-        Self {
-            x: self.x / norm,
-            y: self.y / norm,
-            z: self.z / norm,
-        }
-    }
-
-    pub const fn dot(&self, other: &Self) -> Real {
-        self.x * other.x + self.y * other.y + self.z * other.z
-    }
-
     pub const fn cross(&self, other: &Self) -> Self {
         Self {
             x: self.y * other.z - self.z * other.y,
@@ -46,65 +24,12 @@ impl Vector3  {
             z: self.x * other.y - self.y * other.x,
         }
     }
-}
-
 
-impl Add for Vector3 {
-    type Output = Vector3;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Self {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-            z: self.z + rhs.z,
-        }
+    pub const fn truncate(&self) -> Vector2 {
+        Vector2::new(self.x, self.y)
     }
-}
-
-impl Add<Real> for Vector3 {
-    type Output = Vector3;
 
-    fn add(self, rhs: Real) -> Self::Output {
-        Self {
-            x: self.x + rhs,
-            y: self.y + rhs,
-            z: self.z + rhs,
-        }
-    }
-}
-
-impl Sub for Vector3 {
-    type Output = Vector3;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        Self {
-            x: self.x - rhs.x,
-            y: self.y - rhs.y,
-            z: self.z - rhs.z,
-        }
+    pub const fn extend(&self, w: Real) -> Vector4 {
+        Vector4::new(self.x, self.y, self.z, w)
     }
 }
-
-impl Sub<Real> for Vector3 {
-    type Output = Vector3;
-
-    fn sub(self, rhs: Real) -> Self::Output {
-        Self {
-            x: self.x - rhs,
-            y: self.y - rhs,
-            z: self.z - rhs,
-        }
-    }
-}
-
-impl Mul<Real> for Vector3 {
-    type Output = Vector3;
-
-    fn mul(self, rhs: Real) -> Self::Output {
-        Self {
-            x: self.x * rhs,
-            y: self.y * rhs,
-            z: self.z * rhs,
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,26 @@
+use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
+use super::{Real, Vector3};
+
+
+/// Homogeneous coordinates, or a column of a `Matrix4`.
+#[repr(C)]
+#[derive(Debug, PartialOrd, PartialEq, Copy, Clone, Default)]
+pub struct Vector4 {
+    pub x: Real,
+    pub y: Real,
+    pub z: Real,
+    pub w: Real,
+}
+
+impl_vector!(Vector4, 4, { 0 => x, 1 => y, 2 => z, 3 => w });
+
+impl Vector4 {
+    pub const fn truncate(&self) -> Vector3 {
+        Vector3::new(self.x, self.y, self.z)
+    }
+
+    /// The point (x, y, z) / w.
+    pub const fn to_cartesian(&self) -> Vector3 {
+        self.truncate().divide(self.w)
+    }
+}
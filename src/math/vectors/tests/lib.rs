@@ -2,7 +2,7 @@
 
 #[cfg(test)]
 mod tests {
-    use vectors::Vector3;
+    use vectors::{Vector2, Vector3, Vector4};
 
     pub const STATIC_TEST: [Vector3; 3] = [
         Vector3::new(1.0, 0.0, 0.0),
@@ -84,4 +84,77 @@ mod tests {
         assert_eq!(u.cross(&v), Vector3::new(0.0, 0.0, 1.0));
     }
 
-}
\ No newline at end of file
+    pub const CONST_ARITHMETIC: Vector3 = Vector3::new(1.0, 2.0, 3.0)
+        .plus(Vector3::splat(1.0))
+        .scale(2.0)
+        .cross(&Vector3::new(0.0, 0.0, 1.0));
+
+    #[test]
+    pub fn const_arithmetic() {
+        assert_eq!(CONST_ARITHMETIC, Vector3::new(6.0, -4.0, 0.0));
+    }
+
+    #[test]
+    pub fn assign_operators() {
+        let mut v = Vector3::new(1.0, 2.0, 3.0);
+        v += Vector3::splat(1.0);
+        v *= 3.0;
+        v -= Vector3::new(6.0, 9.0, 12.0);
+        v /= 2.0;
+        assert_eq!(v, Vector3::ZERO);
+        assert_eq!(-Vector3::new(1.0, -2.0, 3.0), Vector3::new(-1.0, 2.0, -3.0));
+        assert_eq!(2.0 * Vector3::new(1.0, 2.0, 3.0), Vector3::new(2.0, 4.0, 6.0));
+    }
+
+    #[test]
+    pub fn index() {
+        let mut v = Vector4::new(1.0, 2.0, 3.0, 4.0);
+        v[3] = 5.0;
+        assert_eq!([v[0], v[1], v[2], v[3]], [1.0, 2.0, 3.0, 5.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn index_out_of_bounds() {
+        let _ = Vector2::new(1.0, 2.0)[2];
+    }
+
+    #[test]
+    pub fn vector2() {
+        let u = Vector2::new(1.0, 0.0);
+        assert_eq!(u.perpendicular(), Vector2::new(0.0, 1.0));
+        assert_eq!(u.perp_dot(&u.perpendicular()), 1.0);
+        assert_eq!(u.lerp(Vector2::new(3.0, 2.0), 0.5), Vector2::new(2.0, 1.0));
+        assert_eq!(u.extend(2.0), Vector3::new(1.0, 0.0, 2.0));
+    }
+
+    #[test]
+    pub fn vector4() {
+        let v = Vector4::new(2.0, 4.0, 6.0, 2.0);
+        assert_eq!(v.to_cartesian(), Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(v.truncate().extend(2.0), v);
+        assert_eq!(Vector4::from([2.0, 4.0, 6.0, 2.0]), v);
+        assert_eq!(v.norm_squared(), 60.0);
+    }
+
+    #[test]
+    pub fn component_min_and_max() {
+        let a = Vector3::new(1.0, 5.0, -2.0);
+        let b = Vector3::new(3.0, 2.0, -4.0);
+        assert_eq!(a.component_min(b), Vector3::new(1.0, 2.0, -4.0));
+        assert_eq!(a.component_max(b), Vector3::new(3.0, 5.0, -2.0));
+        assert_eq!(a.max_component(), 5.0);
+        assert_eq!(Vector2::new(-3.0, -1.0).max_component(), -1.0);
+    }
+
+    #[test]
+    pub fn nalgebra_conversions() {
+        let v = Vector3::new(1.0, 2.0, 3.0);
+        let n = nalgebra::Vector3::from(v);
+        assert_eq!(n, nalgebra::Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(Vector3::from(n), v);
+        assert_eq!(Vector2::from(nalgebra::Vector2::new(1.0, 2.0)), Vector2::new(1.0, 2.0));
+        assert_eq!(nalgebra::Vector4::from(Vector4::new(1.0, 2.0, 3.0, 4.0)), nalgebra::Vector4::new(1.0, 2.0, 3.0, 4.0));
+    }
+
+}
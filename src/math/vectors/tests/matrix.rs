@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod tests {
+    use vectors::{Matrix3, Matrix4, Vector3, Vector4};
+    use nalgebra_glm as glm;
+    use proptest::prelude::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    pub const TRANSFORM: Matrix4 = Matrix4::translation(Vector3::new(1.0, 2.0, 3.0))
+        .mul_matrix(&Matrix4::scaling(Vector3::new(2.0, 2.0, 2.0)));
+    pub const INVERSE: Option<Matrix4> = TRANSFORM.inverse();
+
+    fn assert_matrix_close(a: Matrix4, b: Matrix4) {
+        let difference = a - b;
+        let largest = difference.to_column_array().iter().fold(0.0f32, |largest, x| largest.max(x.abs()));
+        assert!(largest < EPSILON, "{:?} != {:?}", a, b);
+    }
+
+    fn assert_close_to_glm(a: Matrix4, b: glm::Mat4) {
+        assert_matrix_close(a, Matrix4::from(b));
+    }
+
+
+    // ---- STRATEGIES ----
+    fn matrix4() -> impl Strategy<Value = Matrix4> {
+        proptest::array::uniform16(-10.0f32..10.0).prop_map(Matrix4::from_column_array)
+    }
+
+    fn matrix3() -> impl Strategy<Value = Matrix3> {
+        proptest::array::uniform9(-10.0f32..10.0).prop_map(Matrix3::from_column_array)
+    }
+
+    fn vector3() -> impl Strategy<Value = Vector3> {
+        (-10.0f32..10.0, -10.0f32..10.0, -10.0f32..10.0).prop_map(|(x, y, z)| Vector3::new(x, y, z))
+    }
+
+
+    #[test]
+    pub fn const_transforms() {
+        assert_eq!(TRANSFORM.transform_point(Vector3::new(1.0, 1.0, 1.0)), Vector3::new(3.0, 4.0, 5.0));
+        assert_eq!(TRANSFORM.transform_vector(Vector3::new(1.0, 1.0, 1.0)), Vector3::new(2.0, 2.0, 2.0));
+        assert_eq!(INVERSE.unwrap().transform_point(Vector3::new(3.0, 4.0, 5.0)), Vector3::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    pub fn layout_is_column_major() {
+        let matrix = Matrix4::translation(Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(matrix[(0, 3)], 1.0);
+        assert_eq!(matrix.get(2, 3), 3.0);
+        assert_eq!(&matrix.to_column_array()[12..15], &[1.0, 2.0, 3.0]);
+        assert_eq!(matrix.column(3), Vector4::new(1.0, 2.0, 3.0, 1.0));
+        assert_eq!(matrix.row(1), Vector4::new(0.0, 1.0, 0.0, 2.0));
+        assert_eq!(Matrix4::default(), Matrix4::IDENTITY);
+    }
+
+    #[test]
+    pub fn singular_matrices_have_no_inverse() {
+        assert_eq!(Matrix4::ZERO.inverse(), None);
+        assert_eq!(Matrix3::from_rows([[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [0.0, 0.0, 1.0]]).inverse(), None);
+    }
+
+    #[test]
+    pub fn perspective_matches_glm() {
+        assert_close_to_glm(Matrix4::perspective(1.5, 0.8, 0.1, 100.0), glm::perspective(1.5, 0.8, 0.1, 100.0));
+    }
+
+    #[test]
+    pub fn orthographic_matches_glm() {
+        assert_close_to_glm(
+            Matrix4::orthographic(-2.0, 3.0, -1.0, 4.0, 0.5, 20.0),
+            glm::ortho(-2.0, 3.0, -1.0, 4.0, 0.5, 20.0),
+        );
+    }
+
+    #[test]
+    pub fn rotations_are_counterclockwise() {
+        let quarter = std::f32::consts::FRAC_PI_2;
+        let rotated = Matrix4::rotation_z(quarter).transform_vector(Vector3::new(1.0, 0.0, 0.0));
+        assert!((rotated - Vector3::new(0.0, 1.0, 0.0)).norm() < EPSILON);
+        assert_close_to_glm(Matrix4::rotation_x(0.3), glm::rotation(0.3, &glm::vec3(1.0, 0.0, 0.0)));
+        assert_close_to_glm(Matrix4::rotation_y(0.3), glm::rotation(0.3, &glm::vec3(0.0, 1.0, 0.0)));
+    }
+
+
+    proptest! {
+        #[test]
+        fn inverse_is_the_multiplicative_inverse(m in matrix4()) {
+            prop_assume!(m.determinant().abs() > 1.0);
+            let inverse = m.inverse().unwrap();
+            let glm_inverse = glm::inverse(&glm::Mat4::from(m));
+            let scale = 1.0 + glm_inverse.abs().max();
+            prop_assert!((m * inverse - Matrix4::IDENTITY).to_column_array().iter().all(|x| x.abs() < EPSILON * scale * 10.0));
+            prop_assert!((inverse - Matrix4::from(glm_inverse)).to_column_array().iter().all(|x| x.abs() < EPSILON * scale));
+        }
+
+        #[test]
+        fn matrix3_inverse(m in matrix3()) {
+            prop_assume!(m.determinant().abs() > 1.0);
+            let product = m * m.inverse().unwrap();
+            prop_assert!((product - Matrix3::IDENTITY).to_column_array().iter().all(|x| x.abs() < 1e-3));
+        }
+
+        #[test]
+        fn multiplication_matches_nalgebra(a in matrix4(), b in matrix4(), v in vector3()) {
+            let product = glm::Mat4::from(a) * glm::Mat4::from(b);
+            prop_assert_eq!(a * b, Matrix4::from(product));
+            let v = v.extend(1.0);
+            prop_assert_eq!(a * v, Vector4::from(glm::Mat4::from(a) * glm::Vec4::from(v)));
+        }
+
+        #[test]
+        fn transpose_matches_nalgebra(m in matrix4()) {
+            prop_assert_eq!(m.transpose(), Matrix4::from(glm::Mat4::from(m).transpose()));
+            prop_assert_eq!(m.transpose().transpose(), m);
+            prop_assert!((m.determinant() - glm::Mat4::from(m).determinant()).abs() < 1e-2 * (1.0 + m.determinant().abs()));
+        }
+
+        #[test]
+        fn look_at_matches_glm(eye in vector3(), center in vector3()) {
+            let up = Vector3::new(0.0, 1.0, 0.0);
+            let forward = center - eye;
+            prop_assume!(forward.norm() > 0.1 && forward.normalize().cross(&up).norm() > 0.1);
+
+            let view = Matrix4::look_at(eye, center, up);
+            let expected = glm::look_at(&eye.into(), &center.into(), &up.into());
+            prop_assert!((view - Matrix4::from(expected)).to_column_array().iter().all(|x| x.abs() < EPSILON * 100.0));
+
+            // The camera sits at the origin looking down -z.
+            prop_assert!(view.transform_point(eye).norm() < EPSILON * 100.0);
+            let looked_at = view.transform_point(center);
+            prop_assert!(looked_at.truncate().norm() < EPSILON * 100.0 && looked_at.z < 0.0);
+        }
+    }
+}
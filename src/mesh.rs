@@ -1,11 +1,12 @@
 use wasm_bindgen::JsCast;
 use web_sys::*;
 use web_sys::WebGl2RenderingContext as GL;
-use js_sys::{WebAssembly, Float32Array, Uint16Array};
-use glm::{Mat4, Vec2, Vec3, value_ptr};
+use js_sys::{WebAssembly, Float32Array, Uint16Array, Uint32Array};
+use crate::math::{Matrix4, Vector2, Vector3};
 
 use crate::materials::{Material, DrawConfig};
 use crate::camera::Camera;
+use crate::utils::{SurfaceData, math_to_world};
 
 
 pub const VERTICES_2D_RECTANGLE: [f32; 12] = [
@@ -87,8 +88,8 @@ pub struct Model {
     pub mesh: Mesh,
     pub draw_config: DrawConfig,
 
-    pub position: Vec3,
-    pub rotation: Vec3,
+    pub position: Vector3,
+    pub rotation: Vector3,
 }
 
 
@@ -100,8 +101,8 @@ impl Model {
         Self {
             mesh,
             draw_config,
-            position: Vec3::new(0.0, 0.0, 0.0),
-            rotation: Vec3::new(0.0, 0.0, 0.0),
+            position: Vector3::new(0.0, 0.0, 0.0),
+            rotation: Vector3::new(0.0, 0.0, 0.0),
         }
     }
 
@@ -109,15 +110,72 @@ impl Model {
         gl.bind_vertex_array(Some(&self.mesh.id));
     }
 
+    /// Frees the buffers and the shader program of the model.
+    pub fn delete(&self, gl: &GL) {
+        self.mesh.delete(gl);
+        self.draw_config.material.delete(gl);
+    }
+
     pub fn draw(&self, gl: &GL) {
         let config = &self.draw_config;
         let stop   = self.mesh.count + (self.draw_config.stop + 1);
         assert!(stop <= self.mesh.count, "Stop must be negative but with smaller cardinality than count.");
 
-        if self.mesh.is_indexed {
-            gl.draw_elements_with_i32(config.draw_mode, stop, GL::UNSIGNED_SHORT, 0);
-        } else {
-            gl.draw_arrays(config.draw_mode, config.start, stop);
+        match DrawCall::for_mesh(self.mesh.is_instanced, self.mesh.instance_count) {
+            DrawCall::Nothing => {},
+            DrawCall::Instanced(count) if self.mesh.is_indexed => gl.draw_elements_instanced_with_i32(config.draw_mode, stop, self.mesh.index_type, 0, count),
+            DrawCall::Instanced(count) => gl.draw_arrays_instanced(config.draw_mode, config.start, stop, count),
+            DrawCall::Single if self.mesh.is_indexed => gl.draw_elements_with_i32(config.draw_mode, stop, self.mesh.index_type, 0),
+            DrawCall::Single => gl.draw_arrays(config.draw_mode, config.start, stop),
+        }
+    }
+}
+
+
+/// How `Model::draw` draws a mesh.
+#[derive(Debug, PartialEq)]
+enum DrawCall {
+    Single,
+    Instanced(i32),
+    /// An instanced mesh without instances, e.g. arrows of a gradient that's undefined at every
+    /// sample. Drawing it once instead would put a glyph at the origin, read from an empty buffer.
+    Nothing,
+}
+
+impl DrawCall {
+    fn for_mesh(is_instanced: bool, instance_count: i32) -> Self {
+        match (is_instanced, instance_count) {
+            (false, _) => DrawCall::Single,
+            (true, 0)  => DrawCall::Nothing,
+            (true, count) => DrawCall::Instanced(count),
+        }
+    }
+}
+
+
+/// Indices for an indexed mesh. WebGL2 supports 32-bit indices natively, but 16-bit ones are
+/// half the size, so prefer them whenever the vertex count allows it.
+pub enum IndexBuffer {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl IndexBuffer {
+    pub fn len(&self) -> usize {
+        match self {
+            IndexBuffer::U16(indices) => indices.len(),
+            IndexBuffer::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn gl_type(&self) -> u32 {
+        match self {
+            IndexBuffer::U16(_) => GL::UNSIGNED_SHORT,
+            IndexBuffer::U32(_) => GL::UNSIGNED_INT,
         }
     }
 }
@@ -128,6 +186,10 @@ pub struct Mesh {
     pub count: i32,
     pub is_indexed: bool,
     pub is_static: bool,
+    pub index_type: u32,  // UNSIGNED_SHORT or UNSIGNED_INT, only used if indexed.
+    pub is_instanced: bool,   // Set by `set_instances`, even if there are no instances.
+    pub instance_count: i32,  // 0 unless per-instance attributes are set with `set_instances`.
+    pub buffers: Vec<WebGlBuffer>,
 }
 
 impl Mesh {
@@ -148,33 +210,183 @@ impl Mesh {
         gl.bind_buffer(GL::ARRAY_BUFFER, Some(&vbo));
         gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices_array, GL::STATIC_DRAW);
 
+        let component_count = Self::enable_interleaved_attributes(gl, has_texture_coordinates, has_normals);
+
+        Ok(Self {
+            id: vao,
+            count: (vertices.len() / component_count) as i32,
+            is_indexed: false,
+            is_static: true,
+            index_type: GL::UNSIGNED_SHORT,
+            is_instanced: false,
+            instance_count: 0,
+            buffers: vec![vbo],
+        })
+    }
+
+    /// Same layout as `from_f32_array_3d` (position, texture coordinates, normals), but indexed.
+    pub fn from_interleaved_with_indices_3d(gl: &GL, vertices: &[f32], indices: &IndexBuffer, has_texture_coordinates: bool, has_normals: bool) -> Result<Self, String> {
+        assert_ne!(has_texture_coordinates as i32 - has_normals as i32, -1);
+
+        let vertices_array = array_to_wasm_array!(f32, Float32Array, vertices);
+
+        // Create vertex array buffer to store vertex buffers and element buffers.
+        let vao = gl.create_vertex_array().ok_or("[WEBGL2 - VAO ERROR]: Unable to create VAO.")?;
+        gl.bind_vertex_array(Some(&vao));
+
+        // Create an element buffer to put our data into video memory.
+        let ebo = gl.create_buffer().ok_or("[WEBGL2 - VAO ERROR]: Unable to create EBO.")?;
+        gl.bind_buffer(GL::ELEMENT_ARRAY_BUFFER, Some(&ebo));
+        match indices {
+            IndexBuffer::U16(indices) => {
+                let indices_array = array_to_wasm_array!(u16, Uint16Array, indices);
+                gl.buffer_data_with_array_buffer_view(GL::ELEMENT_ARRAY_BUFFER, &indices_array, GL::STATIC_DRAW);
+            },
+            IndexBuffer::U32(indices) => {
+                let indices_array = array_to_wasm_array!(u32, Uint32Array, indices);
+                gl.buffer_data_with_array_buffer_view(GL::ELEMENT_ARRAY_BUFFER, &indices_array, GL::STATIC_DRAW);
+            },
+        }
+
+        // Create vertex buffer to put our data into video memory.
+        let vbo = gl.create_buffer().ok_or("[WEBGL2 - VAO ERROR]: Unable to create VBO.")?;
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&vbo));
+        gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices_array, GL::STATIC_DRAW);
+
+        Self::enable_interleaved_attributes(gl, has_texture_coordinates, has_normals);
+
+        Ok(Self {
+            id: vao,
+            count: indices.len() as i32,
+            is_indexed: true,
+            is_static: true,
+            index_type: indices.gl_type(),
+            is_instanced: false,
+            instance_count: 0,
+            buffers: vec![ebo, vbo],
+        })
+    }
+
+    /// Tells OpenGL the format of the bound vertex buffer and returns the number of floats per vertex.
+    fn enable_interleaved_attributes(gl: &GL, has_texture_coordinates: bool, has_normals: bool) -> usize {
         let size_of_float = 4;
         let stride = (3 + 2 * has_texture_coordinates as i32 + 3 * has_normals as i32) * size_of_float;
 
         // Tell OpenGL the data's format.
         gl.enable_vertex_attrib_array(0);
-        gl.vertex_attrib_pointer_with_i32(0, 3 as i32, GL::FLOAT, false, stride, 0);
+        gl.vertex_attrib_pointer_with_i32(0, 3, GL::FLOAT, false, stride, 0);
         let mut component_count = 3;
 
         if has_texture_coordinates {
             gl.enable_vertex_attrib_array(1);
-            gl.vertex_attrib_pointer_with_i32(1, 2 as i32, GL::FLOAT, false, stride, 3 * size_of_float);
+            gl.vertex_attrib_pointer_with_i32(1, 2, GL::FLOAT, false, stride, 3 * size_of_float);
             component_count += 2;
             if has_normals {
                 gl.enable_vertex_attrib_array(2);
-                gl.vertex_attrib_pointer_with_i32(2, 3 as i32, GL::FLOAT, false, stride, (3 + 2) * size_of_float);
+                gl.vertex_attrib_pointer_with_i32(2, 3, GL::FLOAT, false, stride, (3 + 2) * size_of_float);
                 component_count += 3;
             }
         }
 
+        component_count
+    }
+
+    pub fn from_surface_data(gl: &GL, surface: &SurfaceData) -> Result<Self, String> {
+        Self::from_interleaved_with_indices_3d(gl, &surface.vertices, &surface.index_buffer(), true, true)
+    }
+
+    /// Non-indexed vertices with a position and a color (6 floats per vertex), as used by
+    /// `VertexColorMaterial`.
+    pub fn from_positions_and_colors_3d(gl: &GL, vertices: &[f32]) -> Result<Self, String> {
+        assert_eq!(vertices.len() % 6, 0);
+
+        let vertices_array = array_to_wasm_array!(f32, Float32Array, vertices);
+
+        // Create vertex array buffer to store vertex buffers and element buffers.
+        let vao = gl.create_vertex_array().ok_or("[WEBGL2 - VAO ERROR]: Unable to create VAO.")?;
+        gl.bind_vertex_array(Some(&vao));
+
+        // Create vertex buffer to put our data into video memory.
+        let vbo = gl.create_buffer().ok_or("[WEBGL2 - VAO ERROR]: Unable to create VBO.")?;
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&vbo));
+        gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices_array, GL::STATIC_DRAW);
+
+        // Tell OpenGL the data's format.
+        let size_of_float = 4;
+        gl.enable_vertex_attrib_array(0);
+        gl.vertex_attrib_pointer_with_i32(0, 3, GL::FLOAT, false, 6 * size_of_float, 0);
+        gl.enable_vertex_attrib_array(1);
+        gl.vertex_attrib_pointer_with_i32(1, 3, GL::FLOAT, false, 6 * size_of_float, 3 * size_of_float);
+
         Ok(Self {
             id: vao,
-            count: (vertices.len() / component_count) as i32,
+            count: (vertices.len() / 6) as i32,
             is_indexed: false,
             is_static: true,
+            index_type: GL::UNSIGNED_SHORT,
+            is_instanced: false,
+            instance_count: 0,
+            buffers: vec![vbo],
         })
     }
 
+    /// Draws the mesh once per instance, with per-instance attributes read from `data`. Each
+    /// instance has `attribute_sizes.len()` attributes, bound to consecutive locations starting at
+    /// `first_location`; e.g. `(3, &[3, 3, 3])` gives three `vec3`s at locations 3, 4 and 5.
+    pub fn set_instances(&mut self, gl: &GL, data: &[f32], first_location: u32, attribute_sizes: &[i32]) -> Result<(), String> {
+        let components: i32 = attribute_sizes.iter().sum();
+        assert_eq!(data.len() % components as usize, 0);
+
+        let data_array = array_to_wasm_array!(f32, Float32Array, data);
+
+        gl.bind_vertex_array(Some(&self.id));
+
+        let ibo = gl.create_buffer().ok_or("[WEBGL2 - VAO ERROR]: Unable to create instance buffer.")?;
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&ibo));
+        gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &data_array, GL::STATIC_DRAW);
+
+        let size_of_float = 4;
+        let mut offset = 0;
+        for (i, &size) in attribute_sizes.iter().enumerate() {
+            let location = first_location + i as u32;
+            gl.enable_vertex_attrib_array(location);
+            gl.vertex_attrib_pointer_with_i32(location, size, GL::FLOAT, false, components * size_of_float, offset * size_of_float);
+            gl.vertex_attrib_divisor(location, 1);
+            offset += size;
+        }
+
+        gl.bind_vertex_array(None);
+
+        self.is_instanced   = true;
+        self.instance_count = data.len() as i32 / components;
+        self.buffers.push(ibo);
+        Ok(())
+    }
+
+    /// Replaces the per-instance attributes given to `set_instances`, e.g. to animate them. `data`
+    /// must have the same layout and number of instances.
+    pub fn update_instances(&self, gl: &GL, data: &[f32]) {
+        assert!(self.is_instanced, "The mesh has no instances to update.");
+        if self.instance_count == 0 {
+            assert!(data.is_empty());
+            return;
+        }
+        assert_eq!(data.len() % self.instance_count as usize, 0);
+
+        // `set_instances` pushes the instance buffer last.
+        let data_array = array_to_wasm_array!(f32, Float32Array, data);
+        gl.bind_buffer(GL::ARRAY_BUFFER, self.buffers.last());
+        gl.buffer_sub_data_with_i32_and_array_buffer_view(GL::ARRAY_BUFFER, 0, &data_array);
+    }
+
+    /// Frees the video memory of the mesh. The mesh must not be drawn afterwards.
+    pub fn delete(&self, gl: &GL) {
+        for buffer in &self.buffers {
+            gl.delete_buffer(Some(buffer));
+        }
+        gl.delete_vertex_array(Some(&self.id));
+    }
+
     pub fn from_f32_array_with_indices_3d(gl: &GL, vertices: &[f32], indices: &[u16]) -> Result<Self, String> {
         let vertices_array = array_to_wasm_array!(f32, Float32Array, vertices);
         let indices_array  = array_to_wasm_array!(u16, Uint16Array, indices);
@@ -203,8 +415,239 @@ impl Mesh {
             count: indices.len() as i32,
             is_indexed: true,
             is_static: true,
+            index_type: GL::UNSIGNED_SHORT,
+            is_instanced: false,
+            instance_count: 0,
+            buffers: vec![ebo, vbo],
         })
     }
 }
 
 
+
+
+/// Builds the mesh of a parametric surface r(u, v) = (x(u, v), y(u, v), z(u, v)), given in math
+/// coordinates (z is up).
+///
+/// Normals are r_u x r_v, with the partial derivatives taken by central differences. A parameter
+/// marked as periodic (e.g. the angle of a torus) uses central differences across the ends of its
+/// range too, and if the two ends meet, the last row of vertices is snapped onto the first so the
+/// seam has neither cracks nor a shading discontinuity.
+pub struct ParametricSurface<F: Fn(f32, f32) -> [f32; 3]> {
+    function: F,
+    u_range: (f32, f32),
+    v_range: (f32, f32),
+    u_resolution: usize,
+    v_resolution: usize,
+    u_periodic: bool,
+    v_periodic: bool,
+}
+
+impl<F: Fn(f32, f32) -> [f32; 3]> ParametricSurface<F> {
+    const SEAM_TOLERANCE: f32 = 1e-4;
+
+    pub fn new(function: F) -> Self {
+        Self {
+            function,
+            u_range: (0.0, 1.0),
+            v_range: (0.0, 1.0),
+            u_resolution: 32,
+            v_resolution: 32,
+            u_periodic: false,
+            v_periodic: false,
+        }
+    }
+
+    pub fn u_range(mut self, min: f32, max: f32) -> Self {
+        self.u_range = (min, max);
+        self
+    }
+
+    pub fn v_range(mut self, min: f32, max: f32) -> Self {
+        self.v_range = (min, max);
+        self
+    }
+
+    pub fn resolution(mut self, u_resolution: usize, v_resolution: usize) -> Self {
+        self.u_resolution = u_resolution;
+        self.v_resolution = v_resolution;
+        self
+    }
+
+    pub fn periodic(mut self, u_periodic: bool, v_periodic: bool) -> Self {
+        self.u_periodic = u_periodic;
+        self.v_periodic = v_periodic;
+        self
+    }
+
+    fn parameter(range: (f32, f32), resolution: usize, i: usize) -> f32 {
+        range.0 + (range.1 - range.0) * (i as f32 / resolution as f32)
+    }
+
+    /// Partial derivative along one parameter. Uses one-sided differences at the ends of a
+    /// non-periodic range, since the function might not be defined outside of it.
+    fn derivative(&self, u: f32, v: f32, along_u: bool) -> [f32; 3] {
+        let (range, resolution, periodic) = if along_u {
+            (self.u_range, self.u_resolution, self.u_periodic)
+        } else {
+            (self.v_range, self.v_resolution, self.v_periodic)
+        };
+        let t = if along_u { u } else { v };
+        let h = (range.1 - range.0) / resolution as f32 * 0.5;
+
+        let (lo, hi) = if periodic {
+            (t - h, t + h)
+        } else {
+            // The range may be reversed, e.g. to flip the orientation of the surface.
+            let (min, max) = (range.0.min(range.1), range.0.max(range.1));
+            ((t - h).max(min).min(max), (t + h).min(max).max(min))
+        };
+        let (a, b) = if along_u {
+            ((self.function)(lo, v), (self.function)(hi, v))
+        } else {
+            ((self.function)(u, lo), (self.function)(u, hi))
+        };
+
+        // Differentiate along the direction the grid is laid out in, so the normal agrees with
+        // the winding of the triangles even if the range is reversed.
+        let d = (hi - lo) * (range.1 - range.0).signum();
+        [(b[0] - a[0]) / d, (b[1] - a[1]) / d, (b[2] - a[2]) / d]
+    }
+
+    fn normal(&self, u: f32, v: f32) -> [f32; 3] {
+        let a = self.derivative(u, v, true);
+        let b = self.derivative(u, v, false);
+        let n = [
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ];
+        // Relative to the size of the derivatives, since near a pole one of them almost vanishes
+        // and the direction of the cross product is mostly rounding errors.
+        let scale  = (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt() + (b[0] * b[0] + b[1] * b[1] + b[2] * b[2]).sqrt();
+        let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+        if length > 1e-5 * scale * scale && length.is_finite() {
+            [n[0] / length, n[1] / length, n[2] / length]
+        } else {
+            [0.0, 0.0, 0.0]
+        }
+    }
+
+    pub fn build(&self) -> SurfaceData {
+        assert!(self.u_resolution > 0 && self.v_resolution > 0, "Resolution must be at least one quad in each direction.");
+
+        let columns = self.u_resolution + 1;
+        let rows    = self.v_resolution + 1;
+
+        let mut positions = Vec::with_capacity(columns * rows);
+        let mut normals   = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                let u = Self::parameter(self.u_range, self.u_resolution, column);
+                let v = Self::parameter(self.v_range, self.v_resolution, row);
+                positions.push((self.function)(u, v));
+                normals.push(self.normal(u, v));
+            }
+        }
+
+        self.weld_seams(&mut positions, &mut normals, columns, rows);
+        Self::fix_degenerate_normals(&mut normals, columns, rows);
+
+        let mut vertices = Vec::with_capacity(columns * rows * SurfaceData::COMPONENTS_PER_VERTEX);
+        let mut is_finite = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                let [x, y, z]    = positions[row * columns + column];
+                let [nx, ny, nz] = normals[row * columns + column];
+                let valid = x.is_finite() && y.is_finite() && z.is_finite();
+
+                let position = if valid { math_to_world(x, y, z) } else { [0.0, 0.0, 0.0] };
+                vertices.extend_from_slice(&position);
+                vertices.push(column as f32 / self.u_resolution as f32);
+                vertices.push(row    as f32 / self.v_resolution as f32);
+                vertices.extend_from_slice(&math_to_world(nx, ny, nz));
+                is_finite.push(valid);
+            }
+        }
+
+        let mut indices = Vec::with_capacity(self.u_resolution * self.v_resolution * 6);
+        for row in 0..self.v_resolution {
+            for column in 0..self.u_resolution {
+                let a = (row * columns + column) as u32;
+                let b = a + 1;
+                let c = a + columns as u32;
+                let d = c + 1;
+
+                if [a, b, c, d].iter().all(|&i| is_finite[i as usize]) {
+                    indices.extend_from_slice(&[a, b, d, a, d, c]);
+                }
+            }
+        }
+
+        SurfaceData { vertices, indices }
+    }
+
+    /// Snaps the last column/row onto the first for periodic parameters where the ends coincide.
+    /// They don't always, e.g. a Möbius strip meets itself flipped, and is left as is.
+    fn weld_seams(&self, positions: &mut [[f32; 3]], normals: &mut [[f32; 3]], columns: usize, rows: usize) {
+        let coincide = |a: [f32; 3], b: [f32; 3]| {
+            (0..3).all(|i| (a[i] - b[i]).abs() <= Self::SEAM_TOLERANCE * (1.0 + a[i].abs()))
+        };
+
+        if self.u_periodic {
+            for row in 0..rows {
+                let first = row * columns;
+                let last  = first + columns - 1;
+                if coincide(positions[first], positions[last]) {
+                    positions[last] = positions[first];
+                    normals[last]   = normals[first];
+                }
+            }
+        }
+        if self.v_periodic {
+            for column in 0..columns {
+                let first = column;
+                let last  = (rows - 1) * columns + column;
+                if coincide(positions[first], positions[last]) {
+                    positions[last] = positions[first];
+                    normals[last]   = normals[first];
+                }
+            }
+        }
+    }
+
+    /// Where r_u x r_v vanishes, e.g. at the poles of a sphere, borrow the normal of the closest
+    /// vertex along v (or u) that has one.
+    fn fix_degenerate_normals(normals: &mut [[f32; 3]], columns: usize, rows: usize) {
+        let is_zero = |n: &[f32; 3]| n[0] == 0.0 && n[1] == 0.0 && n[2] == 0.0;
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let index = row * columns + column;
+                if !is_zero(&normals[index]) {
+                    continue;
+                }
+
+                let along_v = (1..rows).flat_map(|d| {
+                    let up   = if row + d < rows { Some((row + d) * columns + column) } else { None };
+                    let down = if row >= d { Some((row - d) * columns + column) } else { None };
+                    up.into_iter().chain(down)
+                });
+                let along_u = (1..columns).flat_map(|d| {
+                    let right = if column + d < columns { Some(row * columns + column + d) } else { None };
+                    let left  = if column >= d { Some(row * columns + column - d) } else { None };
+                    right.into_iter().chain(left)
+                });
+
+                if let Some(other) = along_v.chain(along_u).find(|&i| !is_zero(&normals[i])) {
+                    normals[index] = normals[other];
+                }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+#[path = "tests/mesh.rs"]
+mod tests;
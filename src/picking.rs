@@ -0,0 +1,88 @@
+use crate::math::{Matrix4, Vector3, Vector4};
+
+use crate::utils::SurfaceData;
+
+
+/// A half line from `origin` along `direction`, in world coordinates.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ray {
+    pub origin: Vector3,
+    pub direction: Vector3,
+}
+
+impl Ray {
+    pub fn new(origin: Vector3, direction: Vector3) -> Self {
+        Self { origin, direction: direction.normalize() }
+    }
+
+    /// The ray from the camera through a pixel, where (0, 0) is the top left corner of a `width`
+    /// by `height` canvas. Returns `None` if the camera's matrices can't be inverted.
+    pub fn through_pixel(view: &Matrix4, projection: &Matrix4, x: f32, y: f32, width: f32, height: f32) -> Option<Self> {
+        let inverse = (*projection * *view).inverse()?;
+        let ndc_x = 2.0 * x / width - 1.0;
+        let ndc_y = 1.0 - 2.0 * y / height;
+
+        // The points on the near and far planes that project onto the pixel.
+        let unproject = |ndc_z: f32| {
+            let p = inverse * Vector4::new(ndc_x, ndc_y, ndc_z, 1.0);
+            Vector3::new(p.x, p.y, p.z) / p.w
+        };
+        let near = unproject(-1.0);
+        let far  = unproject(1.0);
+        Some(Self::new(near, far - near))
+    }
+
+    pub fn at(&self, t: f32) -> Vector3 {
+        self.origin + self.direction * t
+    }
+}
+
+
+/// The distance along the ray to the triangle abc, or `None` if it misses, by Möller-Trumbore.
+/// Both sides of the triangle are hit.
+pub fn intersect_triangle(ray: &Ray, a: Vector3, b: Vector3, c: Vector3) -> Option<f32> {
+    const EPSILON: f32 = 1e-7;
+
+    let edge_1 = b - a;
+    let edge_2 = c - a;
+    let p = ray.direction.cross(&edge_2);
+    let determinant = edge_1.dot(&p);
+    if determinant.abs() < EPSILON {
+        return None;  // Parallel to the triangle.
+    }
+
+    let inverse = 1.0 / determinant;
+    let s = ray.origin - a;
+    let u = s.dot(&p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(&edge_1);
+    let v = ray.direction.dot(&q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge_2.dot(&q) * inverse;
+    if t > EPSILON { Some(t) } else { None }
+}
+
+/// The closest point where the ray hits the surface, in world coordinates.
+pub fn pick_surface(ray: &Ray, surface: &SurfaceData) -> Option<Vector3> {
+    let position = |i: u32| {
+        let start = i as usize * SurfaceData::COMPONENTS_PER_VERTEX;
+        Vector3::new(surface.vertices[start], surface.vertices[start + 1], surface.vertices[start + 2])
+    };
+
+    surface.indices
+        .chunks(3)
+        .filter_map(|triangle| intersect_triangle(ray, position(triangle[0]), position(triangle[1]), position(triangle[2])))
+        .fold(None, |closest: Option<f32>, t| Some(closest.map_or(t, |closest| closest.min(t))))
+        .map(|t| ray.at(t))
+}
+
+
+#[cfg(test)]
+#[path = "tests/picking.rs"]
+mod tests;
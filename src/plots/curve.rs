@@ -0,0 +1,124 @@
+use web_sys::WebGl2RenderingContext as GL;
+use crate::math::Vector3;
+
+use crate::errors::ClientError;
+use crate::expression::{Expression, Variable, Variables};
+use crate::curves::{sample_curve, create_polyline, create_tube, FrameKind};
+use crate::materials::{DrawConfig, ShadedMaterial, SingleColorMaterial};
+use crate::mesh::{Mesh, Model, ParametricSurface};
+use crate::utils::math_to_world;
+use super::{PartialModels, check_range, check_resolution, parse_components, check_animation_period};
+
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CurveStyle {
+    Line,
+    Tube { radius: f32, frame: FrameKind },
+}
+
+impl CurveStyle {
+    pub fn from_name(name: &str, radius: f32) -> Result<Self, ClientError> {
+        let style = match name {
+            "line"        => CurveStyle::Line,
+            "tube"        => CurveStyle::Tube { radius, frame: FrameKind::RotationMinimizing },
+            "tube_frenet" => CurveStyle::Tube { radius, frame: FrameKind::Frenet },
+            _ => return Err(ClientError::invalid_argument("style", format!("Expected 'line', 'tube' or 'tube_frenet', got '{}'", name))),
+        };
+        if let CurveStyle::Tube { radius, .. } = style {
+            if !(radius.is_finite() && radius > 0.0) {
+                return Err(ClientError::invalid_argument("radius", format!("Expected a positive tube radius, got {}", radius)));
+            }
+        }
+        Ok(style)
+    }
+}
+
+
+/// The space curve r(t) = (x(t), y(t), z(t)), written as `x, y, z`.
+pub struct CurvePlot {
+    pub source: String,
+    pub components: Vec<Expression>,
+    pub t_range: (f32, f32),
+    pub samples: usize,
+    pub style: CurveStyle,
+    pub animation_period: Option<f32>,  // Seconds for the marker to traverse the curve, if animated.
+}
+
+impl CurvePlot {
+    pub const VARIABLES: [Variable; 1] = [Variable::T];
+    pub const MAX_SAMPLES: usize = 8192;
+    const MARKER_RADIUS: f32 = 0.06;
+
+    pub fn new(source: &str, t_range: (f32, f32), samples: usize, style: CurveStyle, animation_period: Option<f32>) -> Result<Self, ClientError> {
+        check_range("t_range", t_range.0, t_range.1)?;
+        check_resolution("samples", samples, Self::MAX_SAMPLES)?;
+        if samples < 2 {
+            return Err(ClientError::invalid_argument("samples", String::from("A curve needs at least two samples")));
+        }
+        check_animation_period(animation_period)?;
+
+        Ok(Self {
+            source: String::from(source),
+            components: parse_components(source, 3, &Self::VARIABLES)?,
+            t_range,
+            samples,
+            style,
+            animation_period,
+        })
+    }
+
+    pub fn evaluate(&self, t: f32) -> [f32; 3] {
+        let variables = Variables { t, ..Variables::default() };
+        [self.components[0].evaluate(&variables), self.components[1].evaluate(&variables), self.components[2].evaluate(&variables)]
+    }
+
+    pub fn build(&self, gl: &GL, color: Vector3) -> Result<Vec<Model>, ClientError> {
+        let points = sample_curve(|t| self.evaluate(t), self.t_range.0, self.t_range.1, self.samples);
+        if points.len() < 2 {
+            return Err(ClientError::invalid_argument("expression", format!("'{}' is undefined almost everywhere in the interval", self.source)));
+        }
+
+        let mut models = PartialModels::new(gl);
+        models.push(match self.style {
+            CurveStyle::Line => {
+                let mesh = Mesh::from_f32_array_3d(gl, &create_polyline(&points), false, false, true)?;
+                Model::new(mesh, DrawConfig::new(GL::LINE_STRIP, 0, -1, Box::new(SingleColorMaterial::with_color(gl, color)?)))
+            },
+            CurveStyle::Tube { radius, frame } => {
+                let mesh = Mesh::from_surface_data(gl, &create_tube(&points, radius, 12, frame))?;
+                Model::new(mesh, DrawConfig::new(GL::TRIANGLES, 0, -1, Box::new(ShadedMaterial::new(gl, color)?)))
+            },
+        });
+
+        if self.animation_period.is_some() {
+            let sphere = ParametricSurface::new(|u, v| {
+                    let r = Self::MARKER_RADIUS;
+                    [r * u.cos() * v.sin(), r * u.sin() * v.sin(), r * v.cos()]
+                })
+                .u_range(0.0, 2.0 * std::f32::consts::PI)
+                .v_range(std::f32::consts::PI, 0.0)
+                .resolution(16, 8)
+                .periodic(true, false)
+                .build();
+            let mesh = Mesh::from_surface_data(gl, &sphere)?;
+            let mut marker = Model::new(mesh, DrawConfig::new(GL::TRIANGLES, 0, -1, Box::new(ShadedMaterial::new(gl, Vector3::new(1.0, 1.0, 1.0))?)));
+            let [x, y, z] = self.evaluate(self.t_range.0);
+            marker.position = math_to_world(x, y, z).into();
+            models.push(marker);
+        }
+
+        Ok(models.finish())
+    }
+
+    /// Moves the marker along the curve; `time` is in seconds.
+    pub fn animate(&self, models: &mut [Model], time: f32) {
+        if let (Some(period), Some(marker)) = (self.animation_period, models.get_mut(1)) {
+            let fraction = (time / period).fract();
+            let t = self.t_range.0 + fraction * (self.t_range.1 - self.t_range.0);
+            let [x, y, z] = self.evaluate(t);
+            if x.is_finite() && y.is_finite() && z.is_finite() {
+                marker.position = math_to_world(x, y, z).into();
+            }
+        }
+    }
+}
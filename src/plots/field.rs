@@ -0,0 +1,137 @@
+use web_sys::WebGl2RenderingContext as GL;
+use crate::math::Vector3;
+
+use crate::errors::ClientError;
+use crate::expression::{Expression, Variable, Variables};
+use crate::fields::{self, VectorField};
+use crate::implicit::BoundingBox;
+use crate::materials::{DrawConfig, ArrowMaterial, VertexColorMaterial};
+use crate::mesh::{Mesh, Model};
+use super::{PartialModels, check_range, parse_components};
+
+
+/// Where the values of a vector field plot come from.
+#[derive(Clone)]
+pub enum FieldSource {
+    /// `P, Q, R` in x, y and z.
+    Expressions { source: String, components: Vec<Expression> },
+    /// A JS function (x, y, z) => [P, Q, R].
+    Callback(js_sys::Function),
+}
+
+impl FieldSource {
+    pub fn expressions(source: &str) -> Result<Self, ClientError> {
+        Ok(FieldSource::Expressions {
+            source: String::from(source),
+            components: parse_components(source, 3, &VectorFieldPlot::VARIABLES)?,
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            FieldSource::Expressions { source, .. } => source.clone(),
+            FieldSource::Callback(_) => String::from("<callback>"),
+        }
+    }
+
+    pub fn to_vector_field(&self) -> VectorField {
+        match self {
+            FieldSource::Expressions { components, .. } => {
+                let components = components.clone();
+                VectorField::new(move |p| {
+                    let variables = Variables::xyz(p.x, p.y, p.z);
+                    Vector3::new(components[0].evaluate(&variables), components[1].evaluate(&variables), components[2].evaluate(&variables))
+                })
+            },
+            FieldSource::Callback(callback) => {
+                let callback = callback.clone();
+                VectorField::new(move |p| call_field_callback(&callback, p))
+            },
+        }
+    }
+}
+
+/// Calls a JS field callback, treating exceptions and anything but three numbers as undefined.
+fn call_field_callback(callback: &js_sys::Function, p: Vector3) -> Vector3 {
+    let undefined = Vector3::new(f32::NAN, f32::NAN, f32::NAN);
+    let result = match callback.call3(&wasm_bindgen::JsValue::NULL, &p.x.into(), &p.y.into(), &p.z.into()) {
+        Ok(result) => result,
+        Err(_) => return undefined,
+    };
+    if !js_sys::Array::is_array(&result) {
+        return undefined;
+    }
+    let array = js_sys::Array::from(&result);
+    if array.length() != 3 {
+        return undefined;
+    }
+    let component = |i: u32| array.get(i).as_f64().map(|c| c as f32).unwrap_or(f32::NAN);
+    Vector3::new(component(0), component(1), component(2))
+}
+
+
+/// A vector field F(x, y, z) drawn as arrows on a lattice, plus streamlines through the seeds.
+/// Arrows and streamlines are colored by |F|.
+pub struct VectorFieldPlot {
+    pub field: FieldSource,
+    pub bounds: BoundingBox,
+    pub density: usize,  // Arrows along each side of the box; 0 for streamlines only.
+    pub seeds: Vec<Vector3>,
+}
+
+impl VectorFieldPlot {
+    pub const VARIABLES: [Variable; 3] = [Variable::X, Variable::Y, Variable::Z];
+    pub const MAX_DENSITY: usize = 24;
+    pub const MAX_SEEDS: usize = 1024;
+    const STREAMLINE_STEPS: usize = 1000;  // In each direction.
+
+    pub fn new(field: FieldSource, bounds: BoundingBox, density: usize, seeds: Vec<Vector3>) -> Result<Self, ClientError> {
+        check_range("x_range", bounds.min.x, bounds.max.x)?;
+        check_range("y_range", bounds.min.y, bounds.max.y)?;
+        check_range("z_range", bounds.min.z, bounds.max.z)?;
+        if density > Self::MAX_DENSITY {
+            return Err(ClientError::invalid_argument("density", format!("Expected a density in 0..={}, got {}", Self::MAX_DENSITY, density)));
+        }
+        if seeds.len() > Self::MAX_SEEDS {
+            return Err(ClientError::invalid_argument("seeds", format!("Expected at most {} seeds, got {}", Self::MAX_SEEDS, seeds.len())));
+        }
+        Ok(Self { field, bounds, density, seeds })
+    }
+
+    pub fn build(&self, gl: &GL) -> Result<Vec<Model>, ClientError> {
+        let field  = self.field.to_vector_field();
+        let arrows = field.sample_lattice(self.bounds, self.density);
+
+        // Steps are in the parameter of dp/ds = F(p), so scale them to the size of the box and field.
+        let typical = arrows.iter().map(|arrow| arrow.magnitude).sum::<f32>() / arrows.len().max(1) as f32;
+        let step = self.bounds.size().max_component() / 200.0 / if typical > 0.0 { typical } else { 1.0 };
+        let streamlines: Vec<Vec<Vector3>> = self.seeds.iter()
+            .map(|&seed| field.streamline(seed, self.bounds, step, Self::STREAMLINE_STEPS))
+            .filter(|line| line.len() >= 2)
+            .collect();
+
+        // Share the color scale between the arrows and the streamlines.
+        let largest = arrows.iter().map(|arrow| arrow.magnitude)
+            .chain(streamlines.iter().flatten().map(|&p| field.evaluate(p).norm()))
+            .filter(|magnitude| magnitude.is_finite())
+            .fold(0.0, f32::max);
+
+        if arrows.is_empty() && streamlines.is_empty() {
+            return Err(ClientError::invalid_argument("expression", format!("'{}' is undefined everywhere in the box", self.field.describe())));
+        }
+
+        let size    = self.bounds.size();
+        let spacing = size.x.min(size.y).min(size.z) / self.density as f32;
+        let instances = fields::create_instances_with_largest(&arrows, 0.9 * spacing, largest);
+
+        let mut mesh = Mesh::from_surface_data(gl, &fields::create_arrow(8))?;
+        mesh.set_instances(gl, &instances, fields::INSTANCE_LOCATION, &fields::INSTANCE_ATTRIBUTE_SIZES)?;
+        let mut models = PartialModels::new(gl);
+        models.push(Model::new(mesh, DrawConfig::new(GL::TRIANGLES, 0, -1, Box::new(ArrowMaterial::new(gl)?))));
+        if !streamlines.is_empty() {
+            let mesh = Mesh::from_positions_and_colors_3d(gl, &field.create_streamline_lines(&streamlines, largest))?;
+            models.push(Model::new(mesh, DrawConfig::new(GL::LINES, 0, -1, Box::new(VertexColorMaterial::new(gl)?))));
+        }
+        Ok(models.finish())
+    }
+}
@@ -0,0 +1,58 @@
+use web_sys::WebGl2RenderingContext as GL;
+use crate::math::Vector3;
+
+use crate::errors::ClientError;
+use crate::expression::{Expression, Variable, Variables};
+use crate::implicit::{BoundingBox, extract_isosurface};
+use crate::materials::{DrawConfig, ShadedMaterial};
+use crate::mesh::{Mesh, Model};
+use super::{check_range, check_resolution};
+
+
+/// The implicit surface F(x, y, z) = c inside a box, written as an equation, e.g.
+/// `x^2 + y^2 - z^2 = 1`. Without `=` the surface is F(x, y, z) = 0.
+pub struct ImplicitSurfacePlot {
+    pub source: String,
+    pub expression: Expression,  // lhs - rhs
+    pub bounds: BoundingBox,
+    pub resolution: usize,
+}
+
+impl ImplicitSurfacePlot {
+    pub const VARIABLES: [Variable; 3] = [Variable::X, Variable::Y, Variable::Z];
+    pub const MAX_RESOLUTION: usize = 128;
+
+    pub fn new(source: &str, bounds: BoundingBox, resolution: usize) -> Result<Self, ClientError> {
+        check_range("x_range", bounds.min.x, bounds.max.x)?;
+        check_range("y_range", bounds.min.y, bounds.max.y)?;
+        check_range("z_range", bounds.min.z, bounds.max.z)?;
+        check_resolution("resolution", resolution, Self::MAX_RESOLUTION)?;
+
+        Ok(Self {
+            source: String::from(source),
+            expression: Expression::parse_equation(source, &Self::VARIABLES).map_err(|error| ClientError::parse(error, source, "expression"))?,
+            bounds,
+            resolution,
+        })
+    }
+
+    pub fn build(&self, gl: &GL, color: Vector3) -> Result<Vec<Model>, ClientError> {
+        let gradient = self.expression.gradient(&Self::VARIABLES);
+        let surface = extract_isosurface(
+            |p| self.expression.evaluate(&Variables::xyz(p.x, p.y, p.z)),
+            |p| {
+                let variables = Variables::xyz(p.x, p.y, p.z);
+                Vector3::new(gradient[0].evaluate(&variables), gradient[1].evaluate(&variables), gradient[2].evaluate(&variables))
+            },
+            self.bounds,
+            self.resolution,
+        );
+        if surface.indices.is_empty() {
+            return Err(ClientError::invalid_argument("expression", format!("'{}' has no solutions inside the box", self.source)));
+        }
+
+        let mesh = Mesh::from_surface_data(gl, &surface)?;
+        let material = ShadedMaterial::new(gl, color)?;
+        Ok(vec![Model::new(mesh, DrawConfig::new(GL::TRIANGLES, 0, -1, Box::new(material)))])
+    }
+}
@@ -0,0 +1,119 @@
+use web_sys::WebGl2RenderingContext as GL;
+use crate::math::Vector3;
+
+use crate::errors::ClientError;
+use crate::expression::{Expression, Variable, Variables};
+use crate::fields;
+use crate::line_integral::{self, WorkSample};
+use crate::integration::Estimate;
+use crate::materials::{DrawConfig, ArrowMaterial, VertexColorMaterial};
+use crate::mesh::{Mesh, Model};
+use super::{FieldSource, CurvePlot, PartialModels, check_range, check_resolution, parse_components, check_animation_period, rule_from_name};
+
+
+/// The work ∫ F·dr along a space curve r(t) through a vector field. The curve is colored by F·T;
+/// when animated, the unit tangent and the field vector move along it.
+pub struct LineIntegralPlot {
+    pub source: String,
+    pub components: Vec<Expression>,
+    /// r'(t).
+    pub velocity: Vec<Expression>,
+    pub field: FieldSource,
+    pub t_range: (f32, f32),
+    pub samples: usize,
+    pub animation_period: Option<f32>,
+    /// The largest |F| along the curve, which the field arrow is scaled by.
+    largest_field: f32,
+}
+
+impl LineIntegralPlot {
+    pub const MAX_SAMPLES: usize = CurvePlot::MAX_SAMPLES;
+    const ARROW_LENGTH: f32 = 0.5;
+
+    pub fn new(source: &str, field: FieldSource, t_range: (f32, f32), samples: usize, animation_period: Option<f32>) -> Result<Self, ClientError> {
+        check_range("t_range", t_range.0, t_range.1)?;
+        check_resolution("samples", samples, Self::MAX_SAMPLES)?;
+        if samples < 2 {
+            return Err(ClientError::invalid_argument("samples", String::from("A curve needs at least two samples")));
+        }
+        check_animation_period(animation_period)?;
+
+        let (components, velocity) = Self::parse(source)?;
+        let mut plot = Self { source: String::from(source), components, velocity, field, t_range, samples, animation_period, largest_field: 0.0 };
+        plot.largest_field = plot.sample().iter().map(|sample| sample.field.norm()).fold(0.0, f32::max);
+        Ok(plot)
+    }
+
+    /// The components of r(t) and of r'(t).
+    pub fn parse(source: &str) -> Result<(Vec<Expression>, Vec<Expression>), ClientError> {
+        let components = parse_components(source, 3, &CurvePlot::VARIABLES)?;
+        let velocity   = components.iter().map(|component| component.derivative(Variable::T)).collect();
+        Ok((components, velocity))
+    }
+
+    fn evaluate(components: &[Expression], t: f32) -> Vector3 {
+        let variables = Variables { t, ..Variables::default() };
+        Vector3::new(components[0].evaluate(&variables), components[1].evaluate(&variables), components[2].evaluate(&variables))
+    }
+
+    fn sample(&self) -> Vec<WorkSample> {
+        let field = self.field.to_vector_field();
+        line_integral::sample_work(
+            |t| Self::evaluate(&self.components, t),
+            |t| Self::evaluate(&self.velocity, t),
+            |p| field.evaluate(p),
+            self.t_range.0,
+            self.t_range.1,
+            self.samples,
+        )
+    }
+
+    /// ∫ F·dr with an error estimate, by the rule on `subdivisions` subintervals of the range of t.
+    pub fn integrate(&self, rule: &str, subdivisions: usize) -> Result<Estimate, ClientError> {
+        const MAX_SUBDIVISIONS: usize = 4096;
+
+        let rule = rule_from_name(rule)?;
+        check_resolution("subdivisions", subdivisions, MAX_SUBDIVISIONS)?;
+
+        let field = self.field.to_vector_field();
+        let (a, b) = (self.t_range.0 as f64, self.t_range.1 as f64);
+        Ok(Estimate::from_refinement(|n| {
+            line_integral::line_integral(|t| Self::evaluate(&self.components, t), |t| Self::evaluate(&self.velocity, t), |p| field.evaluate(p), a, b, n, rule)
+        }, subdivisions, rule))
+    }
+
+    pub fn build(&self, gl: &GL) -> Result<Vec<Model>, ClientError> {
+        let samples = self.sample();
+        let t_step  = (self.t_range.1 - self.t_range.0) / (self.samples - 1) as f32;
+        let vertices = line_integral::create_work_lines(&samples, t_step);
+        if vertices.is_empty() {
+            return Err(ClientError::invalid_argument("expression", format!("'{}' or the field is undefined almost everywhere in the interval", self.source)));
+        }
+
+        let mesh = Mesh::from_positions_and_colors_3d(gl, &vertices)?;
+        let mut models = PartialModels::new(gl);
+        models.push(Model::new(mesh, DrawConfig::new(GL::LINES, 0, -1, Box::new(VertexColorMaterial::new(gl)?))));
+
+        if self.animation_period.is_some() {
+            let instances = line_integral::create_sample_instances(&samples[0], Self::ARROW_LENGTH, self.largest_field);
+            let mut mesh = Mesh::from_surface_data(gl, &fields::create_arrow(8))?;
+            mesh.set_instances(gl, &instances, fields::INSTANCE_LOCATION, &fields::INSTANCE_ATTRIBUTE_SIZES)?;
+            models.push(Model::new(mesh, DrawConfig::new(GL::TRIANGLES, 0, -1, Box::new(ArrowMaterial::new(gl)?))));
+        }
+        Ok(models.finish())
+    }
+
+    /// Moves the tangent and field arrows along the curve; `time` is in seconds. Where anything
+    /// is undefined the arrows stay where they were.
+    pub fn animate(&self, gl: &GL, models: &mut [Model], time: f32) {
+        if let (Some(period), Some(arrows)) = (self.animation_period, models.get(1)) {
+            let fraction = (time / period).fract();
+            let t = self.t_range.0 + fraction * (self.t_range.1 - self.t_range.0);
+            let field  = self.field.to_vector_field();
+            let sample = WorkSample::at(&|t| Self::evaluate(&self.components, t), &|t| Self::evaluate(&self.velocity, t), &|p| field.evaluate(p), t);
+            if let Some(sample) = sample {
+                arrows.mesh.update_instances(gl, &line_integral::create_sample_instances(&sample, Self::ARROW_LENGTH, self.largest_field));
+            }
+        }
+    }
+}
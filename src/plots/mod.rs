@@ -0,0 +1,445 @@
+use web_sys::WebGl2RenderingContext as GL;
+use crate::math::Vector3;
+use serde::Serialize;
+
+use crate::errors::ClientError;
+use crate::expression::{Expression, Variable};
+use crate::critical::CriticalPoint;
+use crate::lagrange::ConstrainedExtremum;
+use crate::integration::{Estimate, Rule};
+use crate::mesh::Model;
+use crate::picking::Ray;
+use crate::tangent::TangentPlane;
+use crate::utils::Placement;
+
+mod surface;
+mod parametric;
+mod implicit;
+mod field;
+mod riemann;
+mod curve;
+mod line_integral;
+
+pub use surface::{GradientOverlay, ContourLevels, ContourOverlay, ConstraintOverlay, SurfacePlot};
+pub use parametric::{FluxOverlay, ParametricSurfacePlot};
+pub use implicit::ImplicitSurfacePlot;
+pub use field::{FieldSource, VectorFieldPlot};
+pub use riemann::{RegionBounds, integrate_region, integrate_box, RiemannSumPlot};
+pub use curve::{CurveStyle, CurvePlot};
+pub use line_integral::LineIntegralPlot;
+
+
+/// Identifies a plot for the JS side. Handles are never reused, so a stale handle is an error
+/// instead of silently referring to another plot.
+pub type Handle = u32;
+
+
+/// Colors assigned to plots in order, so neighbouring plots are easy to tell apart.
+const PALETTE: [[f32; 3]; 6] = [
+    [0.20, 0.50, 0.90],
+    [0.90, 0.45, 0.20],
+    [0.30, 0.75, 0.35],
+    [0.80, 0.30, 0.70],
+    [0.90, 0.80, 0.25],
+    [0.25, 0.80, 0.80],
+];
+
+pub fn palette_color(handle: Handle) -> Vector3 {
+    let [r, g, b] = PALETTE[handle as usize % PALETTE.len()];
+    Vector3::new(r, g, b)
+}
+
+
+pub fn check_range(field: &'static str, min: f32, max: f32) -> Result<(), ClientError> {
+    if min.is_finite() && max.is_finite() && min < max {
+        Ok(())
+    } else {
+        Err(ClientError::invalid_argument(field, format!("Expected a finite range [min, max] with min < max, got [{}, {}]", min, max)))
+    }
+}
+
+pub fn check_resolution(field: &'static str, resolution: usize, max: usize) -> Result<(), ClientError> {
+    if resolution >= 1 && resolution <= max {
+        Ok(())
+    } else {
+        Err(ClientError::invalid_argument(field, format!("Expected a resolution in 1..={}, got {}", max, resolution)))
+    }
+}
+
+/// Reads a `[min, max]` pair passed from JS.
+pub fn range_from_slice(field: &'static str, range: &[f32]) -> Result<(f32, f32), ClientError> {
+    match range {
+        [min, max] => {
+            check_range(field, *min, *max)?;
+            Ok((*min, *max))
+        },
+        _ => Err(ClientError::invalid_argument(field, format!("Expected [min, max], got {} values", range.len()))),
+    }
+}
+
+
+/// Reads a placement passed from JS, where 'off' means not drawn at all.
+pub fn placement_from_name(field: &'static str, name: &str) -> Result<Option<Placement>, ClientError> {
+    match name {
+        "off" => Ok(None),
+        name  => Placement::from_name(name).map(Some).ok_or_else(|| {
+            ClientError::invalid_argument(field, format!("Expected 'plane', 'surface', 'both' or 'off', got '{}'", name))
+        }),
+    }
+}
+
+
+fn check_animation_period(animation_period: Option<f32>) -> Result<(), ClientError> {
+    match animation_period {
+        Some(period) if !(period.is_finite() && period > 0.0) => {
+            Err(ClientError::invalid_argument("animation_period", format!("Expected a positive period, got {}", period)))
+        },
+        _ => Ok(()),
+    }
+}
+
+fn rule_from_name(name: &str) -> Result<Rule, ClientError> {
+    Rule::from_name(name).ok_or_else(|| {
+        ClientError::invalid_argument("rule", format!("Expected 'midpoint', 'simpson' or 'gauss' with 1 to {} points, got '{}'", Rule::MAX_GAUSS_POINTS, name))
+    })
+}
+
+
+
+/// Reads points passed from JS as a flat array `[x0, y0, z0, x1, y1, z1, ...]`.
+pub fn points_from_slice(field: &'static str, coordinates: &[f32]) -> Result<Vec<Vector3>, ClientError> {
+    if !coordinates.len().is_multiple_of(3) {
+        return Err(ClientError::invalid_argument(field, format!("Expected x, y, z triples, got {} values", coordinates.len())));
+    }
+    if let Some(value) = coordinates.iter().find(|value| !value.is_finite()) {
+        return Err(ClientError::invalid_argument(field, format!("Expected finite coordinates, got {}", value)));
+    }
+    Ok(coordinates.chunks(3).map(|p| Vector3::new(p[0], p[1], p[2])).collect())
+}
+
+
+/// Parses `count` comma separated expressions, e.g. the components of a vector valued function.
+pub fn parse_components(source: &str, count: usize, variables: &[Variable]) -> Result<Vec<Expression>, ClientError> {
+    let components = Expression::parse_tuple(source, variables).map_err(|error| ClientError::parse(error, source, "expression"))?;
+    if components.len() != count {
+        return Err(ClientError::invalid_argument("expression", format!("Expected {} comma separated components, got {}", count, components.len())));
+    }
+    Ok(components)
+}
+
+
+pub enum PlotKind {
+    Surface(SurfacePlot),
+    ParametricSurface(ParametricSurfacePlot),
+    ImplicitSurface(ImplicitSurfacePlot),
+    Curve(CurvePlot),
+    VectorField(VectorFieldPlot),
+    RiemannSum(RiemannSumPlot),
+    LineIntegral(LineIntegralPlot),
+}
+
+impl PlotKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlotKind::Surface(_) => "surface",
+            PlotKind::ParametricSurface(_) => "parametric_surface",
+            PlotKind::ImplicitSurface(_) => "implicit_surface",
+            PlotKind::Curve(_) => "curve",
+            PlotKind::VectorField(_) => "vector_field",
+            PlotKind::RiemannSum(_) => "riemann_sum",
+            PlotKind::LineIntegral(_) => "line_integral",
+        }
+    }
+
+    pub fn expression(&self) -> String {
+        match self {
+            PlotKind::Surface(surface) => surface.source.clone(),
+            PlotKind::ParametricSurface(surface) => surface.source.clone(),
+            PlotKind::ImplicitSurface(surface) => surface.source.clone(),
+            PlotKind::Curve(curve) => curve.source.clone(),
+            PlotKind::VectorField(field) => field.field.describe(),
+            PlotKind::RiemannSum(sum) => sum.source.clone(),
+            PlotKind::LineIntegral(integral) => integral.source.clone(),
+        }
+    }
+
+    /// Returns a copy of the plot with the expression replaced, or an error if it doesn't parse.
+    pub fn with_expression(&self, source: &str) -> Result<Self, ClientError> {
+        match self {
+            PlotKind::Surface(surface) => {
+                let mut replaced = SurfacePlot {
+                    gradient: surface.gradient,
+                    contours: surface.contours.clone(),
+                    tangent_point: surface.tangent_point,
+                    ..SurfacePlot::new(source, surface.domain, surface.resolution)?
+                };
+                replaced.set_critical_point_seeds(surface.critical_point_seeds);
+                replaced.set_constraint(surface.constraint.clone());
+                Ok(PlotKind::Surface(replaced))
+            },
+            PlotKind::ParametricSurface(surface) => Ok(PlotKind::ParametricSurface(ParametricSurfacePlot {
+                flux: surface.flux.clone(),
+                ..ParametricSurfacePlot::new(source, surface.u_range, surface.v_range, surface.resolution, surface.periodic)?
+            })),
+            PlotKind::ImplicitSurface(surface) => Ok(PlotKind::ImplicitSurface(
+                ImplicitSurfacePlot::new(source, surface.bounds, surface.resolution)?
+            )),
+            PlotKind::Curve(curve) => Ok(PlotKind::Curve(
+                CurvePlot::new(source, curve.t_range, curve.samples, curve.style, curve.animation_period)?
+            )),
+            // Replacing a callback with expressions would silently change what kind of field it is.
+            PlotKind::VectorField(VectorFieldPlot { field: FieldSource::Callback(_), .. }) => Err(ClientError::invalid_argument(
+                "handle", String::from("The field is a callback; remove it and add a new one to change it")
+            )),
+            PlotKind::VectorField(field) => Ok(PlotKind::VectorField(
+                VectorFieldPlot::new(FieldSource::expressions(source)?, field.bounds, field.density, field.seeds.clone())?
+            )),
+            PlotKind::RiemannSum(sum) => Ok(PlotKind::RiemannSum(RiemannSumPlot::new(source, sum.bounds.clone(), sum.subdivisions)?)),
+            // The curve is replaced and the field kept.
+            PlotKind::LineIntegral(integral) => Ok(PlotKind::LineIntegral(
+                LineIntegralPlot::new(source, integral.field.clone(), integral.t_range, integral.samples, integral.animation_period)?
+            )),
+        }
+    }
+
+    pub fn build(&self, gl: &GL, color: Vector3) -> Result<Vec<Model>, ClientError> {
+        match self {
+            PlotKind::Surface(surface) => surface.build(gl, color),
+            PlotKind::ParametricSurface(surface) => surface.build(gl, color),
+            PlotKind::ImplicitSurface(surface) => surface.build(gl, color),
+            PlotKind::Curve(curve) => curve.build(gl, color),
+            PlotKind::VectorField(field) => field.build(gl),
+            PlotKind::RiemannSum(sum) => sum.build(gl),
+            PlotKind::LineIntegral(integral) => integral.build(gl),
+        }
+    }
+
+    /// Updates anything that moves over time; `time` is in seconds.
+    pub fn animate(&self, gl: &GL, models: &mut [Model], time: f32) {
+        match self {
+            PlotKind::Curve(curve) => curve.animate(models, time),
+            PlotKind::LineIntegral(integral) => integral.animate(gl, models, time),
+            PlotKind::Surface(_) | PlotKind::ParametricSurface(_) | PlotKind::ImplicitSurface(_) |
+            PlotKind::VectorField(_) | PlotKind::RiemannSum(_) => {},
+        }
+    }
+}
+
+
+pub struct Plot {
+    pub handle: Handle,
+    pub kind: PlotKind,
+    pub models: Vec<Model>,
+}
+
+impl Plot {
+    fn delete(&self, gl: &GL) {
+        for model in &self.models {
+            model.delete(gl);
+        }
+    }
+}
+
+
+/// The models of a plot while it's being built. If building fails part way, the models built so
+/// far are deleted when this is dropped, instead of leaking their buffers and programs.
+pub struct PartialModels<'a> {
+    gl: &'a GL,
+    models: Vec<Model>,
+}
+
+impl<'a> PartialModels<'a> {
+    pub fn new(gl: &'a GL) -> Self {
+        Self { gl, models: Vec::new() }
+    }
+
+    pub fn push(&mut self, model: Model) {
+        self.models.push(model);
+    }
+
+    pub fn extend(&mut self, models: impl IntoIterator<Item = Model>) {
+        self.models.extend(models);
+    }
+
+    /// Everything was built, so the models are kept.
+    pub fn finish(mut self) -> Vec<Model> {
+        std::mem::take(&mut self.models)
+    }
+}
+
+impl Drop for PartialModels<'_> {
+    fn drop(&mut self) {
+        for model in &self.models {
+            model.delete(self.gl);
+        }
+    }
+}
+
+
+/// What `Client::list` reports for each plot.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlotInfo {
+    pub handle: Handle,
+    pub kind: &'static str,
+    pub expression: String,
+}
+
+
+pub struct Plots {
+    next_handle: Handle,
+    plots: Vec<Plot>,
+}
+
+impl Plots {
+    pub fn new() -> Self {
+        Self {
+            next_handle: 1,
+            plots: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, gl: &GL, kind: PlotKind) -> Result<Handle, ClientError> {
+        let handle = self.next_handle;
+        let models = kind.build(gl, palette_color(handle))?;
+
+        self.next_handle += 1;
+        self.plots.push(Plot { handle, kind, models });
+        Ok(handle)
+    }
+
+    pub fn get(&self, handle: Handle) -> Result<&Plot, ClientError> {
+        self.plots.iter().find(|plot| plot.handle == handle).ok_or_else(|| ClientError::unknown_handle(handle))
+    }
+
+    pub fn get_mut(&mut self, handle: Handle) -> Result<&mut Plot, ClientError> {
+        self.plots.iter_mut().find(|plot| plot.handle == handle).ok_or_else(|| ClientError::unknown_handle(handle))
+    }
+
+    /// Rebuilds the plot from a new description. The old plot is kept if building fails.
+    pub fn replace(&mut self, gl: &GL, handle: Handle, kind: PlotKind) -> Result<(), ClientError> {
+        let plot   = self.get_mut(handle)?;
+        let models = kind.build(gl, palette_color(handle))?;
+
+        plot.delete(gl);
+        plot.kind   = kind;
+        plot.models = models;
+        Ok(())
+    }
+
+    pub fn set_expression(&mut self, gl: &GL, handle: Handle, source: &str) -> Result<(), ClientError> {
+        let kind = self.get(handle)?.kind.with_expression(source)?;
+        self.replace(gl, handle, kind)
+    }
+
+    /// Rebuilds a surface z = f(x, y) after changing it with `modify`; `what` names the feature for
+    /// the error if the plot is of another kind.
+    fn modify_surface<F>(&mut self, gl: &GL, handle: Handle, what: &str, modify: F) -> Result<(), ClientError>
+        where F: FnOnce(&mut SurfacePlot)
+    {
+        let mut surface = self.surface(handle, what)?.clone();
+        modify(&mut surface);
+        self.replace(gl, handle, PlotKind::Surface(surface))
+    }
+
+    /// The surface z = f(x, y) with the handle; `what` names the feature for the error if the plot
+    /// is of another kind.
+    fn surface(&self, handle: Handle, what: &str) -> Result<&SurfacePlot, ClientError> {
+        match &self.get(handle)?.kind {
+            PlotKind::Surface(surface) => Ok(surface),
+            kind => Err(ClientError::invalid_argument("handle", format!("{} need a surface z = f(x, y), but plot {} is a {}", what, handle, kind.name()))),
+        }
+    }
+
+    /// The parametric surface with the handle; `what` names the feature for the error if the plot
+    /// is of another kind.
+    fn parametric_surface(&self, handle: Handle, what: &str) -> Result<&ParametricSurfacePlot, ClientError> {
+        match &self.get(handle)?.kind {
+            PlotKind::ParametricSurface(surface) => Ok(surface),
+            kind => Err(ClientError::invalid_argument("handle", format!("{} need a parametric surface, but plot {} is a {}", what, handle, kind.name()))),
+        }
+    }
+
+    /// Shows the flux of a field through a parametric surface, or hides it with `None`. Returns
+    /// the total flux.
+    pub fn set_flux(&mut self, gl: &GL, handle: Handle, overlay: Option<FluxOverlay>) -> Result<Option<Estimate>, ClientError> {
+        let mut surface = self.parametric_surface(handle, "Flux")?.clone();
+        let estimate = overlay.as_ref().map(|overlay| surface.flux(overlay));
+        surface.flux = overlay;
+        self.replace(gl, handle, PlotKind::ParametricSurface(surface))?;
+        Ok(estimate)
+    }
+
+    /// Shows, changes or hides (with `None`) the gradient arrows of a surface z = f(x, y).
+    pub fn set_gradient(&mut self, gl: &GL, handle: Handle, overlay: Option<GradientOverlay>) -> Result<(), ClientError> {
+        self.modify_surface(gl, handle, "Gradient arrows", |surface| surface.gradient = overlay)
+    }
+
+    /// Shows, changes or hides (with `None`) the contours of a surface z = f(x, y).
+    pub fn set_contours(&mut self, gl: &GL, handle: Handle, overlay: Option<ContourOverlay>) -> Result<(), ClientError> {
+        self.modify_surface(gl, handle, "Contours", |surface| surface.contours = overlay)
+    }
+
+    /// Shows the tangent plane of a surface z = f(x, y) at (x, y), or hides it with `None`.
+    /// Returns the plane, or an error if f isn't differentiable at the point.
+    pub fn set_tangent_plane(&mut self, gl: &GL, handle: Handle, point: Option<(f32, f32)>) -> Result<Option<TangentPlane>, ClientError> {
+        let plane = match point {
+            Some((x, y)) => {
+                let plane = self.surface(handle, "Tangent planes")?.tangent_plane(x, y).ok_or_else(|| {
+                    ClientError::invalid_argument("point", format!("The surface isn't differentiable at ({}, {})", x, y))
+                })?;
+                Some(plane)
+            },
+            None => None,
+        };
+        self.modify_surface(gl, handle, "Tangent planes", |surface| surface.tangent_point = point)?;
+        Ok(plane)
+    }
+
+    /// Marks the critical points of a surface z = f(x, y), searching from `seeds` by `seeds`
+    /// starting points, or hides them with `None`. Returns the points.
+    pub fn set_critical_points(&mut self, gl: &GL, handle: Handle, seeds: Option<usize>) -> Result<Vec<CriticalPoint>, ClientError> {
+        if let Some(seeds) = seeds {
+            check_resolution("seeds", seeds, SurfacePlot::MAX_CRITICAL_POINT_SEEDS)?;
+        }
+        self.modify_surface(gl, handle, "Critical points", |surface| surface.set_critical_point_seeds(seeds))?;
+        Ok(self.surface(handle, "Critical points")?.critical_points.clone())
+    }
+
+    /// Shows the extrema of a surface z = f(x, y) on a constraint curve, or hides them with `None`.
+    /// Returns the solutions of the Lagrange system.
+    pub fn set_constraint(&mut self, gl: &GL, handle: Handle, overlay: Option<ConstraintOverlay>) -> Result<Vec<ConstrainedExtremum>, ClientError> {
+        self.modify_surface(gl, handle, "Constraints", |surface| surface.set_constraint(overlay))?;
+        let surface = self.surface(handle, "Constraints")?;
+        Ok(surface.constraint.as_ref().map(|overlay| overlay.solutions.clone()).unwrap_or_default())
+    }
+
+    /// The (x, y) where the ray first hits a surface z = f(x, y), if anywhere.
+    pub fn pick_surface(&self, handle: Handle, ray: &Ray) -> Result<Option<(f32, f32)>, ClientError> {
+        Ok(self.surface(handle, "Picking")?.pick(ray))
+    }
+
+    pub fn remove(&mut self, gl: &GL, handle: Handle) -> Result<(), ClientError> {
+        let index = self.plots.iter().position(|plot| plot.handle == handle).ok_or_else(|| ClientError::unknown_handle(handle))?;
+        let plot  = self.plots.remove(index);
+        plot.delete(gl);
+        Ok(())
+    }
+
+    pub fn animate(&mut self, gl: &GL, time: f32) {
+        for plot in self.plots.iter_mut() {
+            plot.kind.animate(gl, &mut plot.models, time);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=&Plot> {
+        self.plots.iter()
+    }
+
+    pub fn list(&self) -> Vec<PlotInfo> {
+        self.plots.iter().map(|plot| PlotInfo {
+            handle: plot.handle,
+            kind: plot.kind.name(),
+            expression: plot.kind.expression(),
+        }).collect()
+    }
+}
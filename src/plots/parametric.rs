@@ -0,0 +1,144 @@
+use web_sys::WebGl2RenderingContext as GL;
+use crate::math::Vector3;
+
+use crate::errors::ClientError;
+use crate::expression::{Expression, Variable, Variables};
+use crate::fields;
+use crate::flux::{self, OrientedSurface};
+use crate::integration::{Rule, Estimate};
+use crate::materials::{DrawConfig, ShadedMaterial, ArrowMaterial, DivergingColorMaterial};
+use crate::mesh::{Mesh, Model, ParametricSurface};
+use crate::utils::SurfaceData;
+use super::{FieldSource, PartialModels, check_range, check_resolution, parse_components};
+
+
+/// The flux of a vector field through a parametric surface, shown by coloring the surface by F·n.
+#[derive(Clone)]
+pub struct FluxOverlay {
+    pub field: FieldSource,
+    /// Orients the surface by -r_u × r_v instead of r_u × r_v.
+    pub flipped: bool,
+    pub normal_density: usize,  // Normal arrows along each parameter; 0 for none.
+}
+
+impl FluxOverlay {
+    pub const MAX_NORMAL_DENSITY: usize = 32;
+
+    pub fn new(field: FieldSource, flipped: bool, normal_density: usize) -> Result<Self, ClientError> {
+        if normal_density > Self::MAX_NORMAL_DENSITY {
+            return Err(ClientError::invalid_argument("normal_density", format!("Expected a density in 0..={}, got {}", Self::MAX_NORMAL_DENSITY, normal_density)));
+        }
+        Ok(Self { field, flipped, normal_density })
+    }
+}
+
+
+/// The parametric surface r(u, v) = (x(u, v), y(u, v), z(u, v)), written as `x, y, z`.
+#[derive(Clone)]
+pub struct ParametricSurfacePlot {
+    pub source: String,
+    pub components: Vec<Expression>,
+    pub u_range: (f32, f32),
+    pub v_range: (f32, f32),
+    pub resolution: usize,
+    pub periodic: (bool, bool),
+    pub flux: Option<FluxOverlay>,
+}
+
+impl ParametricSurfacePlot {
+    pub const VARIABLES: [Variable; 2] = [Variable::U, Variable::V];
+    pub const MAX_RESOLUTION: usize = 512;
+
+    pub fn new(source: &str, u_range: (f32, f32), v_range: (f32, f32), resolution: usize, periodic: (bool, bool)) -> Result<Self, ClientError> {
+        check_range("u_range", u_range.0, u_range.1)?;
+        check_range("v_range", v_range.0, v_range.1)?;
+        check_resolution("resolution", resolution, Self::MAX_RESOLUTION)?;
+
+        Ok(Self {
+            source: String::from(source),
+            components: parse_components(source, 3, &Self::VARIABLES)?,
+            u_range,
+            v_range,
+            resolution,
+            periodic,
+            flux: None,
+        })
+    }
+
+    fn evaluate(components: &[Expression], u: f32, v: f32) -> Vector3 {
+        let variables = Variables { u, v, ..Variables::default() };
+        Vector3::new(components[0].evaluate(&variables), components[1].evaluate(&variables), components[2].evaluate(&variables))
+    }
+
+    /// r(u, v) with the normal r_u × r_v, or its opposite if `flipped`.
+    pub fn oriented_surface(&self, flipped: bool) -> OrientedSurface {
+        let r_u: Vec<Expression> = self.components.iter().map(|component| component.derivative(Variable::U)).collect();
+        let r_v: Vec<Expression> = self.components.iter().map(|component| component.derivative(Variable::V)).collect();
+        let components = self.components.clone();
+        let sign = if flipped { -1.0 } else { 1.0 };
+        OrientedSurface::new(
+            move |u, v| Self::evaluate(&components, u, v),
+            move |u, v| (Self::evaluate(&r_u, u, v)).cross(&Self::evaluate(&r_v, u, v)) * sign,
+        )
+    }
+
+    /// ∬ F·n dS with an error estimate.
+    pub fn flux(&self, overlay: &FluxOverlay) -> Estimate {
+        const SUBDIVISIONS: usize = 16;
+        const RULE: Rule = Rule::GaussLegendre(3);
+
+        let surface = self.oriented_surface(overlay.flipped);
+        let field   = overlay.field.to_vector_field();
+        let u_range = (self.u_range.0 as f64, self.u_range.1 as f64);
+        let v_range = (self.v_range.0 as f64, self.v_range.1 as f64);
+        Estimate::from_refinement(|n| flux::flux(&surface, |p| field.evaluate(p), u_range, v_range, n, RULE), SUBDIVISIONS, RULE)
+    }
+
+    pub fn build(&self, gl: &GL, color: Vector3) -> Result<Vec<Model>, ClientError> {
+        let mut surface = ParametricSurface::new(|u, v| {
+                let p = Self::evaluate(&self.components, u, v);
+                [p.x, p.y, p.z]
+            })
+            .u_range(self.u_range.0, self.u_range.1)
+            .v_range(self.v_range.0, self.v_range.1)
+            .resolution(self.resolution, self.resolution)
+            .periodic(self.periodic.0, self.periodic.1)
+            .build();
+        if surface.indices.is_empty() {
+            return Err(ClientError::invalid_argument("expression", format!("'{}' is undefined everywhere in the domain", self.source)));
+        }
+
+        let overlay = match &self.flux {
+            Some(overlay) => overlay,
+            None => {
+                let mesh = Mesh::from_surface_data(gl, &surface)?;
+                let material = ShadedMaterial::new(gl, color)?;
+                return Ok(vec![Model::new(mesh, DrawConfig::new(GL::TRIANGLES, 0, -1, Box::new(material)))]);
+            },
+        };
+
+        let oriented = self.oriented_surface(overlay.flipped);
+        let field    = overlay.field.to_vector_field();
+        flux::apply_flux_colors(&mut surface, &oriented, |p| field.evaluate(p), self.u_range, self.v_range);
+
+        let mesh = Mesh::from_surface_data(gl, &surface)?;
+        let mut models = PartialModels::new(gl);
+        models.push(Model::new(mesh, DrawConfig::new(GL::TRIANGLES, 0, -1, Box::new(DivergingColorMaterial::new(gl)?))));
+
+        let samples = flux::sample_flux(&oriented, |p| field.evaluate(p), self.u_range, self.v_range, overlay.normal_density);
+        // Arrows a tenth of the size of the surface.
+        let (min, max) = surface.vertices.chunks(SurfaceData::COMPONENTS_PER_VERTEX).fold(
+            (Vector3::splat(f32::INFINITY), Vector3::splat(f32::NEG_INFINITY)),
+            |(min, max), vertex| {
+                let p = Vector3::new(vertex[0], vertex[1], vertex[2]);
+                (min.component_min(p), max.component_max(p))
+            },
+        );
+        let length = 0.1 * (max - min).max_component();
+
+        let mut mesh = Mesh::from_surface_data(gl, &fields::create_arrow(8))?;
+        mesh.set_instances(gl, &flux::create_normal_instances(&samples, length), fields::INSTANCE_LOCATION, &fields::INSTANCE_ATTRIBUTE_SIZES)?;
+        models.push(Model::new(mesh, DrawConfig::new(GL::TRIANGLES, 0, -1, Box::new(ArrowMaterial::new(gl)?))));
+        Ok(models.finish())
+    }
+}
@@ -0,0 +1,133 @@
+use web_sys::WebGl2RenderingContext as GL;
+
+use crate::errors::ClientError;
+use crate::expression::{Expression, Variable, Variables};
+use crate::implicit::BoundingBox;
+use crate::integration::{self, Region, Estimate};
+use crate::materials::{DrawConfig, PrismMaterial};
+use crate::mesh::{Mesh, Model, VERTICES_TEXTURE_AND_NORMAL_3D_CUBE};
+use super::{SurfacePlot, ImplicitSurfacePlot, check_resolution, range_from_slice, rule_from_name};
+
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RegionKind {
+    Rectangle,
+    TypeI,
+    TypeII,
+}
+
+/// A region of integration as given from JS: the range of the outer variable and the bounds of
+/// the inner one, written as `lower, upper`. The bounds are constants for a rectangle, functions of
+/// x for type I (g_1(x) <= y <= g_2(x)) and functions of y for type II (h_1(y) <= x <= h_2(y)).
+#[derive(Clone)]
+pub struct RegionBounds {
+    pub kind: RegionKind,
+    pub outer: (f32, f32),
+    pub lower: Expression,
+    pub upper: Expression,
+}
+
+impl RegionBounds {
+    pub fn new(kind: &str, outer: &[f32], inner: &str) -> Result<Self, ClientError> {
+        let (kind, variables): (RegionKind, &[Variable]) = match kind {
+            "rectangle" => (RegionKind::Rectangle, &[]),
+            "type_i"    => (RegionKind::TypeI, &[Variable::X]),
+            "type_ii"   => (RegionKind::TypeII, &[Variable::Y]),
+            _ => return Err(ClientError::invalid_argument("region", format!("Expected 'rectangle', 'type_i' or 'type_ii', got '{}'", kind))),
+        };
+        let outer = range_from_slice("outer_range", outer)?;
+        let mut bounds = Expression::parse_tuple(inner, variables).map_err(|error| ClientError::parse(error, inner, "inner_bounds"))?;
+        if bounds.len() != 2 {
+            return Err(ClientError::invalid_argument("inner_bounds", format!("Expected 'lower, upper', got {} bounds", bounds.len())));
+        }
+        let upper = bounds.pop().unwrap();
+        let lower = bounds.pop().unwrap();
+
+        Ok(Self { kind, outer, lower, upper })
+    }
+
+    pub fn to_region(&self) -> Region {
+        let outer = (self.outer.0 as f64, self.outer.1 as f64);
+        let bound = |expression: &Expression, variable: Variable| {
+            let expression = expression.clone();
+            Box::new(move |t: f64| {
+                let mut variables = Variables::new();
+                variables.set(variable, t as f32);
+                expression.evaluate(&variables) as f64
+            }) as Box<dyn Fn(f64) -> f64>
+        };
+
+        match self.kind {
+            RegionKind::Rectangle => {
+                let (lower, upper) = (self.lower.evaluate(&Variables::new()), self.upper.evaluate(&Variables::new()));
+                Region::Rectangle { x: outer, y: (lower as f64, upper as f64) }
+            },
+            RegionKind::TypeI  => Region::TypeI  { x: outer, y_lower: bound(&self.lower, Variable::X), y_upper: bound(&self.upper, Variable::X) },
+            RegionKind::TypeII => Region::TypeII { y: outer, x_lower: bound(&self.lower, Variable::Y), x_upper: bound(&self.upper, Variable::Y) },
+        }
+    }
+}
+
+
+/// ∬ f dA over the region with an error estimate; `source` is f(x, y).
+pub fn integrate_region(source: &str, bounds: &RegionBounds, rule: &str, subdivisions: usize) -> Result<Estimate, ClientError> {
+    const MAX_SUBDIVISIONS: usize = 256;
+
+    let expression = SurfacePlot::parse(source)?;
+    let rule = rule_from_name(rule)?;
+    check_resolution("subdivisions", subdivisions, MAX_SUBDIVISIONS)?;
+
+    let region = bounds.to_region();
+    let f = |x: f64, y: f64| expression.evaluate_xy(x as f32, y as f32, 0.0) as f64;
+    Ok(Estimate::from_refinement(|n| integration::double_integral(f, &region, n, rule), subdivisions, rule))
+}
+
+/// ∭ f dV over the box with an error estimate; `source` is f(x, y, z).
+pub fn integrate_box(source: &str, bounds: BoundingBox, rule: &str, subdivisions: usize) -> Result<Estimate, ClientError> {
+    const MAX_SUBDIVISIONS: usize = 32;
+
+    let expression = Expression::parse_with_variables(source, &ImplicitSurfacePlot::VARIABLES).map_err(|error| ClientError::parse(error, source, "expression"))?;
+    let rule = rule_from_name(rule)?;
+    check_resolution("subdivisions", subdivisions, MAX_SUBDIVISIONS)?;
+
+    let range = |min: f32, max: f32| (min as f64, max as f64);
+    let f = |x: f64, y: f64, z: f64| expression.evaluate(&Variables::xyz(x as f32, y as f32, z as f32)) as f64;
+    Ok(Estimate::from_refinement(|n| {
+        integration::triple_integral(f, range(bounds.min.x, bounds.max.x), range(bounds.min.y, bounds.max.y), range(bounds.min.z, bounds.max.z), n, rule)
+    }, subdivisions, rule))
+}
+
+/// The midpoint Riemann sum of f(x, y) over a region, drawn as boxes between the xy-plane and
+/// the surface.
+pub struct RiemannSumPlot {
+    pub source: String,
+    pub expression: Expression,
+    pub bounds: RegionBounds,
+    pub subdivisions: usize,
+}
+
+impl RiemannSumPlot {
+    pub const MAX_SUBDIVISIONS: usize = 128;
+
+    pub fn new(source: &str, bounds: RegionBounds, subdivisions: usize) -> Result<Self, ClientError> {
+        check_resolution("subdivisions", subdivisions, Self::MAX_SUBDIVISIONS)?;
+        Ok(Self {
+            source: String::from(source),
+            expression: SurfacePlot::parse(source)?,
+            bounds,
+            subdivisions,
+        })
+    }
+
+    pub fn build(&self, gl: &GL) -> Result<Vec<Model>, ClientError> {
+        let region = self.bounds.to_region();
+        let prisms = integration::riemann_prisms(|x, y| self.expression.evaluate_xy(x as f32, y as f32, 0.0) as f64, &region, self.subdivisions);
+        if prisms.is_empty() {
+            return Err(ClientError::invalid_argument("expression", format!("'{}' is undefined everywhere in the region", self.source)));
+        }
+
+        let mut mesh = Mesh::from_f32_array_3d(gl, &VERTICES_TEXTURE_AND_NORMAL_3D_CUBE, true, true, true)?;
+        mesh.set_instances(gl, &integration::create_prism_instances(&prisms), integration::PRISM_LOCATION, &integration::PRISM_ATTRIBUTE_SIZES)?;
+        Ok(vec![Model::new(mesh, DrawConfig::new(GL::TRIANGLES, 0, -1, Box::new(PrismMaterial::new(gl)?)))])
+    }
+}
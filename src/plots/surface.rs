@@ -0,0 +1,354 @@
+use web_sys::WebGl2RenderingContext as GL;
+use crate::math::Vector3;
+
+use crate::errors::ClientError;
+use crate::expression::{Expression, Variable};
+use crate::fields;
+use crate::critical::{self, CriticalPoint};
+use crate::lagrange::{self, SmoothFunction, ConstrainedExtremum};
+use crate::contours::{ScalarGrid, create_contour_lines, evenly_spaced_levels};
+use crate::integration;
+use crate::materials::{DrawConfig, ShadedMaterial, SingleColorMaterial, ArrowMaterial, VertexColorMaterial, PrismMaterial};
+use crate::mesh::{Mesh, Model, VERTICES_TEXTURE_AND_NORMAL_3D_CUBE, INDICES_RECTANGLE};
+use crate::picking::{self, Ray};
+use crate::tangent::TangentPlane;
+use crate::utils::{create_surface_with_gradient, Domain, Placement, SurfaceData};
+use super::{PartialModels, check_range, check_resolution};
+
+
+/// Arrows of ∇f drawn along with a surface.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GradientOverlay {
+    pub placement: Placement,
+    pub density: usize,  // Arrows along each side of the domain.
+}
+
+impl GradientOverlay {
+    pub const MAX_DENSITY: usize = 64;
+
+    pub fn new(placement: Placement, density: usize) -> Result<Self, ClientError> {
+        check_resolution("density", density, Self::MAX_DENSITY)?;
+        Ok(Self { placement, density })
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContourLevels {
+    /// The given values of f.
+    Values(Vec<f32>),
+    /// This many levels evenly spaced between the smallest and largest value of f in the domain.
+    Count(usize),
+}
+
+/// Level curves f(x, y) = c drawn along with a surface.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContourOverlay {
+    pub levels: ContourLevels,
+    pub placement: Placement,
+}
+
+impl ContourOverlay {
+    pub const MAX_LEVELS: usize = 256;
+
+    pub fn new(levels: ContourLevels, placement: Placement) -> Result<Self, ClientError> {
+        match &levels {
+            ContourLevels::Values(values) => {
+                if values.is_empty() || values.len() > Self::MAX_LEVELS {
+                    return Err(ClientError::invalid_argument("levels", format!("Expected 1 to {} levels, got {}", Self::MAX_LEVELS, values.len())));
+                }
+                if let Some(value) = values.iter().find(|value| !value.is_finite()) {
+                    return Err(ClientError::invalid_argument("levels", format!("Expected finite levels, got {}", value)));
+                }
+            },
+            ContourLevels::Count(count) => check_resolution("count", *count, Self::MAX_LEVELS)?,
+        }
+        Ok(Self { levels, placement })
+    }
+}
+
+
+/// A constraint g(x, y) = c on a surface, for finding the extrema of f on it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstraintOverlay {
+    pub source: String,
+    /// g - c, whose zeros are the constraint curve.
+    pub expression: Expression,
+    /// The curve and the extrema of f on it, found by `SurfacePlot::set_constraint` and kept
+    /// since solving the Lagrange system is slow.
+    pub curve: Vec<[(f32, f32); 2]>,
+    pub solutions: Vec<ConstrainedExtremum>,
+}
+
+impl ConstraintOverlay {
+    pub fn new(source: &str) -> Result<Self, ClientError> {
+        Ok(Self {
+            source: String::from(source),
+            expression: Expression::parse_equation(source, &SurfacePlot::VARIABLES).map_err(|error| ClientError::parse(error, source, "constraint"))?,
+            curve: Vec::new(),
+            solutions: Vec::new(),
+        })
+    }
+}
+
+
+/// The expression with its exact first and second derivatives in x and y.
+pub fn smooth_function(expression: &Expression) -> SmoothFunction {
+    let value    = expression.clone();
+    let gradient = expression.gradient(&SurfacePlot::VARIABLES);
+    let hessian  = expression.hessian(&SurfacePlot::VARIABLES);
+    SmoothFunction::new(
+        move |x, y| value.evaluate_xy(x, y, 0.0),
+        move |x, y| (gradient[0].evaluate_xy(x, y, 0.0), gradient[1].evaluate_xy(x, y, 0.0)),
+        move |x, y| [
+            [hessian[0][0].evaluate_xy(x, y, 0.0), hessian[0][1].evaluate_xy(x, y, 0.0)],
+            [hessian[1][0].evaluate_xy(x, y, 0.0), hessian[1][1].evaluate_xy(x, y, 0.0)],
+        ],
+    )
+}
+
+
+/// The height field z = f(x, y) over a rectangular domain.
+#[derive(Clone)]
+pub struct SurfacePlot {
+    pub source: String,
+    pub expression: Expression,
+    pub domain: Domain,
+    pub resolution: usize,
+    pub gradient: Option<GradientOverlay>,
+    pub contours: Option<ContourOverlay>,
+    /// Where the tangent plane is drawn, as (x, y).
+    pub tangent_point: Option<(f32, f32)>,
+    /// Seeds along each side of the domain when searching for critical points to mark. Set with
+    /// `set_critical_point_seeds`, which also finds the points.
+    pub critical_point_seeds: Option<usize>,
+    /// The points found from `critical_point_seeds`, kept since the search is slow.
+    pub critical_points: Vec<CriticalPoint>,
+    /// Set with `set_constraint`, which also finds the extrema on it.
+    pub constraint: Option<ConstraintOverlay>,
+}
+
+impl SurfacePlot {
+    pub const VARIABLES: [Variable; 2] = [Variable::X, Variable::Y];
+    pub const MAX_RESOLUTION: usize = 512;
+    pub const MAX_CRITICAL_POINT_SEEDS: usize = 32;
+
+    pub fn new(source: &str, domain: Domain, resolution: usize) -> Result<Self, ClientError> {
+        check_range("x_range", domain.x_min, domain.x_max)?;
+        check_range("y_range", domain.y_min, domain.y_max)?;
+        check_resolution("resolution", resolution, Self::MAX_RESOLUTION)?;
+
+        Ok(Self {
+            source: String::from(source),
+            expression: Self::parse(source)?,
+            domain,
+            resolution,
+            gradient: None,
+            contours: None,
+            tangent_point: None,
+            critical_point_seeds: None,
+            critical_points: Vec::new(),
+            constraint: None,
+        })
+    }
+
+    pub fn parse(source: &str) -> Result<Expression, ClientError> {
+        Expression::parse_with_variables(source, &Self::VARIABLES)
+            .map_err(|error| ClientError::parse(error, source, "expression"))
+    }
+
+    /// The triangles of the surface as drawn, with normals from the exact partial derivatives.
+    fn sample(&self) -> SurfaceData {
+        let gradient = self.expression.gradient(&Self::VARIABLES);
+        let (dx, dy) = (&gradient[0], &gradient[1]);
+        create_surface_with_gradient(
+            |x, y| self.expression.evaluate_xy(x, y, 0.0),
+            |x, y| (dx.evaluate_xy(x, y, 0.0), dy.evaluate_xy(x, y, 0.0)),
+            self.domain,
+            self.resolution,
+            self.resolution,
+        )
+    }
+
+    pub fn build(&self, gl: &GL, color: Vector3) -> Result<Vec<Model>, ClientError> {
+        let surface = self.sample();
+        if surface.indices.is_empty() {
+            return Err(ClientError::invalid_argument("expression", format!("'{}' is undefined everywhere in the domain", self.source)));
+        }
+
+        let mesh = Mesh::from_interleaved_with_indices_3d(gl, &surface.vertices, &surface.index_buffer(), true, true)?;
+        let material = ShadedMaterial::new(gl, color)?;
+        let mut models = PartialModels::new(gl);
+        models.push(Model::new(mesh, DrawConfig::new(GL::TRIANGLES, 0, -1, Box::new(material))));
+
+        if let Some(overlay) = self.gradient {
+            models.push(self.build_gradient(gl, overlay)?);
+        }
+        if let Some(overlay) = &self.contours {
+            models.extend(self.build_contours(gl, overlay)?);
+        }
+        // After a new expression the plane may be undefined at the point, in which case it's hidden.
+        if let Some(plane) = self.tangent_point.and_then(|(x, y)| self.tangent_plane(x, y)) {
+            models.extend(self.build_tangent_plane(gl, &plane)?);
+        }
+        if self.critical_point_seeds.is_some() {
+            models.push(self.build_critical_points(gl, &self.critical_points)?);
+        }
+        if let Some(overlay) = &self.constraint {
+            models.extend(self.build_constraint(gl, overlay)?);
+        }
+        Ok(models.finish())
+    }
+
+    /// Shows the extrema of f on the constraint curve, by Lagrange multipliers, or hides them.
+    pub fn set_constraint(&mut self, overlay: Option<ConstraintOverlay>) {
+        self.constraint = overlay.map(|overlay| {
+            let g     = smooth_function(&overlay.expression);
+            let curve = lagrange::constraint_curve(&g, self.domain, self.resolution);
+            let solutions = lagrange::solve(&smooth_function(&self.expression), &g, self.domain, &curve);
+            ConstraintOverlay { curve, solutions, ..overlay }
+        });
+    }
+
+    /// The constraint curve lifted onto the surface and, at each solution, the level curve of f
+    /// through it, the parallel gradients of f and g and a cube colored by the classification.
+    fn build_constraint(&self, gl: &GL, overlay: &ConstraintOverlay) -> Result<Vec<Model>, ClientError> {
+        let f = smooth_function(&self.expression);
+        let g = smooth_function(&overlay.expression);
+        let solutions = &overlay.solutions;
+
+        let mut models = PartialModels::new(gl);
+        let mut lines  = lagrange::create_constraint_lines(&f, &overlay.curve);
+        if !solutions.is_empty() {
+            let grid   = ScalarGrid::sample(|x, y| f.value(x, y), self.domain, self.resolution, self.resolution);
+            let levels: Vec<f32> = solutions.iter().map(|solution| solution.z).collect();
+            lines.extend(create_contour_lines(&grid, &levels, true));
+        }
+        if !lines.is_empty() {
+            let mesh = Mesh::from_positions_and_colors_3d(gl, &lines)?;
+            models.push(Model::new(mesh, DrawConfig::new(GL::LINES, 0, -1, Box::new(VertexColorMaterial::new(gl)?))));
+        }
+
+        let length = 0.15 * self.domain.width().min(self.domain.depth());
+        let mut mesh = Mesh::from_surface_data(gl, &fields::create_arrow(8))?;
+        mesh.set_instances(gl, &lagrange::create_gradient_instances(&f, &g, solutions, length), fields::INSTANCE_LOCATION, &fields::INSTANCE_ATTRIBUTE_SIZES)?;
+        models.push(Model::new(mesh, DrawConfig::new(GL::TRIANGLES, 0, -1, Box::new(ArrowMaterial::new(gl)?))));
+
+        let points: Vec<CriticalPoint> = solutions.iter().map(|solution| solution.to_critical_point()).collect();
+        models.push(self.build_critical_points(gl, &points)?);
+        Ok(models.finish())
+    }
+
+    /// Marks the critical points found from `seeds` by `seeds` starting points, or none.
+    pub fn set_critical_point_seeds(&mut self, seeds: Option<usize>) {
+        self.critical_point_seeds = seeds;
+        self.critical_points = seeds.map(|seeds| self.find_critical_points(seeds)).unwrap_or_default();
+    }
+
+    /// The critical points in the domain, classified by the exact second derivatives.
+    pub fn find_critical_points(&self, seeds: usize) -> Vec<CriticalPoint> {
+        let gradient = self.expression.gradient(&Self::VARIABLES);
+        let hessian  = self.expression.hessian(&Self::VARIABLES);
+        critical::find_critical_points(
+            |x, y| self.expression.evaluate_xy(x, y, 0.0),
+            |x, y| (gradient[0].evaluate_xy(x, y, 0.0), gradient[1].evaluate_xy(x, y, 0.0)),
+            |x, y| [
+                [hessian[0][0].evaluate_xy(x, y, 0.0), hessian[0][1].evaluate_xy(x, y, 0.0)],
+                [hessian[1][0].evaluate_xy(x, y, 0.0), hessian[1][1].evaluate_xy(x, y, 0.0)],
+            ],
+            self.domain,
+            seeds,
+        )
+    }
+
+    /// A cube at each critical point, colored by its classification.
+    fn build_critical_points(&self, gl: &GL, points: &[CriticalPoint]) -> Result<Model, ClientError> {
+        let size = 0.03 * self.domain.width().max(self.domain.depth());
+        let mut mesh = Mesh::from_f32_array_3d(gl, &VERTICES_TEXTURE_AND_NORMAL_3D_CUBE, true, true, true)?;
+        mesh.set_instances(gl, &critical::create_glyph_instances(points, size), integration::PRISM_LOCATION, &integration::PRISM_ATTRIBUTE_SIZES)?;
+        Ok(Model::new(mesh, DrawConfig::new(GL::TRIANGLES, 0, -1, Box::new(PrismMaterial::new(gl)?))))
+    }
+
+    /// The (x, y) of the closest point where the ray hits the surface.
+    pub fn pick(&self, ray: &Ray) -> Option<(f32, f32)> {
+        picking::pick_surface(ray, &self.sample()).map(|hit| (hit.x, -hit.z))
+    }
+
+    /// The tangent plane at (x, y) by the exact partial derivatives, if f is differentiable there.
+    pub fn tangent_plane(&self, x: f32, y: f32) -> Option<TangentPlane> {
+        let gradient = self.expression.gradient(&Self::VARIABLES);
+        let (dx, dy) = (&gradient[0], &gradient[1]);
+        TangentPlane::at(
+            |x, y| self.expression.evaluate_xy(x, y, 0.0),
+            |x, y| (dx.evaluate_xy(x, y, 0.0), dy.evaluate_xy(x, y, 0.0)),
+            x,
+            y,
+        )
+    }
+
+    /// A square of the tangent plane around the point, and an arrow along the normal.
+    fn build_tangent_plane(&self, gl: &GL, plane: &TangentPlane) -> Result<Vec<Model>, ClientError> {
+        let half_size = 0.15 * self.domain.width().min(self.domain.depth());
+
+        let mesh     = Mesh::from_f32_array_with_indices_3d(gl, &plane.create_vertices(half_size), &INDICES_RECTANGLE)?;
+        let material = SingleColorMaterial::with_color(gl, Vector3::new(0.85, 0.85, 0.85))?;
+        let mut models = PartialModels::new(gl);
+        models.push(Model::new(mesh, DrawConfig::new(GL::TRIANGLES, 0, -1, Box::new(material))));
+
+        let normal = fields::Arrow { origin: Vector3::new(plane.x, plane.y, plane.z), direction: plane.normal(), magnitude: 1.0 };
+        let mut mesh = Mesh::from_surface_data(gl, &fields::create_arrow(8))?;
+        mesh.set_instances(gl, &fields::create_instances(&[normal], half_size), fields::INSTANCE_LOCATION, &fields::INSTANCE_ATTRIBUTE_SIZES)?;
+        models.push(Model::new(mesh, DrawConfig::new(GL::TRIANGLES, 0, -1, Box::new(ArrowMaterial::new(gl)?))));
+        Ok(models.finish())
+    }
+
+    /// The level curves by marching squares, on the same grid as the surface so that lifted
+    /// contours lie on its triangles.
+    fn build_contours(&self, gl: &GL, overlay: &ContourOverlay) -> Result<Vec<Model>, ClientError> {
+        let grid = ScalarGrid::sample(|x, y| self.expression.evaluate_xy(x, y, 0.0), self.domain, self.resolution, self.resolution);
+        let levels = match &overlay.levels {
+            ContourLevels::Values(values) => values.clone(),
+            ContourLevels::Count(count) => match grid.range() {
+                Some((min, max)) => evenly_spaced_levels(min, max, *count),
+                None => Vec::new(),
+            },
+        };
+
+        let mut models = PartialModels::new(gl);
+        for lifted in [false, true] {
+            let wanted = if lifted { overlay.placement.on_surface() } else { overlay.placement.on_plane() };
+            let vertices = create_contour_lines(&grid, &levels, lifted);
+            if wanted && !vertices.is_empty() {
+                let mesh = Mesh::from_positions_and_colors_3d(gl, &vertices)?;
+                models.push(Model::new(mesh, DrawConfig::new(GL::LINES, 0, -1, Box::new(VertexColorMaterial::new(gl)?))));
+            }
+        }
+        Ok(models.finish())
+    }
+
+    /// The gradient arrows as a single instanced model, using the exact partial derivatives.
+    fn build_gradient(&self, gl: &GL, overlay: GradientOverlay) -> Result<Model, ClientError> {
+        let gradient = self.expression.gradient(&Self::VARIABLES);
+        let (dx, dy) = (&gradient[0], &gradient[1]);
+        let arrows = fields::sample_gradient_field(
+            |x, y| self.expression.evaluate_xy(x, y, 0.0),
+            |x, y| (dx.evaluate_xy(x, y, 0.0), dy.evaluate_xy(x, y, 0.0)),
+            self.domain,
+            overlay.density,
+            overlay.placement,
+        );
+
+        // Leave a small gap between neighbouring arrows.
+        let spacing = self.domain.width().min(self.domain.depth()) / overlay.density as f32;
+        let instances = fields::create_instances(&arrows, 0.9 * spacing);
+
+        let mut mesh = Mesh::from_surface_data(gl, &fields::create_arrow(8))?;
+        mesh.set_instances(gl, &instances, fields::INSTANCE_LOCATION, &fields::INSTANCE_ATTRIBUTE_SIZES)?;
+        Ok(Model::new(mesh, DrawConfig::new(GL::TRIANGLES, 0, -1, Box::new(ArrowMaterial::new(gl)?))))
+    }
+}
+
+
+#[cfg(test)]
+#[path = "tests/surface.rs"]
+mod tests;
@@ -0,0 +1,68 @@
+use super::*;
+use crate::critical::Classification;
+use crate::plots::PlotKind;
+use std::f32::consts::FRAC_1_SQRT_2;
+use crate::test_support::{find_at, square};
+use crate::utils::math_to_world;
+
+
+#[test]
+pub fn critical_points_are_found_once_and_kept() {
+    let mut surface = SurfacePlot::new("x^2 - y^2", square(), 16).unwrap();
+    surface.set_critical_point_seeds(Some(4));
+    assert_eq!(surface.critical_points.len(), 1);
+    assert_eq!(find_at(&surface.critical_points, 0.0, 0.0).kind, Classification::Saddle);
+
+    surface.set_critical_point_seeds(None);
+    assert!(surface.critical_points.is_empty());
+}
+
+#[test]
+pub fn new_expressions_find_their_critical_points() {
+    let mut surface = SurfacePlot::new("x^2 - y^2", square(), 16).unwrap();
+    surface.set_critical_point_seeds(Some(8));
+
+    let replaced = match PlotKind::Surface(surface).with_expression("(x^2 - 1)^2 + y^2").unwrap() {
+        PlotKind::Surface(replaced) => replaced,
+        _ => unreachable!(),
+    };
+    assert_eq!(replaced.critical_point_seeds, Some(8));
+    assert_eq!(replaced.critical_points.len(), 3, "{:?}", replaced.critical_points);
+    assert_eq!(find_at(&replaced.critical_points, 1.0, 0.0).kind, Classification::Minimum);
+}
+
+#[test]
+pub fn constrained_extrema_are_solved_once_and_kept() {
+    let mut surface = SurfacePlot::new("x + y", square(), 32).unwrap();
+    surface.set_constraint(Some(ConstraintOverlay::new("x^2 + y^2 = 1").unwrap()));
+    let overlay = surface.constraint.as_ref().unwrap();
+    assert!(!overlay.curve.is_empty());
+    assert_eq!(overlay.solutions.len(), 2, "{:?}", overlay.solutions);
+    assert_eq!(find_at(&overlay.solutions, FRAC_1_SQRT_2, FRAC_1_SQRT_2).kind, Classification::Maximum);
+
+    // A new expression is solved on the same constraint.
+    let replaced = match PlotKind::Surface(surface.clone()).with_expression("x - y").unwrap() {
+        PlotKind::Surface(replaced) => replaced,
+        _ => unreachable!(),
+    };
+    let solutions = &replaced.constraint.as_ref().unwrap().solutions;
+    assert_eq!(find_at(solutions, FRAC_1_SQRT_2, -FRAC_1_SQRT_2).kind, Classification::Maximum);
+
+    surface.set_constraint(None);
+    assert!(surface.constraint.is_none());
+}
+
+#[test]
+pub fn normals_are_exact_at_low_resolution() {
+    // The corner (2, 2) of z = x^2 + y^2 has the normal (-4, -4, 1) / sqrt(33).
+    let surface = SurfacePlot::new("x^2 + y^2", square(), 2).unwrap().sample();
+    let corner = surface.vertex(surface.vertex_count() - 1);
+    let expected = math_to_world(-4.0 / 33f32.sqrt(), -4.0 / 33f32.sqrt(), 1.0 / 33f32.sqrt());
+    for (a, b) in corner[5..8].iter().zip(expected.iter()) {
+        assert!((a - b).abs() < 1e-5, "{:?} != {:?}", &corner[5..8], expected);
+    }
+
+    // The tip of a cone has no normal, but isn't cut out of the surface.
+    let cone = SurfacePlot::new("sqrt(x^2 + y^2)", square(), 2).unwrap().sample();
+    assert_eq!(cone.indices.len(), 2 * 2 * 6);
+}
@@ -7,11 +7,14 @@ type Program = WebGlProgram;
 use crate::shaders::compile;
 
 pub fn create_program(gl: &GL, vertex_source: &str, fragment_source: &str) -> Result<Program, String> {
-    link(
-        gl,
-        &compile(gl, GL::VERTEX_SHADER, vertex_source)?,
-        &compile(gl, GL::FRAGMENT_SHADER, fragment_source)?,
-    )
+    let vertex_shader   = compile(gl, GL::VERTEX_SHADER, vertex_source)?;
+    let fragment_shader = compile(gl, GL::FRAGMENT_SHADER, fragment_source)?;
+    let program = link(gl, &vertex_shader, &fragment_shader);
+
+    // The shaders are only flagged for deletion, and freed with the program they're attached to.
+    gl.delete_shader(Some(&vertex_shader));
+    gl.delete_shader(Some(&fragment_shader));
+    program
 }
 
 
@@ -20,8 +23,8 @@ pub fn link(context: &GL, vertex_shader: &Shader, fragment_shader: &Shader) -> R
         .create_program()
         .ok_or("[WEBGL2 - PROGRAM LINKING ERROR]: Unable to create program.")?;
 
-    context.attach_shader(&program, &vertex_shader);
-    context.attach_shader(&program, &fragment_shader);
+    context.attach_shader(&program, vertex_shader);
+    context.attach_shader(&program, fragment_shader);
     context.link_program(&program);
 
     if context
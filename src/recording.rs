@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+use crate::app::{AppState, Input, InputEvent};
+use crate::camera::{CameraMode, CameraSnapshot};
+use crate::errors::ClientError;
+use crate::gamepad::{GamepadSettings, GamepadSnapshot};
+
+
+/// Everything a call of `Client::update` reacts to. The time of the frame, in milliseconds, is
+/// `state.time`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Frame {
+    pub dt: f32,
+    pub state: AppState,
+    pub gamepad: Option<GamepadSnapshot>,
+    pub camera_mode: CameraMode,
+    /// The events since the previous frame, which `state` is the result of. Kept to inspect and
+    /// debug recordings; replaying only needs the state.
+    pub events: Vec<InputEvent>,
+}
+
+impl Frame {
+    /// The next frame of live input.
+    pub fn capture(input: &mut Input, dt: f32, width: f32, height: f32, gamepad: Option<GamepadSnapshot>, camera_mode: CameraMode) -> Self {
+        let state  = input.frame_start(dt, width, height);
+        let events = input.take_recorded_events();
+        Self { dt, state, gamepad, camera_mode, events }
+    }
+}
+
+
+/// A recorded session: the view it started from and every frame of input after that.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    // First, so that recordings of other versions are recognized before the rest is decoded.
+    version: u32,
+    pub camera: CameraSnapshot,
+    pub gamepad: GamepadSettings,
+    pub frames: Vec<Frame>,
+}
+
+impl Recording {
+    pub const VERSION: u32 = 1;
+
+    pub fn new(camera: CameraSnapshot, gamepad: GamepadSettings) -> Self {
+        Self { version: Self::VERSION, camera, gamepad, frames: Vec::new() }
+    }
+
+    /// In milliseconds.
+    pub fn duration(&self) -> f32 {
+        self.frames.iter().map(|frame| frame.dt).sum()
+    }
+
+    /// The compact binary form, e.g. to save as a file.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Recordings are always serializable")
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ClientError> {
+        let invalid = |message: String| ClientError::invalid_argument("recording", message);
+
+        let version: u32 = bincode::deserialize(bytes).map_err(|error| invalid(error.to_string()))?;
+        if version != Self::VERSION {
+            return Err(invalid(format!("Expected a recording of version {}, got version {}", Self::VERSION, version)));
+        }
+        bincode::deserialize(bytes).map_err(|error| invalid(error.to_string()))
+    }
+}
+
+
+/// Plays a recording back one frame at a time.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    recording: Recording,
+    next: usize,
+}
+
+impl Replay {
+    pub fn new(recording: Recording) -> Self {
+        Self { recording, next: 0 }
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    pub fn next_frame(&mut self) -> Option<&Frame> {
+        let frame = self.recording.frames.get(self.next)?;
+        self.next += 1;
+        Some(frame)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.recording.frames.len()
+    }
+}
+
+
+#[cfg(test)]
+#[path = "tests/recording.rs"]
+mod tests;
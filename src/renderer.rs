@@ -1,5 +1,5 @@
 use web_sys::WebGl2RenderingContext as GL;
-use glm::{Mat4, Vec3, value_ptr};
+use crate::math::{Matrix4, Vector3};
 
 use crate::Camera;
 use crate::mesh::Model;
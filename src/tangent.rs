@@ -0,0 +1,133 @@
+use crate::math::Vector3;
+use serde::Serialize;
+
+use crate::mesh::VERTICES_3D_INDEXED_RECTANGLE;
+use crate::utils::math_to_world;
+
+
+/// The tangent plane of z = f(x, y) at (x, y, f(x, y)), which is the graph of the linearization
+/// L(x', y') = f(x, y) + fx (x' - x) + fy (y' - y).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TangentPlane {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub fx: f32,
+    pub fy: f32,
+}
+
+impl TangentPlane {
+    /// Returns `None` where f or its gradient is undefined.
+    pub fn at<F, G>(f: F, gradient: G, x: f32, y: f32) -> Option<Self>
+        where F: Fn(f32, f32) -> f32,
+              G: Fn(f32, f32) -> (f32, f32)
+    {
+        let z = f(x, y);
+        let (fx, fy) = gradient(x, y);
+        if z.is_finite() && fx.is_finite() && fy.is_finite() {
+            Some(Self { x, y, z, fx, fy })
+        } else {
+            None
+        }
+    }
+
+    pub fn linearization(&self, x: f32, y: f32) -> f32 {
+        self.z + self.fx * (x - self.x) + self.fy * (y - self.y)
+    }
+
+    /// The upward unit normal (-fx, -fy, 1) / |(-fx, -fy, 1)|, in math coordinates.
+    pub fn normal(&self) -> Vector3 {
+        Vector3::new(-self.fx, -self.fy, 1.0).normalize()
+    }
+
+    /// The equation of the plane, e.g. `z = 2 + 2 (x - 1) - 4 (y + 2)`, with the numbers rounded
+    /// to four decimals.
+    pub fn equation(&self) -> String {
+        let mut terms = Vec::new();
+        if round(self.z) != 0.0 {
+            terms.push((round(self.z), String::new()));
+        }
+        for (coefficient, variable, at) in [(self.fx, "x", self.x), (self.fy, "y", self.y)] {
+            let coefficient = round(coefficient);
+            if coefficient != 0.0 {
+                let at = round(at);
+                let factor = if at == 0.0 {
+                    String::from(variable)
+                } else {
+                    format!("({} {} {})", variable, if at < 0.0 { '+' } else { '-' }, at.abs())
+                };
+                terms.push((coefficient, factor));
+            }
+        }
+
+        let mut equation = String::from("z =");
+        for (i, (coefficient, factor)) in terms.iter().enumerate() {
+            let sign = match (i, *coefficient < 0.0) {
+                (0, false) => "",
+                (0, true)  => "-",
+                (_, false) => "+ ",
+                (_, true)  => "- ",
+            };
+            let magnitude = coefficient.abs();
+            let term = if factor.is_empty() {
+                magnitude.to_string()
+            } else if magnitude == 1.0 {
+                factor.clone()
+            } else {
+                format!("{} {}", magnitude, factor)
+            };
+            equation.push_str(&format!(" {}{}", sign, term));
+        }
+        if terms.is_empty() {
+            equation.push_str(" 0");
+        }
+        equation
+    }
+
+    /// The square of the plane over [x - half_size, x + half_size] x [y - half_size, y + half_size],
+    /// as the corners of `VERTICES_3D_INDEXED_RECTANGLE` in world coordinates. Draw it with
+    /// `INDICES_RECTANGLE`.
+    pub fn create_vertices(&self, half_size: f32) -> Vec<f32> {
+        VERTICES_3D_INDEXED_RECTANGLE.chunks(3).flat_map(|corner| {
+            let x = self.x + half_size * corner[0];
+            let y = self.y + half_size * corner[1];
+            math_to_world(x, y, self.linearization(x, y))
+        }).collect()
+    }
+
+    pub fn info(&self) -> TangentPlaneInfo {
+        let normal = self.normal();
+        TangentPlaneInfo {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+            fx: self.fx,
+            fy: self.fy,
+            normal: [normal.x, normal.y, normal.z],
+            equation: self.equation(),
+        }
+    }
+}
+
+fn round(value: f32) -> f32 {
+    let rounded = (value * 1e4).round() / 1e4;
+    if rounded == 0.0 { 0.0 } else { rounded }  // No '-0'.
+}
+
+
+/// What the JS side gets for a tangent plane.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TangentPlaneInfo {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub fx: f32,
+    pub fy: f32,
+    pub normal: [f32; 3],
+    pub equation: String,
+}
+
+
+#[cfg(test)]
+#[path = "tests/tangent.rs"]
+mod tests;
@@ -0,0 +1,132 @@
+use super::*;
+use crate::camera::{Camera, CameraMode};
+use crate::controls;
+use crate::gamepad::GamepadSettings;
+use crate::recording::Frame;
+
+
+fn input_of_size(width: f32, height: f32) -> Input {
+    let mut input = Input::new();
+    input.frame_start(0.0, width, height);
+    input
+}
+
+
+#[test]
+pub fn mouse_positions_are_from_the_lower_left() {
+    let mut input = input_of_size(200.0, 100.0);
+    input.mouse_down(2, 30.0, 10.0);
+    let state = input.frame_start(16.0, 200.0, 100.0);
+    assert!(state.mouse_down);
+    assert_eq!(state.mouse_button, 2);
+    assert_eq!((state.mouse_x, state.mouse_y), (30.0, 90.0));
+
+    input.mouse_up(40.0, 20.0);
+    let state = input.frame_start(16.0, 200.0, 100.0);
+    assert!(!state.mouse_down);
+    assert_eq!((state.mouse_x, state.mouse_y), (40.0, 80.0));
+    assert_eq!(state.time, 32.0);
+}
+
+#[test]
+pub fn movement_accumulates_until_the_next_frame() {
+    let mut input = input_of_size(200.0, 100.0);
+    input.mouse_move(10.0, 10.0);
+    input.frame_start(16.0, 200.0, 100.0);
+
+    input.mouse_move(15.0, 5.0);
+    input.mouse_move(25.0, 0.0);
+    input.wheel(3.0);
+    input.wheel(-1.0);
+    let state = input.frame_start(16.0, 200.0, 100.0);
+    assert_eq!((state.delta_mouse_x, state.delta_mouse_y), (15.0, 10.0));
+    assert_eq!(state.wheel_delta, 2.0);
+
+    let state = input.frame_start(16.0, 200.0, 100.0);
+    assert_eq!((state.delta_mouse_x, state.delta_mouse_y, state.wheel_delta), (0.0, 0.0, 0.0));
+}
+
+#[test]
+pub fn clients_have_separate_input() {
+    let mut first  = input_of_size(100.0, 100.0);
+    let mut second = input_of_size(100.0, 100.0);
+    first.key_down("KeyW");
+    first.mouse_down(0, 1.0, 1.0);
+    second.keyboard.bindings.bind(Action::MoveUp, "KeyW").unwrap();
+
+    let (a, b) = (first.frame_start(16.0, 100.0, 100.0), second.frame_start(16.0, 100.0, 100.0));
+    assert!(a.actions.pressed(Action::MoveForward) && a.mouse_down);
+    assert!(!b.actions.pressed(Action::MoveForward) && !b.mouse_down);
+    assert!(!first.keyboard.bindings.is_bound("KeyW", Action::MoveUp));
+}
+
+#[test]
+pub fn the_bound_key_toggles_the_mouse_lock() {
+    let mut input = Input::new();
+    assert!(input.mouse_locked());
+    assert!(!input.key_up("KeyW"));
+    assert!(input.key_up("Escape"));
+    assert!(!input.mouse_locked());
+
+    input.keyboard.bindings.clear(Action::ToggleMouseLock);
+    input.keyboard.bindings.bind(Action::ToggleMouseLock, "KeyM").unwrap();
+    assert!(!input.key_up("Escape"));
+    assert!(input.key_up("KeyM"));
+    assert!(input.mouse_locked());
+}
+
+#[test]
+pub fn blur_releases_the_keys() {
+    let mut input = input_of_size(100.0, 100.0);
+    input.key_down("KeyD");
+    assert!(input.frame_start(16.0, 100.0, 100.0).actions.pressed(Action::MoveRight));
+    input.blur();
+    assert!(input.frame_start(16.0, 100.0, 100.0).actions.just_released(Action::MoveRight));
+}
+
+#[test]
+pub fn gestures_are_part_of_the_frame() {
+    let mut input = input_of_size(100.0, 100.0);
+    input.gestures.start(ContactId::Touch(3), 10.0, 10.0);
+    input.gestures.moved(ContactId::Touch(3), 20.0, 10.0);
+    assert_eq!(input.frame_start(16.0, 100.0, 100.0).gesture.rotate_x, 10.0);
+    assert!(input.frame_start(16.0, 100.0, 100.0).gesture.is_none());
+
+    input.blur();
+    assert_eq!(input.gestures.contact_count(), 0);
+}
+
+#[test]
+pub fn a_pen_drag_rotates_the_camera_once() {
+    let pen = ContactId::Pointer(1);
+    let drag = |frames: Vec<Vec<InputEvent>>| {
+        let mut input  = input_of_size(100.0, 100.0);
+        let mut camera = Camera::new();
+        for events in frames {
+            for event in events {
+                input.handle(event);
+            }
+            let frame = Frame::capture(&mut input, 16.0, 100.0, 100.0, None, CameraMode::Orbit);
+            controls::update_camera(&mut camera, &frame, &GamepadSettings::default());
+        }
+        camera.snapshot()
+    };
+
+    let pen_only = drag(vec![
+        vec![InputEvent::ContactStart { id: pen, x: 10.0, y: 10.0 }, InputEvent::ContactMove { id: pen, x: 30.0, y: 10.0 }],
+        vec![InputEvent::ContactEnd(pen)],
+    ]);
+    // With the mouse events a browser may send for the same stroke.
+    let with_mouse_events = drag(vec![
+        vec![
+            InputEvent::ContactStart { id: pen, x: 10.0, y: 10.0 },
+            InputEvent::MouseDown { button: 0, x: 10.0, y: 10.0 },
+            InputEvent::ContactMove { id: pen, x: 30.0, y: 10.0 },
+            InputEvent::MouseMove { x: 30.0, y: 10.0 },
+        ],
+        vec![InputEvent::ContactEnd(pen), InputEvent::MouseUp { x: 30.0, y: 10.0 }],
+    ]);
+
+    assert_ne!(pen_only, drag(vec![Vec::new(), Vec::new()]));
+    assert_eq!(with_mouse_events, pen_only);
+}
@@ -0,0 +1,111 @@
+use super::*;
+
+fn circle_grid(resolution: usize) -> ScalarGrid {
+    ScalarGrid::sample(|x, y| x * x + y * y, Domain::new(-2.0, 2.0, -2.0, 2.0), resolution, resolution)
+}
+
+/// Rounds a point so that shared endpoints of neighbouring segments compare equal.
+fn key((x, y): (f32, f32)) -> (i64, i64) {
+    ((x * 1e4).round() as i64, (y * 1e4).round() as i64)
+}
+
+
+#[test]
+pub fn grid_layout_and_range() {
+    let grid = ScalarGrid::sample(|x, y| x + 10.0 * y, Domain::new(0.0, 2.0, 0.0, 1.0), 2, 1);
+    assert_eq!(grid.values.len(), 3 * 2);
+    assert_eq!(grid.get(2, 0), 2.0);
+    assert_eq!(grid.get(1, 1), 11.0);
+    assert_eq!(grid.position(2, 1), (2.0, 1.0));
+    assert_eq!(grid.range(), Some((0.0, 12.0)));
+
+    let undefined = ScalarGrid::sample(|_, _| f32::NAN, Domain::new(0.0, 1.0, 0.0, 1.0), 1, 1);
+    assert_eq!(undefined.range(), None);
+}
+
+#[test]
+pub fn evenly_spaced_levels_avoid_the_extremes() {
+    assert_eq!(evenly_spaced_levels(0.0, 4.0, 3), vec![1.0, 2.0, 3.0]);
+    assert!(evenly_spaced_levels(0.0, 1.0, 0).is_empty());
+}
+
+#[test]
+pub fn circle_contour_lies_on_the_circle() {
+    let segments = marching_squares(&circle_grid(32), 1.0);
+    assert!(!segments.is_empty());
+
+    // Linear interpolation cuts corners, so allow for the chord error of the grid.
+    for point in segments.iter().flatten() {
+        let radius = (point.0 * point.0 + point.1 * point.1).sqrt();
+        assert!((radius - 1.0).abs() < 0.02, "Point {:?} is off the circle", point);
+    }
+}
+
+#[test]
+pub fn circle_contour_is_closed() {
+    let segments = marching_squares(&circle_grid(17), 2.0);
+
+    // Every endpoint of a closed curve is shared by exactly two segments.
+    let mut counts = std::collections::HashMap::new();
+    for point in segments.iter().flatten() {
+        *counts.entry(key(*point)).or_insert(0) += 1;
+    }
+    assert!(counts.values().all(|&count| count == 2), "Contour has loose ends: {:?}", counts);
+}
+
+#[test]
+pub fn levels_outside_the_range_have_no_contours() {
+    let grid = circle_grid(8);
+    assert!(marching_squares(&grid, -1.0).is_empty());
+    assert!(marching_squares(&grid, 100.0).is_empty());
+}
+
+#[test]
+pub fn saddle_is_resolved_by_the_center() {
+    // Corners 0 and 2 are above the level, 1 and 3 below.
+    let mut grid = ScalarGrid::sample(|_, _| 0.0, Domain::new(0.0, 1.0, 0.0, 1.0), 1, 1);
+    grid.values = vec![1.0, 0.0, 0.0, 1.0];  // Row by row: (0, 0), (1, 0), (0, 1), (1, 1).
+
+    // Center 0.5 >= 0.4: corners 0 and 2 connect, cutting off the corners (1, 0) and (0, 1).
+    let connected = marching_squares(&grid, 0.4);
+    assert_eq!(connected.len(), 2);
+    assert!(connected.iter().any(|segment| segment.iter().all(|&(x, y)| x > 0.5 && y < 0.5)));
+    assert!(connected.iter().any(|segment| segment.iter().all(|&(x, y)| x < 0.5 && y > 0.5)));
+
+    // Center 0.5 < 0.6: corners 0 and 2 are cut off instead.
+    let separated = marching_squares(&grid, 0.6);
+    assert_eq!(separated.len(), 2);
+    assert!(separated.iter().any(|segment| segment.iter().all(|&(x, y)| x < 0.5 && y < 0.5)));
+    assert!(separated.iter().any(|segment| segment.iter().all(|&(x, y)| x > 0.5 && y > 0.5)));
+}
+
+#[test]
+pub fn undefined_cells_are_skipped() {
+    // sqrt is undefined for x < 0, so only the right half has a contour.
+    let grid = ScalarGrid::sample(|x, y| x.sqrt() + y, Domain::new(-1.0, 1.0, -1.0, 1.0), 8, 8);
+    let segments = marching_squares(&grid, 0.5);
+    assert!(!segments.is_empty());
+    assert!(segments.iter().flatten().all(|&(x, _)| x >= 0.0));
+}
+
+#[test]
+pub fn contour_lines_on_the_plane_and_lifted() {
+    let grid = circle_grid(16);
+    let flat   = create_contour_lines(&grid, &[1.0, 2.0], false);
+    let lifted = create_contour_lines(&grid, &[1.0, 2.0], true);
+    assert_eq!(flat.len(), lifted.len());
+    assert_eq!(flat.len() % 12, 0);
+
+    // World y is the math z: 0 on the plane and the level (plus a small offset) on the surface.
+    for (a, b) in flat.chunks(6).zip(lifted.chunks(6)) {
+        assert_eq!(a[1], 0.0);
+        assert!((b[1] - 1.01).abs() < 1e-6 || (b[1] - 2.01).abs() < 1e-6);
+        assert_eq!(a[3..6], b[3..6]);
+    }
+
+    // The lowest level gets the start of the colormap and the highest the end.
+    let low  = colormap(0.0);
+    let high = colormap(1.0);
+    assert_eq!(&flat[3..6], &[low.x, low.y, low.z]);
+    assert_eq!(&flat[flat.len() - 3..], &[high.x, high.y, high.z]);
+}
@@ -0,0 +1,111 @@
+use super::*;
+use crate::test_support::{find_at, square};
+use std::f32::consts::FRAC_PI_2;
+
+
+#[test]
+pub fn second_derivative_test() {
+    assert_eq!(Classification::from_hessian([[2.0, 0.0], [0.0, 2.0]]), Classification::Minimum);
+    assert_eq!(Classification::from_hessian([[-2.0, 1.0], [1.0, -2.0]]), Classification::Maximum);
+    assert_eq!(Classification::from_hessian([[2.0, 0.0], [0.0, -2.0]]), Classification::Saddle);
+    assert_eq!(Classification::from_hessian([[1.0, 2.0], [2.0, 1.0]]), Classification::Saddle);
+    assert_eq!(Classification::from_hessian([[1.0, 1.0], [1.0, 1.0]]), Classification::Degenerate);
+}
+
+#[test]
+pub fn double_well_has_two_minima_and_a_saddle() {
+    // f = (x^2 - 1)^2 + y^2.
+    let points = find_critical_points(
+        |x, y| (x * x - 1.0).powi(2) + y * y,
+        |x, y| (4.0 * x * (x * x - 1.0), 2.0 * y),
+        |x, _| [[12.0 * x * x - 4.0, 0.0], [0.0, 2.0]],
+        square(),
+        8,
+    );
+    assert_eq!(points.len(), 3, "{:?}", points);
+    assert_eq!(find_at(&points, -1.0, 0.0).kind, Classification::Minimum);
+    assert_eq!(find_at(&points, 1.0, 0.0).kind, Classification::Minimum);
+    assert_eq!(find_at(&points, 0.0, 0.0).kind, Classification::Saddle);
+    assert_eq!(find_at(&points, 0.0, 0.0).z, 1.0);
+}
+
+#[test]
+pub fn egg_crate_has_every_kind() {
+    // f = sin(x) sin(y) has a saddle at the origin and extrema at (±pi/2, ±pi/2).
+    let points = find_critical_points(
+        |x, y| x.sin() * y.sin(),
+        |x, y| (x.cos() * y.sin(), x.sin() * y.cos()),
+        |x, y| [[-x.sin() * y.sin(), x.cos() * y.cos()], [x.cos() * y.cos(), -x.sin() * y.sin()]],
+        square(),
+        12,
+    );
+    assert_eq!(points.len(), 5, "{:?}", points);
+    assert_eq!(find_at(&points, 0.0, 0.0).kind, Classification::Saddle);
+    assert_eq!(find_at(&points, FRAC_PI_2, FRAC_PI_2).kind, Classification::Maximum);
+    assert_eq!(find_at(&points, -FRAC_PI_2, -FRAC_PI_2).kind, Classification::Maximum);
+    assert_eq!(find_at(&points, FRAC_PI_2, -FRAC_PI_2).kind, Classification::Minimum);
+    assert_eq!(find_at(&points, -FRAC_PI_2, FRAC_PI_2).kind, Classification::Minimum);
+}
+
+#[test]
+pub fn scaling_the_function_keeps_the_kinds() {
+    // D = -4e-8 for 1e-4 (x^2 - y^2), which is still clearly a saddle.
+    assert_eq!(Classification::from_hessian([[2e-4, 0.0], [0.0, -2e-4]]), Classification::Saddle);
+    assert_eq!(Classification::from_hessian([[2e4, 1e4], [1e4, 2e4]]), Classification::Minimum);
+    assert_eq!(Classification::from_hessian([[1e4, 1e4], [1e4, 1e4]]), Classification::Degenerate);
+
+    let flat = find_critical_points(
+        |x, y| 1e-4 * (x * x - y * y),
+        |x, y| (2e-4 * x, -2e-4 * y),
+        |_, _| [[2e-4, 0.0], [0.0, -2e-4]],
+        square(),
+        4,
+    );
+    assert_eq!(flat.len(), 1, "{:?}", flat);
+    assert_eq!(find_at(&flat, 0.0, 0.0).kind, Classification::Saddle);
+
+    // The gradient at the rounded minimum is far from 0 in absolute terms.
+    let steep = find_critical_points(
+        |x, y| 1e5 * ((x - 0.3).powi(2) + y * y),
+        |x, y| (2e5 * (x - 0.3), 2e5 * y),
+        |_, _| [[2e5, 0.0], [0.0, 2e5]],
+        square(),
+        4,
+    );
+    assert_eq!(steep.len(), 1, "{:?}", steep);
+    assert_eq!(find_at(&steep, 0.3, 0.0).kind, Classification::Minimum);
+}
+
+#[test]
+pub fn points_outside_the_domain_are_dropped() {
+    // The minimum of (x - 3)^2 + y^2 is outside [-2, 2]^2.
+    let points = find_critical_points(
+        |x, y| (x - 3.0).powi(2) + y * y,
+        |x, y| (2.0 * (x - 3.0), 2.0 * y),
+        |_, _| [[2.0, 0.0], [0.0, 2.0]],
+        square(),
+        6,
+    );
+    assert!(points.is_empty());
+}
+
+#[test]
+pub fn planes_have_no_critical_points() {
+    // The Hessian is singular everywhere.
+    let points = find_critical_points(|x, y| x + y, |_, _| (1.0, 1.0), |_, _| [[0.0, 0.0], [0.0, 0.0]], square(), 6);
+    assert!(points.is_empty());
+}
+
+#[test]
+pub fn glyphs_are_colored_by_kind() {
+    let points = [
+        CriticalPoint { x: 1.0, y: 2.0, z: 3.0, kind: Classification::Minimum },
+        CriticalPoint { x: 0.0, y: 0.0, z: 0.0, kind: Classification::Saddle },
+    ];
+    let data  = create_glyph_instances(&points, 0.1);
+    let (minimum, saddle) = (Classification::Minimum.color(), Classification::Saddle.color());
+    assert_eq!(data, vec![
+        1.0, 3.0, -2.0, 0.1, 0.1, 0.1, minimum.x, minimum.y, minimum.z,
+        0.0, 0.0, 0.0, 0.1, 0.1, 0.1, saddle.x, saddle.y, saddle.z,
+    ]);
+}
@@ -0,0 +1,76 @@
+use super::*;
+use std::f32::consts::PI;
+
+fn helix(t: f32) -> [f32; 3] {
+    [t.cos(), t.sin(), 0.2 * t]
+}
+
+fn assert_orthonormal(frame: &Frame) {
+    let Frame { tangent, normal, binormal } = frame;
+    assert!((tangent.norm()  - 1.0).abs() < 1e-4);
+    assert!((normal.norm()   - 1.0).abs() < 1e-4);
+    assert!((binormal.norm() - 1.0).abs() < 1e-4);
+    assert!(tangent.dot(normal).abs()   < 1e-4);
+    assert!(tangent.dot(binormal).abs() < 1e-4);
+    assert!(normal.dot(binormal).abs()  < 1e-4);
+}
+
+
+#[test]
+pub fn sample_curve_drops_undefined_points() {
+    let points = sample_curve(|t| [t, t.ln(), 0.0], -1.0, 1.0, 5);
+    assert_eq!(points.len(), 2);  // ln is only defined at t = 0.5 and t = 1.
+}
+
+#[test]
+pub fn polyline_is_in_world_coordinates() {
+    let vertices = create_polyline(&[Vector3::new(1.0, 2.0, 3.0)]);
+    assert_eq!(vertices, vec![1.0, 3.0, -2.0]);
+}
+
+#[test]
+pub fn frames_are_orthonormal() {
+    let points = sample_curve(helix, 0.0, 4.0 * PI, 200);
+    for kind in &[FrameKind::Frenet, FrameKind::RotationMinimizing] {
+        for frame in compute_frames(&points, *kind) {
+            assert_orthonormal(&frame);
+        }
+    }
+}
+
+#[test]
+pub fn frenet_normal_of_a_helix_points_to_the_axis() {
+    let points = sample_curve(helix, 0.0, 2.0 * PI, 100);
+    let frames = compute_frames(&points, FrameKind::Frenet);
+    for (point, frame) in points.iter().zip(frames.iter()).skip(1).take(98) {
+        let to_axis = Vector3::new(-point.x, -point.y, 0.0).normalize();
+        assert!(frame.normal.dot(&to_axis) > 0.99);
+    }
+}
+
+#[test]
+pub fn rotation_minimizing_frames_handle_straight_lines() {
+    let points = sample_curve(|t| [t, 0.0, 0.0], 0.0, 1.0, 10);
+    let frames = compute_frames(&points, FrameKind::RotationMinimizing);
+    for frame in &frames {
+        assert_orthonormal(frame);
+        assert!((frame.normal - frames[0].normal).norm() < 1e-5, "The frame shouldn't twist along a line");
+    }
+}
+
+#[test]
+pub fn tube_vertices_lie_at_the_radius() {
+    let points = sample_curve(helix, 0.0, 2.0 * PI, 50);
+    let tube   = create_tube(&points, 0.1, 8, FrameKind::RotationMinimizing);
+    assert_eq!(tube.vertex_count(), 50 * 9);
+    assert_eq!(tube.indices.len(), 49 * 8 * 6);
+
+    for (i, point) in points.iter().enumerate() {
+        let center = math_to_world(point.x, point.y, point.z);
+        for j in 0..9 {
+            let vertex = tube.vertex(i * 9 + j);
+            let distance = ((vertex[0] - center[0]).powi(2) + (vertex[1] - center[1]).powi(2) + (vertex[2] - center[2]).powi(2)).sqrt();
+            assert!((distance - 0.1).abs() < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use super::*;
+use crate::expression::{Expression, Span};
+
+
+#[test]
+pub fn parse_error_spans_are_in_utf16_code_units() {
+    // '²' is two bytes long but a single UTF-16 code unit.
+    let source = "x² + y";
+    let error = ClientError::parse(Expression::parse(source).unwrap_err(), source, "expression");
+    assert_eq!(error.span, Some((1, 2)));
+
+    // Everything before the span counts too: 'π' and '·' are two bytes each.
+    let source = "π·x + $";
+    let error = ClientError::parse(ParseError::new(String::from("Unexpected character '$'"), Span::new(8, 9)), source, "expression");
+    assert_eq!(error.span, Some((6, 7)));
+
+    // Characters outside the basic multilingual plane take two code units.
+    let source = "𝑥";
+    let error = ClientError::parse(Expression::parse(source).unwrap_err(), source, "expression");
+    assert_eq!(error.span, Some((0, 2)));
+}
@@ -0,0 +1,168 @@
+use super::*;
+
+
+#[test]
+pub fn arrow_is_a_valid_mesh_of_unit_length() {
+    let arrow = create_arrow(8);
+    assert_eq!(arrow.indices.len() % 3, 0);
+    assert!(arrow.indices.iter().all(|&i| (i as usize) < arrow.vertex_count()));
+
+    let mut highest: f32 = 0.0;
+    for i in 0..arrow.vertex_count() {
+        let v = arrow.vertex(i);
+        let normal = Vector3::new(v[5], v[6], v[7]);
+        assert!((normal.norm() - 1.0).abs() < 1e-5, "Normal {} isn't unit length", i);
+        assert!(v[1] >= 0.0 && v[1] <= 1.0);
+        highest = highest.max(v[1]);
+    }
+    assert_eq!(highest, 1.0);
+}
+
+#[test]
+pub fn arrow_triangles_face_their_normals() {
+    let arrow = create_arrow(6);
+    for triangle in arrow.indices.chunks(3) {
+        let p = |i: u32| { let v = arrow.vertex(i as usize); Vector3::new(v[0], v[1], v[2]) };
+        let n = |i: u32| { let v = arrow.vertex(i as usize); Vector3::new(v[5], v[6], v[7]) };
+        let face = (p(triangle[1]) - p(triangle[0])).cross(&(p(triangle[2]) - p(triangle[0])));
+        let average = n(triangle[0]) + n(triangle[1]) + n(triangle[2]);
+        assert!(face.dot(&average) > 0.0, "Triangle {:?} is wound against its normals", triangle);
+    }
+}
+
+#[test]
+pub fn gradient_field_sample_counts() {
+    let domain = Domain::new(-1.0, 1.0, -1.0, 1.0);
+    let f = |x: f32, y: f32| x * x + y * y;
+    let gradient = |x: f32, y: f32| (2.0 * x, 2.0 * y);
+
+    assert_eq!(sample_gradient_field(f, gradient, domain, 4, Placement::Plane).len(), 16);
+    assert_eq!(sample_gradient_field(f, gradient, domain, 4, Placement::Surface).len(), 16);
+    assert_eq!(sample_gradient_field(f, gradient, domain, 4, Placement::Both).len(), 32);
+
+    // ln(x) is undefined for x <= 0, which is the left half of the domain.
+    let undefined_left = |x: f32, _: f32| (if x > 0.0 { 1.0 / x } else { f32::NAN }, 0.0);
+    let arrows = sample_gradient_field(|x, _| x.ln(), undefined_left, domain, 4, Placement::Plane);
+    assert_eq!(arrows.len(), 8);
+    assert!(arrows.iter().all(|arrow| arrow.origin.x > 0.0));
+}
+
+#[test]
+pub fn gradient_on_the_surface_is_tangent() {
+    let f = |x: f32, y: f32| x * x - 3.0 * y;
+    let gradient = |x: f32, _: f32| (2.0 * x, -3.0);
+    let arrows = sample_gradient_field(f, gradient, Domain::new(-2.0, 2.0, -2.0, 2.0), 5, Placement::Surface);
+
+    for arrow in arrows {
+        let (dx, dy) = gradient(arrow.origin.x, arrow.origin.y);
+        let normal = Vector3::new(-dx, -dy, 1.0);
+        assert!(normal.dot(&arrow.direction).abs() < 1e-4);
+        assert!((arrow.magnitude - (dx * dx + dy * dy).sqrt()).abs() < 1e-5);
+    }
+}
+
+#[test]
+pub fn instances_scale_and_color_by_magnitude() {
+    let arrows = [
+        Arrow { origin: Vector3::new(1.0, 2.0, 3.0), direction: Vector3::new(4.0, 0.0, 0.0), magnitude: 4.0 },
+        Arrow { origin: Vector3::new(0.0, 0.0, 0.0), direction: Vector3::new(0.0, 1.0, 0.0), magnitude: 1.0 },
+        Arrow { origin: Vector3::new(0.0, 0.0, 0.0), direction: Vector3::new(0.0, 0.0, 0.0), magnitude: 0.0 },
+    ];
+    let data = create_instances(&arrows, 0.5);
+    assert_eq!(data.len(), 3 * 9);
+
+    // Origins and vectors are in world coordinates (y up).
+    assert_eq!(&data[0..3], &[1.0, 3.0, -2.0]);
+    assert_eq!(&data[3..6], &[0.5, 0.0, 0.0]);
+    assert_eq!(&data[9 + 3..9 + 6], &[0.0, 0.0, -0.125]);
+    assert_eq!(&data[18 + 3..18 + 6], &[0.0, 0.0, 0.0]);
+
+    let strongest = colormap(1.0);
+    let weakest   = colormap(0.0);
+    assert_eq!(&data[6..9], &[strongest.x, strongest.y, strongest.z]);
+    assert_eq!(&data[18 + 6..18 + 9], &[weakest.x, weakest.y, weakest.z]);
+}
+
+#[test]
+pub fn colormap_is_clamped() {
+    assert_eq!(colormap(-1.0), colormap(0.0));
+    assert_eq!(colormap(2.0), colormap(1.0));
+    assert_eq!(colormap(f32::NAN), colormap(0.0));
+}
+
+fn unit_box(half: f32) -> BoundingBox {
+    BoundingBox::new(Vector3::new(-half, -half, -half), Vector3::new(half, half, half))
+}
+
+#[test]
+pub fn diverging_colors() {
+    assert_eq!(diverging_colormap(0.0), Vector3::new(0.95, 0.95, 0.95));
+    assert_eq!(diverging_colormap(1.0), Vector3::new(0.95, 0.25, 0.20));
+    assert_eq!(diverging_colormap(-5.0), Vector3::new(0.20, 0.40, 0.95));
+    assert_eq!(diverging_colormap(f32::NAN), diverging_colormap(0.0));
+}
+
+#[test]
+pub fn lattice_samples_the_cell_centers() {
+    let field  = VectorField::new(|p| p * 2.0);
+    let arrows = field.sample_lattice(unit_box(1.0), 2);
+    assert_eq!(arrows.len(), 8);
+    assert_eq!(arrows[0].origin, Vector3::new(-0.5, -0.5, -0.5));
+    assert_eq!(arrows[0].direction, Vector3::new(-1.0, -1.0, -1.0));
+    assert!((arrows[0].magnitude - 3.0f32.sqrt()).abs() < 1e-6);
+
+    // Undefined points are skipped.
+    let half = VectorField::new(|p| if p.x > 0.0 { p } else { Vector3::new(f32::NAN, 0.0, 0.0) });
+    assert_eq!(half.sample_lattice(unit_box(1.0), 2).len(), 4);
+}
+
+#[test]
+pub fn rk4_follows_a_rotation() {
+    // Rotation about the z-axis; after a quarter turn (1, 0, 0.5) is at (0, 1, 0.5).
+    let rotation = VectorField::new(|p| Vector3::new(-p.y, p.x, 0.0));
+    let steps = 100;
+    let h = std::f32::consts::FRAC_PI_2 / steps as f32;
+
+    let mut p = Vector3::new(1.0, 0.0, 0.5);
+    for _ in 0..steps {
+        p = rotation.rk4_step(p, h);
+    }
+    assert!((p - Vector3::new(0.0, 1.0, 0.5)).norm() < 1e-5, "Ended at {:?}", p);
+}
+
+#[test]
+pub fn streamlines_stay_inside_and_pass_through_the_seed() {
+    let field = VectorField::new(|_| Vector3::new(1.0, 0.0, 0.0));
+    let seed  = Vector3::new(0.25, 0.5, 0.0);
+    let line  = field.streamline(seed, unit_box(1.0), 0.1, 1000);
+
+    assert!(line.contains(&seed));
+    assert!(line.iter().all(|p| p.x >= -1.0 && p.x <= 1.0 && p.y == 0.5));
+    assert!(line.windows(2).all(|pair| pair[1].x > pair[0].x), "The line runs backwards then forwards");
+    assert!(line.first().unwrap().x < -0.85 && line.last().unwrap().x > 0.85);
+}
+
+#[test]
+pub fn streamlines_stop_at_stagnation_points_and_limits() {
+    // Flows into the origin, where the field vanishes.
+    let sink = VectorField::new(|p| -p);
+    let line = sink.streamline(Vector3::new(0.5, 0.0, 0.0), unit_box(1.0), 0.05, 10_000);
+    assert!(line.len() < 10_000);
+
+    let field = VectorField::new(|_| Vector3::new(0.0, 0.0, 1.0));
+    assert_eq!(field.streamline(Vector3::ZERO, unit_box(100.0), 0.1, 5).len(), 11);
+}
+
+#[test]
+pub fn streamline_colors_follow_the_magnitude() {
+    let field = VectorField::new(|p| Vector3::new(p.x, 0.0, 0.0));
+    let lines = vec![vec![Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0), Vector3::new(2.0, 0.0, 0.0)]];
+    let data  = field.create_streamline_lines(&lines, 2.0);
+    assert_eq!(data.len(), 2 * 2 * 6);
+
+    let color = |vertex: usize| Vector3::new(data[vertex * 6 + 3], data[vertex * 6 + 4], data[vertex * 6 + 5]);
+    assert_eq!(color(0), colormap(0.0));
+    assert_eq!(color(1), colormap(0.5));
+    assert_eq!(color(2), colormap(0.5));
+    assert_eq!(color(3), colormap(1.0));
+}
@@ -0,0 +1,97 @@
+use super::*;
+use crate::mesh::ParametricSurface;
+use std::f64::consts::PI;
+
+/// The unit sphere r = (cos u sin v, sin u sin v, cos v), with N = r_u × r_v pointing inwards,
+/// or outwards if flipped.
+fn sphere(flipped: bool) -> OrientedSurface {
+    let sign = if flipped { -1.0 } else { 1.0 };
+    OrientedSurface::new(
+        |u, v| Vector3::new(u.cos() * v.sin(), u.sin() * v.sin(), v.cos()),
+        move |u, v| {
+            let r_u = Vector3::new(-u.sin() * v.sin(), u.cos() * v.sin(), 0.0);
+            let r_v = Vector3::new(u.cos() * v.cos(), u.sin() * v.cos(), -v.sin());
+            r_u.cross(&r_v) * sign
+        },
+    )
+}
+
+fn radial(p: Vector3) -> Vector3 {
+    p
+}
+
+
+#[test]
+pub fn flux_out_of_the_unit_sphere() {
+    // By the divergence theorem the flux of (x, y, z) out of the sphere is 3 times its volume.
+    let outward = flux(&sphere(true), radial, (0.0, 2.0 * PI), (0.0, PI), 8, Rule::GaussLegendre(3));
+    assert!((outward - 4.0 * PI).abs() < 1e-4, "{}", outward);
+
+    let inward = flux(&sphere(false), radial, (0.0, 2.0 * PI), (0.0, PI), 8, Rule::GaussLegendre(3));
+    assert!((inward + 4.0 * PI).abs() < 1e-4, "{}", inward);
+
+    // A constant field flows in as much as it flows out.
+    let constant = flux(&sphere(true), |_| Vector3::new(0.0, 0.0, 1.0), (0.0, 2.0 * PI), (0.0, PI), 8, Rule::GaussLegendre(3));
+    assert!(constant.abs() < 1e-5, "{}", constant);
+}
+
+#[test]
+pub fn flux_through_a_disc() {
+    // r = (s cos t, s sin t, 0) for s in [0, 1], with N = (0, 0, s): the flux of (0, 0, 2) is 2π.
+    let disc = OrientedSurface::new(|s, t| Vector3::new(s * t.cos(), s * t.sin(), 0.0), |s, _| Vector3::new(0.0, 0.0, s));
+    let value = flux(&disc, |_| Vector3::new(0.0, 0.0, 2.0), (0.0, 1.0), (0.0, 2.0 * PI), 4, Rule::Simpson);
+    assert!((value - 2.0 * PI).abs() < 1e-5, "{}", value);
+}
+
+#[test]
+pub fn samples_have_unit_normals() {
+    let sample = FluxSample::at(&sphere(true), &radial, 0.3, 1.2).unwrap();
+    assert!((sample.normal.norm() - 1.0).abs() < 1e-6);
+    assert!((sample.normal - sample.point).norm() < 1e-6);
+    assert!((sample.density - 1.0).abs() < 1e-6);
+
+    // The normal vanishes at the poles.
+    assert_eq!(FluxSample::at(&sphere(true), &radial, 0.3, 0.0), None);
+    assert_eq!(sample_flux(&sphere(true), radial, (0.0, 6.0), (0.0, 3.0), 4).len(), 16);
+}
+
+#[test]
+pub fn colors_follow_the_sign_of_the_flux() {
+    // A constant upward field flows out through the upper half and in through the lower half.
+    let u_range = (0.0, 2.0 * std::f32::consts::PI);
+    let v_range = (0.0, std::f32::consts::PI);
+    let surface = sphere(true);
+    let field = |_| Vector3::new(0.0, 0.0, 1.0);
+
+    let mut data = ParametricSurface::new(|u, v| { let p = surface.position(u, v); [p.x, p.y, p.z] })
+        .u_range(u_range.0, u_range.1)
+        .v_range(v_range.0, v_range.1)
+        .resolution(8, 8)
+        .periodic(true, false)
+        .build();
+    apply_flux_colors(&mut data, &surface, field, u_range, v_range);
+
+    for vertex in data.vertices.chunks(SurfaceData::COMPONENTS_PER_VERTEX) {
+        let (t, height) = (vertex[3], vertex[1]);  // World y is math z.
+        assert!((0.0..=1.0).contains(&t));
+        if height > 1e-3 && height < 0.999 {
+            assert!(t > 0.5, "Flux out of the top at height {} has {}", height, t);
+        } else if height < -1e-3 && height > -0.999 {
+            assert!(t < 0.5, "Flux into the bottom at height {} has {}", height, t);
+        }
+    }
+}
+
+#[test]
+pub fn normal_arrows_are_colored_by_flux() {
+    let samples = [
+        FluxSample { point: Vector3::new(0.0, 0.0, 1.0), normal: Vector3::new(0.0, 0.0, 1.0), density: 2.0 },
+        FluxSample { point: Vector3::new(0.0, 0.0, -1.0), normal: Vector3::new(0.0, 0.0, -1.0), density: -1.0 },
+    ];
+    let data = create_normal_instances(&samples, 0.5);
+    assert_eq!(data.len(), 18);
+    assert_eq!(&data[0..6], &[0.0, 1.0, -0.0, 0.0, 0.5, -0.0]);
+    let (positive, negative) = (diverging_colormap(1.0), diverging_colormap(-0.5));
+    assert_eq!(&data[6..9], &[positive.x, positive.y, positive.z]);
+    assert_eq!(&data[15..18], &[negative.x, negative.y, negative.z]);
+}
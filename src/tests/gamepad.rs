@@ -0,0 +1,100 @@
+use super::*;
+use crate::test_support::assert_close;
+
+fn snapshot(left: (f32, f32), right: (f32, f32), buttons: &[(usize, f32)]) -> GamepadSnapshot {
+    let mut snapshot = GamepadSnapshot { axes: vec![left.0, left.1, right.0, right.1], buttons: vec![0.0; 17] };
+    for &(index, value) in buttons {
+        snapshot.buttons[index] = value;
+    }
+    snapshot
+}
+
+/// Linear, without dead zone and at 1 unit and radian per second.
+fn linear() -> GamepadSettings {
+    GamepadSettings::new(0.0, 1.0, 1.0, 1.0, false).unwrap()
+}
+
+
+#[test]
+pub fn resting_sticks_do_nothing() {
+    let drifting = snapshot((0.05, -0.1), (0.1, 0.02), &[(GamepadSnapshot::LEFT_TRIGGER, 0.1)]);
+    assert!(CameraMotion::from_snapshot(&drifting, &GamepadSettings::default(), 16.0).is_still());
+    assert!(CameraMotion::from_snapshot(&GamepadSnapshot::default(), &GamepadSettings::default(), 16.0).is_still());
+}
+
+#[test]
+pub fn dead_zone_is_radial_and_rescaled() {
+    let settings = GamepadSettings::new(0.2, 1.0, 1.0, 1.0, false).unwrap();
+    assert_eq!(settings.shape_stick((0.15, 0.1)), (0.0, 0.0));
+
+    // Just outside starts from zero and full deflection reaches one.
+    let (x, _) = settings.shape_stick((0.21, 0.0));
+    assert!(x > 0.0 && x < 0.02);
+    assert_close(settings.shape_stick((1.0, 0.0)).0, 1.0);
+
+    // The direction of a diagonal is kept, and overshoot past the unit circle is clamped.
+    let (x, y) = settings.shape_stick((0.8, 0.8));
+    assert_close(x, y);
+    assert_close((x * x + y * y).sqrt(), 1.0);
+}
+
+#[test]
+pub fn exponent_curves_small_deflections() {
+    let settings = GamepadSettings::new(0.0, 2.0, 1.0, 1.0, false).unwrap();
+    assert_close(settings.shape_stick((0.5, 0.0)).0, 0.25);
+    assert_close(settings.shape_stick((0.0, -1.0)).1, -1.0);
+}
+
+#[test]
+pub fn sticks_walk_and_look() {
+    let forward_right = snapshot((0.5, -1.0), (0.0, 0.0), &[]);
+    let motion = CameraMotion::from_snapshot(&forward_right, &linear(), 500.0);
+    assert_close(motion.forward, 0.5 * (1.0 / 1.25f32.sqrt()));
+    assert_close(motion.right, 0.5 * (0.5 / 1.25f32.sqrt()));
+    assert_eq!((motion.yaw, motion.pitch), (0.0, 0.0));
+
+    // Right on the stick turns right, which is negative yaw, and down looks down.
+    let look = CameraMotion::from_snapshot(&snapshot((0.0, 0.0), (1.0, 0.0), &[]), &linear(), 1000.0);
+    assert_close(look.yaw, -1.0);
+    let look = CameraMotion::from_snapshot(&snapshot((0.0, 0.0), (0.0, 1.0), &[]), &linear(), 1000.0);
+    assert_close(look.pitch, 1.0);
+
+    let inverted = GamepadSettings { invert_y: true, ..linear() };
+    assert_close(CameraMotion::from_snapshot(&snapshot((0.0, 0.0), (0.0, 1.0), &[]), &inverted, 1000.0).pitch, -1.0);
+}
+
+#[test]
+pub fn triggers_and_bumpers() {
+    let pressed = snapshot((0.0, 0.0), (0.0, 0.0), &[
+        (GamepadSnapshot::RIGHT_TRIGGER, 1.0),
+        (GamepadSnapshot::LEFT_TRIGGER, 0.5),
+        (GamepadSnapshot::LEFT_BUMPER, 1.0),
+    ]);
+    let motion = CameraMotion::from_snapshot(&pressed, &linear(), 1000.0);
+    assert_close(motion.up, 0.5);
+    assert_close(motion.roll, -1.0);
+}
+
+#[test]
+pub fn missing_and_invalid_axes_are_centered() {
+    let partial = GamepadSnapshot { axes: vec![f32::NAN, 1.0], buttons: vec![] };
+    let motion = CameraMotion::from_snapshot(&partial, &linear(), 1000.0);
+    assert_eq!((motion.right, motion.yaw, motion.up), (0.0, 0.0, 0.0));
+    assert_close(motion.forward, -1.0);
+}
+
+#[test]
+pub fn motion_moves_the_camera() {
+    let mut camera = FPSCamera::new();
+    let start = camera.position;
+    CameraMotion::from_snapshot(&snapshot((0.0, -1.0), (0.0, 0.0), &[]), &linear(), 1000.0).apply(&mut camera);
+    assert!((camera.position - (start + camera.direction.forward)).norm() < 1e-5);
+}
+
+#[test]
+pub fn settings_are_validated() {
+    assert_eq!(GamepadSettings::new(1.0, 1.0, 1.0, 1.0, false).unwrap_err().field, Some("dead_zone"));
+    assert_eq!(GamepadSettings::new(0.1, 0.0, 1.0, 1.0, false).unwrap_err().field, Some("exponent"));
+    assert_eq!(GamepadSettings::new(0.1, 1.0, f32::NAN, 1.0, false).unwrap_err().field, Some("move_speed"));
+    assert_eq!(GamepadSettings::new(0.1, 1.0, 1.0, -1.0, false).unwrap_err().field, Some("look_speed"));
+}
@@ -0,0 +1,90 @@
+use super::*;
+use crate::test_support::assert_close;
+
+const FIRST: ContactId  = ContactId::Touch(0);
+const SECOND: ContactId = ContactId::Touch(1);
+
+
+#[test]
+pub fn one_finger_rotates() {
+    let mut gestures = Gestures::default();
+    gestures.start(FIRST, 10.0, 10.0);
+    gestures.moved(FIRST, 15.0, 4.0);
+    gestures.moved(FIRST, 20.0, 0.0);
+
+    let delta = gestures.take_delta();
+    assert_eq!((delta.rotate_x, delta.rotate_y), (10.0, 10.0));  // y is up.
+    assert_eq!((delta.pan_x, delta.pan_y, delta.zoom), (0.0, 0.0, 1.0));
+    assert!(gestures.take_delta().is_none());
+}
+
+#[test]
+pub fn two_fingers_pan_together() {
+    let mut gestures = Gestures::default();
+    gestures.start(FIRST, 0.0, 0.0);
+    gestures.start(SECOND, 100.0, 0.0);
+    gestures.moved(FIRST, 10.0, 20.0);
+    gestures.moved(SECOND, 110.0, 20.0);
+
+    let delta = gestures.take_delta();
+    assert_close(delta.pan_x, 10.0);
+    assert_close(delta.pan_y, -20.0);
+    assert_close(delta.zoom, 1.0);
+    assert_eq!((delta.rotate_x, delta.rotate_y), (0.0, 0.0));
+}
+
+#[test]
+pub fn spreading_fingers_zooms_in() {
+    let mut gestures = Gestures::default();
+    gestures.start(FIRST, 50.0, 50.0);
+    gestures.start(SECOND, 150.0, 50.0);
+    gestures.moved(FIRST, 0.0, 50.0);
+    gestures.moved(SECOND, 200.0, 50.0);
+
+    // The spread doubles while the center stays put.
+    let delta = gestures.take_delta();
+    assert_close(delta.zoom, 2.0);
+    assert_close(delta.pan_x, 0.0);
+
+    gestures.moved(SECOND, 100.0, 50.0);
+    assert!(gestures.take_delta().zoom < 1.0);
+}
+
+#[test]
+pub fn adding_and_lifting_fingers_does_not_jump() {
+    let mut gestures = Gestures::default();
+    gestures.start(FIRST, 0.0, 0.0);
+    gestures.start(SECOND, 300.0, 300.0);
+    gestures.end(FIRST);
+    assert!(gestures.take_delta().is_none());
+    assert_eq!(gestures.contact_count(), 1);
+
+    // Moves of lifted or unknown contacts are ignored.
+    gestures.moved(FIRST, 50.0, 50.0);
+    gestures.moved(ContactId::Pointer(0), 50.0, 50.0);
+    assert!(gestures.take_delta().is_none());
+
+    gestures.cancel_all();
+    gestures.moved(SECOND, 0.0, 0.0);
+    assert!(gestures.take_delta().is_none());
+}
+
+#[test]
+pub fn fingers_too_close_do_not_pinch() {
+    let mut gestures = Gestures::default();
+    gestures.start(FIRST, 0.0, 0.0);
+    gestures.start(SECOND, 1.0, 0.0);
+    gestures.moved(SECOND, 2.0, 0.0);
+    assert_eq!(gestures.take_delta().zoom, 1.0);
+}
+
+#[test]
+pub fn trackpad_pinch_zooms() {
+    let mut gestures = Gestures::default();
+    gestures.trackpad_pinch(-10.0);
+    gestures.trackpad_pinch(10.0);
+    assert_close(gestures.take_delta().zoom, 1.0);
+
+    gestures.trackpad_pinch(-10.0);
+    assert!(gestures.take_delta().zoom > 1.0);
+}
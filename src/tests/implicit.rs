@@ -0,0 +1,103 @@
+use super::*;
+
+fn cube(half: f32) -> BoundingBox {
+    BoundingBox::new(Vector3::new(-half, -half, -half), Vector3::new(half, half, half))
+}
+
+fn sphere(p: Vector3) -> f32 {
+    p.norm_squared() - 1.0
+}
+
+fn sphere_gradient(p: Vector3) -> Vector3 {
+    p * 2.0
+}
+
+
+#[test]
+pub fn sphere_vertices_lie_on_the_sphere() {
+    let surface = extract_isosurface(sphere, sphere_gradient, cube(1.5), 24);
+    assert!(!surface.indices.is_empty());
+    assert!(surface.indices.iter().all(|&i| (i as usize) < surface.vertex_count()));
+
+    for i in 0..surface.vertex_count() {
+        let v = surface.vertex(i);
+        let position = Vector3::new(v[0], v[1], v[2]);
+        let normal   = Vector3::new(v[5], v[6], v[7]);
+        assert!((position.norm() - 1.0).abs() < 0.02, "Vertex {:?} is off the sphere", position);
+        assert!((normal.norm() - 1.0).abs() < 1e-5);
+        assert!(normal.dot(&position.normalize()) > 0.999, "Normal {:?} at {:?} isn't radial", normal, position);
+    }
+}
+
+#[test]
+pub fn sphere_is_closed_and_wound_outwards() {
+    let surface = extract_isosurface(sphere, sphere_gradient, cube(1.3), 10);
+
+    // In a closed, consistently wound mesh every directed edge appears once, and its reverse once.
+    let mut directed = std::collections::HashMap::new();
+    for triangle in surface.indices.chunks(3) {
+        for (a, b) in [(triangle[0], triangle[1]), (triangle[1], triangle[2]), (triangle[2], triangle[0])] {
+            *directed.entry((a, b)).or_insert(0) += 1;
+        }
+    }
+    for (&(a, b), &count) in directed.iter() {
+        assert_eq!(count, 1, "Edge ({}, {}) is used twice in the same direction", a, b);
+        assert_eq!(directed.get(&(b, a)), Some(&1), "Edge ({}, {}) has no neighbour", a, b);
+    }
+
+    for triangle in surface.indices.chunks(3) {
+        let p = |i: u32| { let v = surface.vertex(i as usize); Vector3::new(v[0], v[1], v[2]) };
+        let face = (p(triangle[1]) - p(triangle[0])).cross(&(p(triangle[2]) - p(triangle[0])));
+        assert!(face.dot(&p(triangle[0])) > 0.0, "Triangle {:?} faces inwards", triangle);
+    }
+}
+
+#[test]
+pub fn shared_vertices_are_welded() {
+    let surface = extract_isosurface(sphere, sphere_gradient, cube(1.5), 12);
+    // Each vertex of a closed triangle mesh is used by several triangles.
+    assert!(surface.indices.len() > 2 * surface.vertex_count());
+}
+
+#[test]
+pub fn numerical_gradient_is_the_fallback() {
+    // A gradient that is never defined forces the central differences.
+    let exact     = extract_isosurface(sphere, sphere_gradient, cube(1.5), 8);
+    let numerical = extract_isosurface(sphere, |_| Vector3::new(f32::NAN, 0.0, 0.0), cube(1.5), 8);
+    assert_eq!(exact.vertices.len(), numerical.vertices.len());
+    for (a, b) in exact.vertices.iter().zip(numerical.vertices.iter()) {
+        assert!((a - b).abs() < 1e-2);
+    }
+}
+
+#[test]
+pub fn hyperboloid_vertices_satisfy_the_equation() {
+    let hyperboloid = |p: Vector3| p.x * p.x + p.y * p.y - p.z * p.z - 1.0;
+    let gradient    = |p: Vector3| Vector3::new(2.0 * p.x, 2.0 * p.y, -2.0 * p.z);
+    let surface = extract_isosurface(hyperboloid, gradient, cube(2.0), 20);
+    assert!(!surface.indices.is_empty());
+
+    for i in 0..surface.vertex_count() {
+        let v = surface.vertex(i);
+        let (x, y, z) = (v[0], -v[2], v[1]);  // World back to math.
+        assert!(hyperboloid(Vector3::new(x, y, z)).abs() < 0.05);
+    }
+}
+
+#[test]
+pub fn undefined_regions_are_left_open() {
+    // sqrt(x) is undefined for x < 0, so only the half of the sphere with x > 0 remains.
+    let half = |p: Vector3| p.x.sqrt() * 0.0 + sphere(p);
+    let surface = extract_isosurface(half, sphere_gradient, cube(1.5), 12);
+    assert!(!surface.indices.is_empty());
+    for i in 0..surface.vertex_count() {
+        assert!(surface.vertex(i)[0] >= 0.0);
+    }
+}
+
+#[test]
+pub fn nothing_is_extracted_without_a_crossing() {
+    let surface = extract_isosurface(sphere, sphere_gradient, BoundingBox::new(Vector3::new(2.0, 2.0, 2.0), Vector3::new(3.0, 3.0, 3.0)), 4);
+    assert!(surface.vertices.is_empty());
+    assert!(surface.indices.is_empty());
+}
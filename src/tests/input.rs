@@ -0,0 +1,122 @@
+use super::*;
+
+
+#[test]
+pub fn actions_have_names() {
+    for action in Action::ALL.iter() {
+        assert_eq!(Action::from_name(action.name()), Some(*action));
+        assert_eq!(serde_json::to_string(action).unwrap(), format!("\"{}\"", action.name()));
+    }
+    assert_eq!(Action::from_name("jump"), None);
+}
+
+#[test]
+pub fn default_bindings() {
+    let bindings = ActionMap::default();
+    assert_eq!(bindings.keys(Action::MoveForward), &["KeyW", "ArrowUp"]);
+    assert_eq!(bindings.actions("KeyW").collect::<Vec<_>>(), vec![Action::MoveForward]);
+    assert_eq!(bindings.actions("KeyZ").count(), 0);
+    assert!(bindings.is_bound("Escape", Action::ToggleMouseLock));
+}
+
+#[test]
+pub fn rebinding_for_azerty() {
+    let mut bindings = ActionMap::default();
+    bindings.unbind(Action::MoveForward, "KeyW");
+    bindings.bind(Action::MoveForward, "KeyZ").unwrap();
+    bindings.bind(Action::MoveForward, "KeyZ").unwrap();
+    assert_eq!(bindings.keys(Action::MoveForward), &["ArrowUp", "KeyZ"]);
+
+    bindings.clear(Action::MoveLeft);
+    bindings.bind(Action::MoveLeft, "KeyQ").unwrap();
+    assert_eq!(bindings.actions("KeyQ").collect::<Vec<_>>(), vec![Action::MoveLeft, Action::RollLeft]);
+
+    assert_eq!(bindings.bind(Action::MoveLeft, "").unwrap_err().field, Some("key"));
+}
+
+#[test]
+pub fn bindings_round_trip_through_json() {
+    let mut bindings = ActionMap::default();
+    bindings.bind(Action::MoveUp, "KeyR").unwrap();
+    bindings.clear(Action::RollLeft);
+
+    let json = bindings.to_json();
+    assert!(json.contains("\"move_up\":[\"Space\",\"KeyR\"]"), "{}", json);
+    assert_eq!(ActionMap::from_json(&json).unwrap(), bindings);
+}
+
+#[test]
+pub fn missing_actions_keep_their_defaults() {
+    let bindings = ActionMap::from_json(r#"{"move_forward": ["Comma"]}"#).unwrap();
+    assert_eq!(bindings.keys(Action::MoveForward), &["Comma"]);
+    assert_eq!(bindings.keys(Action::MoveBackwards), ActionMap::default().keys(Action::MoveBackwards));
+}
+
+#[test]
+pub fn invalid_json_is_rejected() {
+    for json in &[r#"{"jump": ["Space"]}"#, r#"{"move_up": "Space"}"#, r#"{"move_up": [""]}"#, "[1, 2"] {
+        let error = ActionMap::from_json(json).unwrap_err();
+        assert_eq!(error.field, Some("bindings"), "{}", json);
+    }
+}
+
+#[test]
+pub fn pressed_just_pressed_and_just_released() {
+    let mut keyboard = Keyboard::default();
+    let mut states = ActionStates::default();
+
+    keyboard.key_down("KeyW");
+    states = keyboard.frame_states(&states);
+    assert!(states.pressed(Action::MoveForward) && states.just_pressed(Action::MoveForward));
+
+    states = keyboard.frame_states(&states);
+    assert!(states.pressed(Action::MoveForward) && !states.just_pressed(Action::MoveForward));
+    assert!(!states.just_released(Action::MoveForward));
+
+    keyboard.key_up("KeyW");
+    states = keyboard.frame_states(&states);
+    assert!(!states.pressed(Action::MoveForward) && states.just_released(Action::MoveForward));
+
+    states = keyboard.frame_states(&states);
+    assert_eq!(states, ActionStates::default());
+}
+
+#[test]
+pub fn taps_between_frames_count() {
+    let mut keyboard = Keyboard::default();
+    keyboard.key_down("Space");
+    keyboard.key_up("Space");
+
+    let states = keyboard.frame_states(&ActionStates::default());
+    assert!(states.just_pressed(Action::MoveUp));
+    assert!(keyboard.frame_states(&states).just_released(Action::MoveUp));
+}
+
+#[test]
+pub fn actions_stay_pressed_while_any_key_is_held() {
+    let mut keyboard = Keyboard::default();
+    keyboard.key_down("KeyW");
+    keyboard.key_down("ArrowUp");
+    keyboard.key_up("KeyW");
+    let states = keyboard.frame_states(&ActionStates::default());
+    assert!(states.pressed(Action::MoveForward));
+    assert_eq!(states.axis(Action::MoveBackwards, Action::MoveForward), 1.0);
+
+    keyboard.key_down("KeyS");
+    assert_eq!(keyboard.frame_states(&states).axis(Action::MoveBackwards, Action::MoveForward), 0.0);
+
+    keyboard.release_all();
+    keyboard.frame_states(&states);
+    assert!(!keyboard.frame_states(&states).pressed(Action::MoveForward));
+}
+
+#[test]
+pub fn rebinding_applies_to_held_keys() {
+    let mut keyboard = Keyboard::default();
+    keyboard.key_down("KeyZ");
+    let states = keyboard.frame_states(&ActionStates::default());
+    assert!(!states.pressed(Action::MoveForward));
+
+    keyboard.bindings.bind(Action::MoveForward, "KeyZ").unwrap();
+    assert!(keyboard.frame_states(&states).just_pressed(Action::MoveForward));
+}
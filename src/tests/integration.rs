@@ -0,0 +1,114 @@
+use super::*;
+use crate::test_support::assert_within;
+
+
+#[test]
+pub fn rule_names() {
+    assert_eq!(Rule::from_name("midpoint"), Some(Rule::Midpoint));
+    assert_eq!(Rule::from_name("simpson"), Some(Rule::Simpson));
+    assert_eq!(Rule::from_name("gauss"), Some(Rule::GaussLegendre(3)));
+    assert_eq!(Rule::from_name("gauss5"), Some(Rule::GaussLegendre(5)));
+    assert_eq!(Rule::from_name("gauss0"), None);
+    assert_eq!(Rule::from_name("gauss99"), None);
+    assert_eq!(Rule::from_name("trapezoid"), None);
+}
+
+#[test]
+pub fn gauss_legendre_nodes_and_weights() {
+    let two = gauss_legendre(2);
+    let node = 1.0 / 3f64.sqrt();
+    assert_within(two[0].0.abs(), node, 1e-14);
+    assert_within(two[0].0 + two[1].0, 0.0, 1e-14);
+    assert_within(two[0].1, 1.0, 1e-14);
+
+    // The weights of every rule add up to the length of [-1, 1].
+    for n in 1..=Rule::MAX_GAUSS_POINTS {
+        assert_within(gauss_legendre(n).iter().map(|(_, w)| w).sum(), 2.0, 1e-12);
+    }
+}
+
+#[test]
+pub fn rules_are_exact_for_low_degree_polynomials() {
+    // Midpoint is exact for lines, Simpson for cubics and n point Gauss for degree 2n - 1.
+    assert_within(integrate(|x| 3.0 * x + 1.0, 0.0, 2.0, 1, Rule::Midpoint), 8.0, 1e-12);
+    assert_within(integrate(|x| x * x * x, 0.0, 2.0, 2, Rule::Simpson), 4.0, 1e-12);
+    assert_within(integrate(|x| x.powi(7) + x, -1.0, 2.0, 1, Rule::GaussLegendre(4)), 255.0 / 8.0 + 1.5, 1e-10);
+
+    // Simpson rounds an odd number of subintervals up.
+    assert_within(integrate(|x| x * x * x, 0.0, 2.0, 3, Rule::Simpson), 4.0, 1e-12);
+}
+
+#[test]
+pub fn rules_converge_at_their_order() {
+    let exact = 2.0;  // ∫ sin over [0, pi].
+    for rule in [Rule::Midpoint, Rule::Simpson, Rule::GaussLegendre(2)] {
+        let coarse = (integrate(f64::sin, 0.0, std::f64::consts::PI, 8, rule) - exact).abs();
+        let fine   = (integrate(f64::sin, 0.0, std::f64::consts::PI, 16, rule) - exact).abs();
+        let ratio  = coarse / fine;
+        let expected = 2f64.powi(rule.order());
+        assert!(ratio > 0.8 * expected && ratio < 1.2 * expected, "{:?} converges by {} instead of {}", rule, ratio, expected);
+    }
+}
+
+#[test]
+pub fn double_integrals_over_each_kind_of_region() {
+    let rectangle = Region::Rectangle { x: (0.0, 1.0), y: (0.0, 2.0) };
+    assert_within(double_integral(|x, y| x * y, &rectangle, 4, Rule::Simpson), 1.0, 1e-12);
+
+    // Under the parabola y = x^2 for 0 <= x <= 1: the area is 1/3.
+    let type_i = Region::TypeI { x: (0.0, 1.0), y_lower: Box::new(|_| 0.0), y_upper: Box::new(|x| x * x) };
+    assert_within(double_integral(|_, _| 1.0, &type_i, 4, Rule::GaussLegendre(3)), 1.0 / 3.0, 1e-12);
+
+    // The triangle y <= x <= 1 for 0 <= y <= 1, where ∬ x dA = 1/3.
+    let type_ii = Region::TypeII { y: (0.0, 1.0), x_lower: Box::new(|y| y), x_upper: Box::new(|_| 1.0) };
+    assert_within(double_integral(|x, _| x, &type_ii, 4, Rule::GaussLegendre(2)), 1.0 / 3.0, 1e-12);
+
+    // The same triangle as type I gives the same result.
+    let as_type_i = Region::TypeI { x: (0.0, 1.0), y_lower: Box::new(|_| 0.0), y_upper: Box::new(|x| x) };
+    assert_within(double_integral(|x, _| x, &as_type_i, 4, Rule::GaussLegendre(2)), 1.0 / 3.0, 1e-12);
+}
+
+#[test]
+pub fn triple_integral_over_a_box() {
+    let value = triple_integral(|x, y, z| x * y * z, (0.0, 1.0), (0.0, 2.0), (0.0, 3.0), 2, Rule::Simpson);
+    assert_within(value, 0.5 * 2.0 * 4.5, 1e-12);
+}
+
+#[test]
+pub fn error_estimate_bounds_the_actual_error() {
+    let region = Region::Rectangle { x: (0.0, 1.0), y: (0.0, 1.0) };
+    let exact  = (1f64.exp() - 1.0) * (1f64.exp() - 1.0);
+    let estimate = Estimate::from_refinement(|n| double_integral(|x, y| (x + y).exp(), &region, n, Rule::Midpoint), 8, Rule::Midpoint);
+
+    let actual = (estimate.value - exact).abs();
+    assert!(estimate.error > 0.5 * actual && estimate.error < 2.0 * actual, "Estimated {} for an error of {}", estimate.error, actual);
+}
+
+#[test]
+pub fn prisms_add_up_to_the_midpoint_sum() {
+    let region = Region::TypeI { x: (0.0, 2.0), y_lower: Box::new(|x| -x), y_upper: Box::new(|x| x * x) };
+    let f = |x: f64, y: f64| x - y + 1.0;
+    let prisms = riemann_prisms(f, &region, 10);
+    assert_eq!(prisms.len(), 100);
+
+    let volume: f64 = prisms.iter().map(|p| (p.width * p.depth * p.height) as f64).sum();
+    assert_within(volume, double_integral(f, &region, 10, Rule::Midpoint), 1e-4);
+}
+
+#[test]
+pub fn prism_instances_stand_on_the_plane() {
+    let prisms = [
+        Prism { x: 1.0, y: 2.0, width: 0.5, depth: 0.25, height: 4.0 },
+        Prism { x: 0.0, y: 0.0, width: 0.5, depth: 0.25, height: -2.0 },
+    ];
+    let data = create_prism_instances(&prisms);
+    assert_eq!(data.len(), 2 * 9);
+
+    // Centers and sizes are in world coordinates (y up), with negative values hanging below.
+    assert_eq!(&data[0..6], &[1.0, 2.0, -2.0, 0.5, 4.0, 0.25]);
+    assert_eq!(&data[9..15], &[0.0, -1.0, 0.0, 0.5, 2.0, 0.25]);
+
+    let (highest, lowest) = (colormap(1.0), colormap(0.0));
+    assert_eq!(&data[6..9], &[highest.x, highest.y, highest.z]);
+    assert_eq!(&data[15..18], &[lowest.x, lowest.y, lowest.z]);
+}
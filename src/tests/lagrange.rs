@@ -0,0 +1,134 @@
+use super::*;
+use crate::test_support::{find_at, square};
+use std::f32::consts::FRAC_1_SQRT_2;
+
+/// g = x^2 + y^2 - 1.
+fn unit_circle() -> SmoothFunction {
+    SmoothFunction::new(|x, y| x * x + y * y - 1.0, |x, y| (2.0 * x, 2.0 * y), |_, _| [[2.0, 0.0], [0.0, 2.0]])
+}
+
+fn solve_on_grid(f: &SmoothFunction, g: &SmoothFunction) -> Vec<ConstrainedExtremum> {
+    solve(f, g, square(), &constraint_curve(g, square(), 32))
+}
+
+
+#[test]
+pub fn linear_function_on_a_circle() {
+    // x + y on the unit circle is largest at (1, 1) / √2 and smallest at the opposite point.
+    let f = SmoothFunction::new(|x, y| x + y, |_, _| (1.0, 1.0), |_, _| [[0.0, 0.0], [0.0, 0.0]]);
+    let solutions = solve_on_grid(&f, &unit_circle());
+    assert_eq!(solutions.len(), 2, "{:?}", solutions);
+
+    let maximum = find_at(&solutions, FRAC_1_SQRT_2, FRAC_1_SQRT_2);
+    assert_eq!(maximum.kind, Classification::Maximum);
+    assert!((maximum.z - 2f32.sqrt()).abs() < 1e-5);
+    assert!((maximum.lambda - FRAC_1_SQRT_2).abs() < 1e-5);
+
+    assert_eq!(find_at(&solutions, -FRAC_1_SQRT_2, -FRAC_1_SQRT_2).kind, Classification::Minimum);
+}
+
+#[test]
+pub fn product_on_a_line() {
+    // xy on x + y = 2 is x (2 - x), with its maximum at (1, 1).
+    let f = SmoothFunction::new(|x, y| x * y, |x, y| (y, x), |_, _| [[0.0, 1.0], [1.0, 0.0]]);
+    let g = SmoothFunction::new(|x, y| x + y - 2.0, |_, _| (1.0, 1.0), |_, _| [[0.0, 0.0], [0.0, 0.0]]);
+    let solutions = solve_on_grid(&f, &g);
+    assert_eq!(solutions.len(), 1, "{:?}", solutions);
+    assert_eq!(find_at(&solutions, 1.0, 1.0).kind, Classification::Maximum);
+}
+
+#[test]
+pub fn quadratic_on_a_circle_has_four_extrema() {
+    // x^2 - y^2 on the unit circle: maxima at (±1, 0) and minima at (0, ±1).
+    let f = SmoothFunction::new(|x, y| x * x - y * y, |x, y| (2.0 * x, -2.0 * y), |_, _| [[2.0, 0.0], [0.0, -2.0]]);
+    let solutions = solve_on_grid(&f, &unit_circle());
+    assert_eq!(solutions.len(), 4, "{:?}", solutions);
+    assert_eq!(find_at(&solutions, 1.0, 0.0).kind, Classification::Maximum);
+    assert_eq!(find_at(&solutions, -1.0, 0.0).kind, Classification::Maximum);
+    assert_eq!(find_at(&solutions, 0.0, 1.0).kind, Classification::Minimum);
+    assert_eq!(find_at(&solutions, 0.0, -1.0).kind, Classification::Minimum);
+
+    // The gradients are parallel at every solution.
+    for solution in &solutions {
+        let (fx, fy) = f.gradient(solution.x, solution.y);
+        let (gx, gy) = unit_circle().gradient(solution.x, solution.y);
+        assert!((fx * gy - fy * gx).abs() < 1e-4);
+    }
+}
+
+#[test]
+pub fn constraint_outside_the_domain_has_no_solutions() {
+    let f = SmoothFunction::new(|x, y| x + y, |_, _| (1.0, 1.0), |_, _| [[0.0, 0.0], [0.0, 0.0]]);
+    let g = SmoothFunction::new(|x, y| x * x + y * y - 100.0, |x, y| (2.0 * x, 2.0 * y), |_, _| [[2.0, 0.0], [0.0, 2.0]]);
+    assert!(constraint_curve(&g, square(), 16).is_empty());
+    assert!(solve_on_grid(&f, &g).is_empty());
+}
+
+#[test]
+pub fn constraint_is_lifted_onto_the_surface() {
+    let f = SmoothFunction::new(|x, y| x * y, |x, y| (y, x), |_, _| [[0.0, 1.0], [1.0, 0.0]]);
+    let curve = constraint_curve(&unit_circle(), square(), 16);
+    let vertices = create_constraint_lines(&f, &curve);
+    assert_eq!(vertices.len(), curve.len() * 12);
+
+    for vertex in vertices.chunks(6) {
+        let (x, y, z) = (vertex[0], -vertex[2], vertex[1]);  // World back to math.
+        assert!((z - 0.01 - f.value(x, y)).abs() < 1e-5);
+        assert_eq!(&vertex[3..6], &CONSTRAINT_COLOR);
+    }
+}
+
+#[test]
+pub fn gradient_arrows_have_the_same_length() {
+    let f = SmoothFunction::new(|x, _| 3.0 * x, |_, _| (3.0, 0.0), |_, _| [[0.0, 0.0], [0.0, 0.0]]);
+    let solution = ConstrainedExtremum { x: 1.0, y: 0.0, z: 3.0, lambda: 1.5, kind: Classification::Maximum };
+    let data = create_gradient_instances(&f, &unit_circle(), &[solution], 0.5);
+
+    assert_eq!(data, vec![
+        1.0, 3.0, -0.0, 0.5, 0.0, -0.0, GRADIENT_F_COLOR[0], GRADIENT_F_COLOR[1], GRADIENT_F_COLOR[2],
+        1.0, 3.0, -0.0, 0.5, 0.0, -0.0, GRADIENT_G_COLOR[0], GRADIENT_G_COLOR[1], GRADIENT_G_COLOR[2],
+    ]);
+}
+
+#[test]
+pub fn scaling_f_finds_the_same_extrema() {
+    // The residual check is relative, so neither a steep nor a flat f changes what's found.
+    for scale in [1e-4, 1.0, 1e3] {
+        let f = SmoothFunction::new(move |x, y| scale * x * y, move |x, y| (scale * y, scale * x), move |_, _| [[0.0, scale], [scale, 0.0]]);
+        let g = SmoothFunction::new(|x, y| x + y - 2.0, |_, _| (1.0, 1.0), |_, _| [[0.0, 0.0], [0.0, 0.0]]);
+        let solutions = solve_on_grid(&f, &g);
+        assert_eq!(solutions.len(), 1, "Scale {}: {:?}", scale, solutions);
+        assert_eq!(find_at(&solutions, 1.0, 1.0).kind, Classification::Maximum);
+
+        let f = SmoothFunction::new(move |x, y| scale * (x * x - y * y), move |x, y| (scale * 2.0 * x, -scale * 2.0 * y), move |_, _| [[scale * 2.0, 0.0], [0.0, -scale * 2.0]]);
+        let solutions = solve_on_grid(&f, &unit_circle());
+        assert_eq!(solutions.len(), 4, "Scale {}: {:?}", scale, solutions);
+    }
+}
+
+#[test]
+pub fn scaling_g_finds_the_same_extrema() {
+    for scale in [1e-4, 1.0, 1e3] {
+        let f = SmoothFunction::new(|x, y| x + y, |_, _| (1.0, 1.0), |_, _| [[0.0, 0.0], [0.0, 0.0]]);
+        let g = SmoothFunction::new(move |x, y| scale * (x * x + y * y - 1.0), move |x, y| (scale * 2.0 * x, scale * 2.0 * y), move |_, _| [[scale * 2.0, 0.0], [0.0, scale * 2.0]]);
+        let solutions = solve_on_grid(&f, &g);
+        assert_eq!(solutions.len(), 2, "Scale {}: {:?}", scale, solutions);
+        assert_eq!(find_at(&solutions, FRAC_1_SQRT_2, FRAC_1_SQRT_2).kind, Classification::Maximum);
+    }
+}
+
+#[test]
+pub fn the_residual_is_relative_to_the_gradients() {
+    // A step tolerance away from the maximum of a steep f, ∇f - λ∇g is about 1e-3 in absolute
+    // terms, which is still only rounding compared to ∇f.
+    let f = SmoothFunction::new(|x, y| 1e3 * (x + y), |_, _| (1e3, 1e3), |_, _| [[0.0, 0.0], [0.0, 0.0]]);
+    let (x, y) = (FRAC_1_SQRT_2 + 1e-6, FRAC_1_SQRT_2 - 1e-6);
+    assert!(is_converged(&f, &unit_circle(), square(), x, y, 1e3 * FRAC_1_SQRT_2));
+
+    // Off the line by a tenth, the residual of a flat f and g is tiny in absolute terms but large
+    // compared to their gradients.
+    let f = SmoothFunction::new(|x, y| 1e-4 * x * y, |x, y| (1e-4 * y, 1e-4 * x), |_, _| [[0.0, 1e-4], [1e-4, 0.0]]);
+    let g = SmoothFunction::new(|x, y| 1e-4 * (x + y - 2.0), |_, _| (1e-4, 1e-4), |_, _| [[0.0, 0.0], [0.0, 0.0]]);
+    assert!(!is_converged(&f, &g, square(), 1.1, 1.0, 1.0));
+    assert!(is_converged(&f, &g, square(), 1.0, 1.0, 1.0));
+}
@@ -0,0 +1,87 @@
+use super::*;
+use crate::fields::diverging_colormap;
+use std::f64::consts::PI;
+
+fn circle(t: f32) -> Vector3 {
+    Vector3::new(t.cos(), t.sin(), 0.0)
+}
+
+fn circle_velocity(t: f32) -> Vector3 {
+    Vector3::new(-t.sin(), t.cos(), 0.0)
+}
+
+fn rotation(p: Vector3) -> Vector3 {
+    Vector3::new(-p.y, p.x, 0.0)
+}
+
+
+#[test]
+pub fn circulation_around_the_unit_circle() {
+    // By Green's theorem, ∮ -y dx + x dy is twice the enclosed area.
+    let work = line_integral(circle, circle_velocity, rotation, 0.0, 2.0 * PI, 16, Rule::GaussLegendre(3));
+    assert!((work - 2.0 * PI).abs() < 1e-5, "{}", work);
+
+    // Going around the other way flips the sign.
+    let backwards = line_integral(|t| circle(-t), |t| -circle_velocity(-t), rotation, 0.0, 2.0 * PI, 16, Rule::GaussLegendre(3));
+    assert!((backwards + 2.0 * PI).abs() < 1e-5, "{}", backwards);
+}
+
+#[test]
+pub fn conservative_fields_only_depend_on_the_endpoints() {
+    // F = ∇(xyz), along a helix and along a straight line between the same endpoints.
+    let potential = |p: Vector3| (p.x * p.y * p.z) as f64;
+    let field = |p: Vector3| Vector3::new(p.y * p.z, p.x * p.z, p.x * p.y);
+
+    let helix   = |t: f32| Vector3::new(t.cos(), t.sin(), t);
+    let helix_velocity = |t: f32| Vector3::new(-t.sin(), t.cos(), 1.0);
+    let (start, end) = (helix(0.0), helix(3.0));
+    let line = |t: f32| start + (end - start) * (t / 3.0);
+
+    let along_helix = line_integral(helix, helix_velocity, field, 0.0, 3.0, 32, Rule::Simpson);
+    let along_line  = line_integral(line, |_| (end - start) / 3.0, field, 0.0, 3.0, 32, Rule::Simpson);
+    let expected = potential(end) - potential(start);
+    assert!((along_helix - expected).abs() < 1e-4, "{} != {}", along_helix, expected);
+    assert!((along_line - expected).abs() < 1e-4, "{} != {}", along_line, expected);
+}
+
+#[test]
+pub fn samples_measure_work_along_the_motion() {
+    let sample = WorkSample::at(&circle, &circle_velocity, &rotation, 0.5).unwrap();
+    assert!((sample.tangent.norm() - 1.0).abs() < 1e-6);
+    assert!((sample.contribution() - 1.0).abs() < 1e-6);
+
+    // A field against the motion does negative work; one across it does none.
+    let against = WorkSample::at(&circle, &circle_velocity, &|p| -rotation(p), 0.5).unwrap();
+    assert!((against.contribution() + 1.0).abs() < 1e-6);
+    let radial = WorkSample::at(&circle, &circle_velocity, &|p| p, 0.5).unwrap();
+    assert!(radial.contribution().abs() < 1e-6);
+
+    // Undefined fields and curves that stop are skipped.
+    assert_eq!(WorkSample::at(&circle, &circle_velocity, &|p: Vector3| p / 0.0 * 0.0, 0.5), None);
+    assert_eq!(WorkSample::at(&circle, &|_| Vector3::ZERO, &rotation, 0.5), None);
+}
+
+#[test]
+pub fn work_lines_are_colored_by_sign_and_leave_gaps() {
+    // F = (x, 0, 0) along the line from (-1, 0, 0) to (1, 0, 0): against the motion, then with it.
+    // The field is undefined at x = 0, where the line must be broken.
+    let field = |p: Vector3| if p.x == 0.0 { Vector3::new(f32::NAN, 0.0, 0.0) } else { Vector3::new(p.x, 0.0, 0.0) };
+    let samples = sample_work(|t| Vector3::new(t, 0.0, 0.0), |_| Vector3::new(1.0, 0.0, 0.0), field, -1.0, 1.0, 5);
+    assert_eq!(samples.len(), 4);
+
+    let vertices = create_work_lines(&samples, 0.5);
+    assert_eq!(vertices.len(), 2 * 12);
+    assert_eq!(Vector3::new(vertices[3], vertices[4], vertices[5]), diverging_colormap(-0.75));
+    assert_eq!(Vector3::new(vertices[15], vertices[16], vertices[17]), diverging_colormap(0.75));
+}
+
+#[test]
+pub fn sample_instances_scale_the_field() {
+    let sample = WorkSample { t: 0.0, point: Vector3::new(1.0, 0.0, 0.0), tangent: Vector3::new(0.0, 1.0, 0.0), field: Vector3::new(0.0, 0.0, 2.0) };
+    let data = create_sample_instances(&sample, 0.5, 4.0);
+    assert_eq!(data.len(), 18);
+    assert_eq!(&data[3..6], &[0.0, 0.0, -0.5]);
+    assert_eq!(&data[12..15], &[0.0, 0.25, -0.0]);
+    assert_eq!(&data[6..9], &TANGENT_COLOR);
+    assert_eq!(&data[15..18], &FIELD_COLOR);
+}
@@ -0,0 +1,94 @@
+use super::*;
+use crate::fields;
+use crate::test_support::square;
+use crate::utils::Placement;
+use std::f32::consts::PI;
+
+fn sphere(u: f32, v: f32) -> [f32; 3] {
+    [u.cos() * v.sin(), u.sin() * v.sin(), v.cos()]
+}
+
+
+#[test]
+pub fn parametric_surface_counts() {
+    let surface = ParametricSurface::new(|u, v| [u, v, 0.0]).resolution(4, 2).build();
+    assert_eq!(surface.vertex_count(), 5 * 3);
+    assert_eq!(surface.indices.len(), 4 * 2 * 6);
+}
+
+#[test]
+pub fn parametric_sphere_normals_point_outwards() {
+    let surface = ParametricSurface::new(sphere)
+        .u_range(0.0, 2.0 * PI)
+        .v_range(PI, 0.0)  // From the south to the north pole, so r_u x r_v points outwards.
+        .resolution(16, 8)
+        .periodic(true, false)
+        .build();
+
+    for i in 0..surface.vertex_count() {
+        let vertex   = surface.vertex(i);
+        let position = Vector3::new(vertex[0], vertex[1], vertex[2]);
+        let normal   = Vector3::new(vertex[5], vertex[6], vertex[7]);
+
+        assert!((normal.norm() - 1.0).abs() < 1e-3, "Normal {:?} at {} isn't normalized", normal, i);
+        assert!(normal.dot(&position) > 0.9, "Normal {:?} at {:?} isn't pointing outwards", normal, position);
+    }
+}
+
+#[test]
+pub fn parametric_seams_are_welded_when_the_ends_meet() {
+    let torus = |u: f32, v: f32| [(2.0 + v.cos()) * u.cos(), (2.0 + v.cos()) * u.sin(), v.sin()];
+    let surface = ParametricSurface::new(torus)
+        .u_range(0.0, 2.0 * PI)
+        .v_range(0.0, 2.0 * PI)
+        .resolution(12, 6)
+        .periodic(true, true)
+        .build();
+
+    let columns = 13;
+    for row in 0..7 {
+        let first = surface.vertex(row * columns);
+        let last  = surface.vertex(row * columns + columns - 1);
+        assert_eq!(&first[0..3], &last[0..3]);
+        assert_eq!(&first[5..8], &last[5..8]);
+        assert_eq!((first[3], last[3]), (0.0, 1.0));  // Texture coordinates still span [0, 1].
+    }
+}
+
+#[test]
+pub fn parametric_seams_are_kept_when_the_ends_differ() {
+    // A Möbius strip meets itself with v flipped, so the ends must not be snapped together.
+    let mobius = |u: f32, v: f32| {
+        let r = 1.0 + 0.5 * v * (u / 2.0).cos();
+        [r * u.cos(), r * u.sin(), 0.5 * v * (u / 2.0).sin()]
+    };
+    let surface = ParametricSurface::new(mobius)
+        .u_range(0.0, 2.0 * PI)
+        .v_range(-1.0, 1.0)
+        .resolution(16, 2)
+        .periodic(true, false)
+        .build();
+
+    let first = surface.vertex(0);
+    let last  = surface.vertex(16);
+    assert!((first[0] - last[0]).abs() > 0.5);
+}
+
+#[test]
+pub fn instanced_meshes_without_instances_draw_nothing() {
+    // The gradient of sqrt(x^2 + y^2) is undefined at the origin, the only sample at density 1.
+    let arrows = fields::sample_gradient_field(
+        |x, y| (x * x + y * y).sqrt(),
+        |x, y| (x / (x * x + y * y).sqrt(), y / (x * x + y * y).sqrt()),
+        square(),
+        1,
+        Placement::Both,
+    );
+    let instances = fields::create_instances(&arrows, 1.0);
+    let components: i32 = fields::INSTANCE_ATTRIBUTE_SIZES.iter().sum();
+    assert!(instances.is_empty());
+
+    assert_eq!(DrawCall::for_mesh(true, instances.len() as i32 / components), DrawCall::Nothing);
+    assert_eq!(DrawCall::for_mesh(true, 4), DrawCall::Instanced(4));
+    assert_eq!(DrawCall::for_mesh(false, 0), DrawCall::Single);
+}
@@ -0,0 +1,55 @@
+use super::*;
+use crate::utils::{create_surface, Domain};
+use crate::test_support::assert_within;
+
+
+#[test]
+pub fn ray_through_the_center_follows_the_view_direction() {
+    let eye    = Vector3::new(0.0, 2.0, 5.0);
+    let view   = Matrix4::look_at(eye, Vector3::ZERO, Vector3::new(0.0, 1.0, 0.0));
+    let projection = Matrix4::perspective(16.0 / 9.0, std::f32::consts::PI / 3.0, 0.1, 100.0);
+
+    let ray = Ray::through_pixel(&view, &projection, 400.0, 300.0, 800.0, 600.0).unwrap();
+    assert_within(ray.direction, -eye.normalize(), 1e-4);
+    // The origin is on the near plane, in front of the eye.
+    assert!((ray.origin - eye).norm() > 0.09 && (ray.origin - eye).norm() < 0.2);
+
+    // A pixel to the top right leans right and up from the view direction.
+    let corner = Ray::through_pixel(&view, &projection, 800.0, 0.0, 800.0, 600.0).unwrap();
+    assert!(corner.direction.x > 0.0);
+    assert!(corner.direction.y > ray.direction.y);
+}
+
+#[test]
+pub fn triangle_hits_and_misses() {
+    let (a, b, c) = (Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+    let down = Vector3::new(0.0, 0.0, -1.0);
+
+    assert_eq!(intersect_triangle(&Ray::new(Vector3::new(0.25, 0.25, 2.0), down), a, b, c), Some(2.0));
+    // From below, as surfaces are two sided.
+    assert_eq!(intersect_triangle(&Ray::new(Vector3::new(0.25, 0.25, -1.0), -down), a, b, c), Some(1.0));
+
+    // Outside the triangle, behind the origin and parallel to it.
+    assert_eq!(intersect_triangle(&Ray::new(Vector3::new(0.75, 0.75, 2.0), down), a, b, c), None);
+    assert_eq!(intersect_triangle(&Ray::new(Vector3::new(0.25, 0.25, 2.0), -down), a, b, c), None);
+    assert_eq!(intersect_triangle(&Ray::new(Vector3::new(0.25, 0.25, 2.0), Vector3::new(1.0, 0.0, 0.0)), a, b, c), None);
+}
+
+#[test]
+pub fn pick_finds_the_closest_hit_on_a_surface() {
+    // z = sin(x) + y over [-2, 2]^2, looked at from above along the math z-axis (world y).
+    let f = |x: f32, y: f32| x.sin() + y;
+    let surface = create_surface(f, Domain::new(-2.0, 2.0, -2.0, 2.0), 64, 64);
+
+    let world = crate::utils::math_to_world(0.5, -1.0, 10.0);
+    let ray   = Ray::new(Vector3::new(world[0], world[1], world[2]), Vector3::new(0.0, -1.0, 0.0));
+    let hit   = pick_surface(&ray, &surface).unwrap();
+
+    let (x, y, z) = (hit.x, -hit.z, hit.y);
+    assert!((x - 0.5).abs() < 1e-5 && (y + 1.0).abs() < 1e-5);
+    assert!((z - f(x, y)).abs() < 1e-2);
+
+    // Outside the domain nothing is hit.
+    let outside = Ray::new(Vector3::new(3.0, 10.0, 0.0), Vector3::new(0.0, -1.0, 0.0));
+    assert_eq!(pick_surface(&outside, &surface), None);
+}
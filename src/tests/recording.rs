@@ -0,0 +1,148 @@
+use super::*;
+use crate::camera::Camera;
+use crate::controls;
+use crate::gestures::ContactId;
+
+
+const WIDTH: f32  = 640.0;
+const HEIGHT: f32 = 480.0;
+const DT: f32     = 16.0;
+
+
+/// The events of each frame of a short walkthrough: flying with the keys and the gamepad, looking
+/// around with the mouse, then orbiting with the mouse, the wheel and two fingers.
+fn session() -> Vec<(CameraMode, Vec<InputEvent>, Option<GamepadSnapshot>)> {
+    let stick = GamepadSnapshot { axes: vec![0.3, -0.8, 0.5, 0.2], buttons: vec![0.0; 8] };
+    let key = |code: &str| String::from(code);
+
+    let mut frames = vec![
+        (CameraMode::Fps, vec![InputEvent::MouseMove { x: 500.0, y: 100.0 }, InputEvent::KeyDown(key("KeyW"))], None),
+        (CameraMode::Fps, vec![InputEvent::KeyDown(key("KeyQ"))], Some(stick.clone())),
+        (CameraMode::Fps, vec![InputEvent::KeyUp(key("KeyQ")), InputEvent::KeyDown(key("Space"))], Some(stick)),
+        (CameraMode::Fps, vec![InputEvent::KeyUp(key("KeyW")), InputEvent::KeyUp(key("Space"))], None),
+        (CameraMode::Orbit, vec![InputEvent::MouseDown { button: 0, x: 300.0, y: 200.0 }], None),
+    ];
+    for step in 1..=20 {
+        let x = 300.0 + 7.0 * step as f32;
+        frames.push((CameraMode::Orbit, vec![InputEvent::MouseMove { x, y: 200.0 - 3.0 * step as f32 }], None));
+    }
+    frames.push((CameraMode::Orbit, vec![InputEvent::MouseUp { x: 440.0, y: 140.0 }, InputEvent::Wheel(-120.0)], None));
+    frames.push((CameraMode::Orbit, vec![
+        InputEvent::ContactStart { id: ContactId::Touch(0), x: 200.0, y: 200.0 },
+        InputEvent::ContactStart { id: ContactId::Touch(1), x: 300.0, y: 200.0 },
+    ], None));
+    for step in 1..=10 {
+        let spread = 5.0 * step as f32;
+        frames.push((CameraMode::Orbit, vec![
+            InputEvent::ContactMove { id: ContactId::Touch(0), x: 200.0 - spread, y: 200.0 + step as f32 },
+            InputEvent::ContactMove { id: ContactId::Touch(1), x: 300.0 + spread, y: 200.0 + step as f32 },
+        ], None));
+    }
+    frames.push((CameraMode::Orbit, vec![InputEvent::Blur], None));
+    frames.push((CameraMode::Fps, vec![InputEvent::KeyDown(key("KeyD"))], None));
+    frames
+}
+
+/// Plays the session live, recording it like `Client::update` does.
+fn record(camera: &mut Camera) -> Recording {
+    let gamepad = GamepadSettings::default();
+    let mut input = Input::new();
+    input.frame_start(0.0, WIDTH, HEIGHT);
+
+    let snapshot = camera.snapshot();
+    camera.restore(&snapshot);
+    let mut recording = Recording::new(snapshot, gamepad);
+    input.set_recording(true);
+
+    for (mode, events, pad) in session() {
+        for event in events {
+            input.handle(event);
+        }
+        let frame = Frame::capture(&mut input, DT, WIDTH, HEIGHT, pad, mode);
+        controls::update_camera(camera, &frame, &gamepad);
+        recording.frames.push(frame);
+    }
+    recording
+}
+
+fn replay(camera: &mut Camera, recording: Recording) {
+    camera.restore(&recording.camera);
+    let mut replay = Replay::new(recording);
+    while let Some(frame) = replay.next_frame().cloned() {
+        controls::update_camera(camera, &frame, &replay.recording().gamepad);
+    }
+    assert!(replay.is_finished());
+}
+
+
+#[test]
+pub fn replays_retrace_the_camera_exactly() {
+    let mut live = Camera::new();
+    live.fps.rotate(0.4, 0.1, 0.0);
+    let recording = record(&mut live);
+    assert_ne!(live.snapshot(), recording.camera);
+
+    let mut replayed = Camera::new();
+    replay(&mut replayed, Recording::from_bytes(&recording.to_bytes()).unwrap());
+    assert_eq!(replayed.snapshot(), live.snapshot());
+    assert_eq!(replayed.is_transitioning(), live.is_transitioning());
+    assert_eq!(replayed.view_matrix(), live.view_matrix());
+
+    // Twice in a row, e.g. in a regression test.
+    let first = replayed.snapshot();
+    replay(&mut replayed, recording);
+    assert_eq!(replayed.snapshot(), first);
+}
+
+#[test]
+pub fn recordings_round_trip_through_bytes() {
+    let recording = record(&mut Camera::new());
+    assert_eq!(recording.frames.len(), session().len());
+    assert_eq!(recording.duration(), DT * session().len() as f32);
+
+    let bytes = recording.to_bytes();
+    assert_eq!(Recording::from_bytes(&bytes).unwrap(), recording);
+}
+
+#[test]
+pub fn recorded_events_reproduce_the_states() {
+    let recording = record(&mut Camera::new());
+    assert_eq!(recording.frames[0].events, session()[0].1);
+
+    let mut input = Input::new();
+    input.frame_start(0.0, WIDTH, HEIGHT);
+    for frame in &recording.frames {
+        for event in &frame.events {
+            input.handle(event.clone());
+        }
+        assert_eq!(input.frame_start(frame.dt, WIDTH, HEIGHT), frame.state);
+    }
+}
+
+#[test]
+pub fn events_are_only_kept_while_recording() {
+    let mut input = Input::new();
+    input.handle(InputEvent::KeyDown(String::from("KeyW")));
+    assert!(input.take_recorded_events().is_empty());
+
+    input.set_recording(true);
+    input.handle(InputEvent::Wheel(3.0));
+    assert_eq!(input.take_recorded_events(), vec![InputEvent::Wheel(3.0)]);
+    assert!(input.take_recorded_events().is_empty());
+
+    input.handle(InputEvent::Wheel(1.0));
+    input.set_recording(false);
+    assert!(input.take_recorded_events().is_empty());
+}
+
+#[test]
+pub fn invalid_recordings_are_rejected() {
+    let mut other_version = Recording::new(Camera::new().snapshot(), GamepadSettings::default());
+    other_version.version = Recording::VERSION + 1;
+    let truncated = record(&mut Camera::new()).to_bytes();
+
+    for bytes in [&other_version.to_bytes()[..], &truncated[..truncated.len() / 2], &[1, 2], &[]] {
+        let error = Recording::from_bytes(bytes).unwrap_err();
+        assert_eq!(error.field, Some("recording"));
+    }
+}
@@ -0,0 +1,77 @@
+//! Assertions and fixtures shared by the tests of several modules.
+
+use std::fmt::Debug;
+
+use crate::math::Vector3;
+
+use crate::critical::CriticalPoint;
+use crate::lagrange::ConstrainedExtremum;
+use crate::utils::Domain;
+
+
+/// Values with a distance between them, to compare results up to rounding.
+pub trait Distance: Debug {
+    fn distance(&self, other: &Self) -> f64;
+}
+
+impl Distance for f32 {
+    fn distance(&self, other: &Self) -> f64 {
+        (self - other).abs() as f64
+    }
+}
+
+impl Distance for f64 {
+    fn distance(&self, other: &Self) -> f64 {
+        (self - other).abs()
+    }
+}
+
+impl Distance for Vector3 {
+    fn distance(&self, other: &Self) -> f64 {
+        (*self - *other).norm() as f64
+    }
+}
+
+impl<T: Distance + ?Sized> Distance for &T {
+    fn distance(&self, other: &Self) -> f64 {
+        (**self).distance(*other)
+    }
+}
+
+/// Closer than 1e-5, i.e. equal up to the rounding of a few `f32` operations.
+pub fn assert_close<T: Distance>(a: T, b: T) {
+    assert_within(a, b, 1e-5);
+}
+
+pub fn assert_within<T: Distance>(a: T, b: T, tolerance: f64) {
+    assert!(a.distance(&b) < tolerance, "{:?} != {:?}", a, b);
+}
+
+
+/// [-2, 2] x [-2, 2].
+pub fn square() -> Domain {
+    Domain::new(-2.0, 2.0, -2.0, 2.0)
+}
+
+/// Points found on a surface, like critical points.
+pub trait Located: Copy + Debug {
+    fn xy(&self) -> (f32, f32);
+}
+
+impl Located for CriticalPoint {
+    fn xy(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+}
+
+impl Located for ConstrainedExtremum {
+    fn xy(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+}
+
+/// The point found at (x, y).
+pub fn find_at<T: Located>(points: &[T], x: f32, y: f32) -> T {
+    *points.iter().find(|point| (point.xy().0 - x).hypot(point.xy().1 - y) < 1e-3)
+        .unwrap_or_else(|| panic!("Nothing found at ({}, {}) in {:?}", x, y, points))
+}
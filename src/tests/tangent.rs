@@ -0,0 +1,67 @@
+use super::*;
+
+fn paraboloid(x: f32, y: f32) -> f32 {
+    x * x + y * y
+}
+
+fn paraboloid_gradient(x: f32, y: f32) -> (f32, f32) {
+    (2.0 * x, 2.0 * y)
+}
+
+
+#[test]
+pub fn plane_touches_the_surface() {
+    let plane = TangentPlane::at(paraboloid, paraboloid_gradient, 1.0, -2.0).unwrap();
+    assert_eq!(plane, TangentPlane { x: 1.0, y: -2.0, z: 5.0, fx: 2.0, fy: -4.0 });
+    assert_eq!(plane.linearization(1.0, -2.0), 5.0);
+
+    // The linearization is within O(h^2) of f nearby.
+    let h = 0.01;
+    assert!((plane.linearization(1.0 + h, -2.0 + h) - paraboloid(1.0 + h, -2.0 + h)).abs() < 3.0 * h * h);
+
+    // The normal is perpendicular to both tangent directions (1, 0, fx) and (0, 1, fy).
+    let normal = plane.normal();
+    assert!((normal.norm() - 1.0).abs() < 1e-6);
+    assert!(normal.dot(&Vector3::new(1.0, 0.0, plane.fx)).abs() < 1e-6);
+    assert!(normal.dot(&Vector3::new(0.0, 1.0, plane.fy)).abs() < 1e-6);
+    assert!(normal.z > 0.0);
+}
+
+#[test]
+pub fn undefined_points_have_no_plane() {
+    assert_eq!(TangentPlane::at(|x, _| x.ln(), |x, _| (1.0 / x, 0.0), -1.0, 0.0), None);
+    assert_eq!(TangentPlane::at(|x, _| x.abs().sqrt(), |x, _| (0.5 / x.sqrt(), 0.0), 0.0, 0.0), None);
+}
+
+#[test]
+pub fn equations() {
+    let plane = |x, y, z, fx, fy| TangentPlane { x, y, z, fx, fy }.equation();
+
+    assert_eq!(plane(1.0, -2.0, 5.0, 2.0, -4.0), "z = 5 + 2 (x - 1) - 4 (y + 2)");
+    assert_eq!(plane(0.0, 0.0, 0.0, 0.0, 0.0), "z = 0");
+    assert_eq!(plane(0.0, 0.0, 0.0, -1.0, 1.0), "z = -x + y");
+    assert_eq!(plane(0.5, 0.0, -1.25, 0.0, 0.333_333), "z = -1.25 + 0.3333 y");
+    // Rounding doesn't leave a negative zero.
+    assert_eq!(plane(0.0, -0.000_01, 1.0, 0.0, 1.0), "z = 1 + y");
+}
+
+#[test]
+pub fn vertices_span_the_plane_around_the_point() {
+    let plane    = TangentPlane::at(paraboloid, paraboloid_gradient, 1.0, 1.0).unwrap();
+    let vertices = plane.create_vertices(0.5);
+    assert_eq!(vertices.len(), VERTICES_3D_INDEXED_RECTANGLE.len());
+
+    for corner in vertices.chunks(3) {
+        let (x, y, z) = (corner[0], -corner[2], corner[1]);  // World back to math.
+        assert_eq!((x - 1.0).abs(), 0.5);
+        assert_eq!((y - 1.0).abs(), 0.5);
+        assert!((z - plane.linearization(x, y)).abs() < 1e-6);
+    }
+}
+
+#[test]
+pub fn info_reports_the_equation() {
+    let info = TangentPlane::at(paraboloid, paraboloid_gradient, 0.0, 0.0).unwrap().info();
+    assert_eq!(info.equation, "z = 0");
+    assert_eq!(info.normal, [0.0, 0.0, 1.0]);
+}
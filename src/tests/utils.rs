@@ -0,0 +1,53 @@
+use super::*;
+
+
+#[test]
+pub fn surface_vertex_and_index_counts() {
+    let surface = create_surface(|x, y| x + y, Domain::new(-1.0, 1.0, -1.0, 1.0), 4, 3);
+    assert_eq!(surface.vertex_count(), 5 * 4);
+    assert_eq!(surface.indices.len(), 4 * 3 * 6);
+    assert!(surface.indices.iter().all(|&i| (i as usize) < surface.vertex_count()));
+}
+
+#[test]
+pub fn surface_positions_and_texture_coordinates() {
+    let surface = create_surface(|x, y| x * y, Domain::new(0.0, 2.0, 0.0, 4.0), 2, 2);
+
+    // Last vertex is the corner (x, y) = (2, 4), i.e. world (2, 8, -4) with uv (1, 1).
+    let corner = surface.vertex(surface.vertex_count() - 1);
+    assert_eq!(&corner[0..5], &[2.0, 8.0, -4.0, 1.0, 1.0]);
+}
+
+#[test]
+pub fn surface_normals_of_a_plane() {
+    // z = x has the normal (-1, 0, 1) / sqrt(2), which is (-1, 1, 0) / sqrt(2) in the world.
+    let analytic  = create_surface_with_gradient(|x, _| x, |_, _| (1.0, 0.0), Domain::new(-1.0, 1.0, -1.0, 1.0), 2, 2);
+    let numerical = create_surface(|x, _| x, Domain::new(-1.0, 1.0, -1.0, 1.0), 2, 2);
+
+    let expected = [-1.0 / 2f32.sqrt(), 1.0 / 2f32.sqrt(), 0.0];
+    for surface in &[analytic, numerical] {
+        for i in 0..surface.vertex_count() {
+            let normal = &surface.vertex(i)[5..8];
+            for (a, b) in normal.iter().zip(expected.iter()) {
+                assert!((a - b).abs() < 1e-5, "{:?} != {:?}", normal, expected);
+            }
+        }
+    }
+}
+
+#[test]
+pub fn surface_skips_undefined_quads() {
+    // ln(x) is undefined at x = -1 and x = 0, so only the quad between x = 1 and x = 2 is kept.
+    let surface = create_surface(|x, _| x.ln(), Domain::new(-1.0, 2.0, 0.0, 1.0), 3, 1);
+    assert_eq!(surface.indices.len(), 6);
+}
+
+#[test]
+pub fn surface_index_buffer_size() {
+    let small = create_surface(|_, _| 0.0, Domain::new(0.0, 1.0, 0.0, 1.0), 255, 255);
+    let large = create_surface(|_, _| 0.0, Domain::new(0.0, 1.0, 0.0, 1.0), 256, 256);
+
+    assert!(matches!(small.index_buffer(), IndexBuffer::U16(_)));
+    assert!(matches!(large.index_buffer(), IndexBuffer::U32(_)));
+    assert_eq!(large.index_buffer().len(), large.indices.len());
+}
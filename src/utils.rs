@@ -1,23 +1,34 @@
-use glm::Mat4;
+use crate::math::Matrix4;
 use super::log;
+use crate::mesh::IndexBuffer;
+use serde::Serialize;
+use wasm_bindgen::JsValue;
 
 
-pub fn print_matrix(m: &Mat4) {
+pub fn print_matrix(m: &Matrix4) {
+    let [r1, r2, r3, r4] = m.to_rows();
     log(format!("\
 Matrix(\n\
 \t{:.2}, {:.2}, {:.2}, {:.2},\n\
 \t{:.2}, {:.2}, {:.2}, {:.2},\n\
 \t{:.2}, {:.2}, {:.2}, {:.2},\n\
 \t{:.2}, {:.2}, {:.2}, {:.2},\n\
-)", m.m11, m.m12, m.m13, m.m14,
-                m.m21, m.m22, m.m23, m.m24,
-                m.m31, m.m32, m.m33, m.m34,
-                m.m41, m.m42, m.m43, m.m44
+)", r1[0], r1[1], r1[2], r1[3],
+                r2[0], r2[1], r2[2], r2[3],
+                r3[0], r3[1], r3[2], r3[3],
+                r4[0], r4[1], r4[2], r4[3]
     ).as_str());
 }
 
 
 
+/// Converts plain Rust data into a JS value, e.g. an array of objects for the `Client` API. Maps
+/// become plain objects and `None` becomes `null`, like going through JSON.
+pub fn to_js_value<T: Serialize>(value: &T) -> JsValue {
+    value.serialize(&serde_wasm_bindgen::Serializer::json_compatible()).expect("Couldn't serialize value to JS!")
+}
+
+
 pub fn create_grid(width: usize, depth: usize) -> (Vec<f32>, Vec<u16>) {
     let half_width = (width / 2) as i32;
     let half_depth = (depth / 2) as i32;
@@ -53,6 +64,196 @@ pub fn create_grid(width: usize, depth: usize) -> (Vec<f32>, Vec<u16>) {
 }
 
 
+/// Converts a point from the usual math convention (z is up) to the OpenGL world (y is up). The
+/// math y-axis points into the screen, i.e. along -z, which keeps the coordinate system right handed.
+pub fn math_to_world(x: f32, y: f32, z: f32) -> [f32; 3] {
+    [x, z, -y]
+}
+
+
+/// The rectangle [x_min, x_max] x [y_min, y_max] in the math xy-plane.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Domain {
+    pub x_min: f32,
+    pub x_max: f32,
+    pub y_min: f32,
+    pub y_max: f32,
+}
+
+impl Domain {
+    pub fn new(x_min: f32, x_max: f32, y_min: f32, y_max: f32) -> Self {
+        Self { x_min, x_max, y_min, y_max }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.x_max - self.x_min
+    }
+
+    pub fn depth(&self) -> f32 {
+        self.y_max - self.y_min
+    }
+
+    /// Maps texture coordinates (u, v) in [0, 1] x [0, 1] to a point in the domain.
+    pub fn lerp(&self, u: f32, v: f32) -> (f32, f32) {
+        (self.x_min + u * self.width(), self.y_min + v * self.depth())
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        (self.x_min..=self.x_max).contains(&x) && (self.y_min..=self.y_max).contains(&y)
+    }
+}
+
+
+/// Where something drawn for a height field z = f(x, y) goes, e.g. gradient arrows or contours.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Placement {
+    /// Flat in the xy-plane, at z = 0.
+    Plane,
+    /// Lifted onto the surface.
+    Surface,
+    Both,
+}
+
+impl Placement {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "plane"   => Some(Placement::Plane),
+            "surface" => Some(Placement::Surface),
+            "both"    => Some(Placement::Both),
+            _ => None,
+        }
+    }
+
+    pub fn on_plane(&self) -> bool {
+        matches!(self, Placement::Plane | Placement::Both)
+    }
+
+    pub fn on_surface(&self) -> bool {
+        matches!(self, Placement::Surface | Placement::Both)
+    }
+}
+
+
+/// Interleaved vertex data (position, texture coordinate and normal; 8 floats per vertex) and
+/// triangle indices for a sampled surface.
+pub struct SurfaceData {
+    pub vertices: Vec<f32>,
+    pub indices: Vec<u32>,
+}
+
+impl SurfaceData {
+    pub const COMPONENTS_PER_VERTEX: usize = 3 + 2 + 3;
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len() / Self::COMPONENTS_PER_VERTEX
+    }
+
+    /// The position, texture coordinate and normal of a vertex.
+    #[cfg(test)]
+    pub fn vertex(&self, index: usize) -> &[f32] {
+        let start = index * Self::COMPONENTS_PER_VERTEX;
+        &self.vertices[start..start + Self::COMPONENTS_PER_VERTEX]
+    }
+
+    /// The indices as `u16`, if every vertex can be addressed by one.
+    pub fn indices_u16(&self) -> Option<Vec<u16>> {
+        if self.vertex_count() <= (u16::MAX as usize) + 1 {
+            Some(self.indices.iter().map(|&i| i as u16).collect())
+        } else {
+            None
+        }
+    }
+
+    /// The smallest index buffer that can address all the vertices.
+    pub fn index_buffer(&self) -> IndexBuffer {
+        match self.indices_u16() {
+            Some(indices) => IndexBuffer::U16(indices),
+            None => IndexBuffer::U32(self.indices.clone()),
+        }
+    }
+}
+
+
+/// Samples the height field z = f(x, y) on a grid of `resolution_x` by `resolution_y` quads,
+/// with normals computed by central differences.
+pub fn create_surface<F>(f: F, domain: Domain, resolution_x: usize, resolution_y: usize) -> SurfaceData
+    where F: Fn(f32, f32) -> f32
+{
+    // Step relative to the cell size so that the differences are meaningful at every scale.
+    let hx = domain.width() / resolution_x.max(1) as f32 * 0.5;
+    let hy = domain.depth() / resolution_y.max(1) as f32 * 0.5;
+
+    let gradient = |x: f32, y: f32| {
+        let dx = (f(x + hx, y) - f(x - hx, y)) / (2.0 * hx);
+        let dy = (f(x, y + hy) - f(x, y - hy)) / (2.0 * hy);
+        (dx, dy)
+    };
+
+    create_surface_with_gradient(&f, gradient, domain, resolution_x, resolution_y)
+}
+
+/// Same as `create_surface`, but with normals from the analytic gradient (∂f/∂x, ∂f/∂y).
+pub fn create_surface_with_gradient<F, G>(f: F, gradient: G, domain: Domain, resolution_x: usize, resolution_y: usize) -> SurfaceData
+    where F: Fn(f32, f32) -> f32,
+          G: Fn(f32, f32) -> (f32, f32)
+{
+    assert!(resolution_x > 0 && resolution_y > 0, "Resolution must be at least one quad in each direction.");
+
+    let columns = resolution_x + 1;
+    let rows    = resolution_y + 1;
+
+    let mut vertices = Vec::with_capacity(columns * rows * SurfaceData::COMPONENTS_PER_VERTEX);
+    let mut is_finite = Vec::with_capacity(columns * rows);
+
+    for row in 0..rows {
+        for column in 0..columns {
+            let u = column as f32 / resolution_x as f32;
+            let v = row    as f32 / resolution_y as f32;
+            let (x, y) = domain.lerp(u, v);
+
+            let z = f(x, y);
+            let (dx, dy) = gradient(x, y);
+            let valid = z.is_finite();
+
+            // The normal of z = f(x, y) is (-∂f/∂x, -∂f/∂y, 1). Where only the derivatives are
+            // undefined, like at the tip of a cone, the point is kept with the normal straight up.
+            let (z, nx, ny, nz) = if !valid {
+                (0.0, 0.0, 0.0, 1.0)
+            } else if dx.is_finite() && dy.is_finite() {
+                let length = (dx * dx + dy * dy + 1.0).sqrt();
+                (z, -dx / length, -dy / length, 1.0 / length)
+            } else {
+                (z, 0.0, 0.0, 1.0)
+            };
+
+            vertices.extend_from_slice(&math_to_world(x, y, z));
+            vertices.push(u);
+            vertices.push(v);
+            vertices.extend_from_slice(&math_to_world(nx, ny, nz));
+            is_finite.push(valid);
+        }
+    }
+
+    // Two counter-clockwise triangles per quad (seen from above), skipping any that touch a
+    // point where the function is undefined, e.g. ln(x) for x <= 0.
+    let mut indices = Vec::with_capacity(resolution_x * resolution_y * 6);
+    for row in 0..resolution_y {
+        for column in 0..resolution_x {
+            let a = (row * columns + column) as u32;
+            let b = a + 1;
+            let c = a + columns as u32;
+            let d = c + 1;
+
+            if [a, b, c, d].iter().all(|&i| is_finite[i as usize]) {
+                indices.extend_from_slice(&[a, b, d, a, d, c]);
+            }
+        }
+    }
+
+    SurfaceData { vertices, indices }
+}
+
+
 use wasm_bindgen::JsCast;
 macro_rules! array_to_wasm_array {
     ($T:ident, $JSArray:ident, $array:ident) => {
@@ -86,4 +287,8 @@ macro_rules! array_to_wasm_array {
 //     );
 //
 //     js_array
-// }
\ No newline at end of file
+// }
+
+#[cfg(test)]
+#[path = "tests/utils.rs"]
+mod tests;